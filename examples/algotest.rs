@@ -0,0 +1,481 @@
+#!/usr/bin/env cargo script
+
+//! Chunking Algorithm Benchmark (`algotest`)
+//!
+//! Feeds a synthetic corpus through Blaze's chunking engine under a sweep of
+//! configurations and reports, per configuration: throughput (MB/s), chunk
+//! count, mean/min/max/stddev of chunk sizes, and the dedup ratio (unique
+//! bytes after hashing ÷ total input bytes). A second, mutated copy of the
+//! corpus (the original with a block inserted partway through) is chunked
+//! under the same configuration so the number of chunks shared between the
+//! two can be reported too - this is what demonstrates content-defined
+//! chunking's resilience to a localized edit versus fixed-size chunking's
+//! lack of it.
+//!
+//! The sweep covers [`ChunkingConfig::avg_size`] (`--sizes`) crossed with
+//! [`ChunkingStrategy`] (`--strategies`). Blaze's FastCDC implementation
+//! fixes its gear-hash masks and window width as internal constants rather
+//! than exposing them through [`ChunkingConfig`], so unlike `avg_size` they
+//! aren't sweepable from outside the crate; this harness sweeps everything
+//! [`ChunkingConfig`] actually allows a caller to tune.
+//!
+//! Run with: cargo run --example algotest
+//!           cargo run --example algotest -- --sizes 4096,16384 --strategies cdc
+//!           cargo run --example algotest -- --corpus-size 33554432
+//!           cargo run --example algotest -- --export-json algotest.json
+//!           cargo run --example algotest -- --export-csv algotest.csv
+
+use blaze::config::{ChunkingConfig, ChunkingStrategy};
+use blaze::files::{chunk_file_with_config, FileChunk, HashAlgo};
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use tempfile::TempDir;
+
+/// Schema version of the `--export-json` document; see `git_comparison.rs`
+/// for the same convention applied to its own export format.
+const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Default target average chunk sizes swept when `--sizes` isn't given
+const DEFAULT_SIZES: &[usize] = &[4 * 1024, 8 * 1024, 16 * 1024, 64 * 1024];
+
+/// Bytes spliced into the mutated corpus copy to simulate a localized edit
+const DEFAULT_INSERT_SIZE: usize = 4096;
+
+/// A tiny deterministic xorshift64 PRNG, used only to generate reproducible
+/// synthetic corpus bytes - not suitable for anything security-sensitive,
+/// but that's not what it's for here.
+struct Xorshift64(u64);
+
+impl Xorshift64 {
+    fn new(seed: u64) -> Self {
+        Xorshift64(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+/// `size` bytes of deterministic pseudo-random content, seeded by `seed` so
+/// the same corpus is generated on every run
+fn generate_corpus(size: usize, seed: u64) -> Vec<u8> {
+    let mut rng = Xorshift64::new(seed);
+    let mut data = Vec::with_capacity(size);
+    while data.len() < size {
+        data.extend_from_slice(&rng.next_u64().to_le_bytes());
+    }
+    data.truncate(size);
+    data
+}
+
+/// `base` with `insert_len` bytes of fresh pseudo-random content spliced in
+/// partway through - simulating a single localized edit partway through an
+/// otherwise-unchanged file
+fn mutate_with_insertion(base: &[u8], insert_len: usize, seed: u64) -> Vec<u8> {
+    let at = base.len() / 2;
+    let insertion = generate_corpus(insert_len, seed);
+
+    let mut mutated = Vec::with_capacity(base.len() + insert_len);
+    mutated.extend_from_slice(&base[..at]);
+    mutated.extend_from_slice(&insertion);
+    mutated.extend_from_slice(&base[at..]);
+    mutated
+}
+
+/// Mean/min/max/stddev over a set of chunk sizes
+#[derive(Debug, Clone, Serialize)]
+struct ChunkSizeStats {
+    count: usize,
+    mean: f64,
+    min: usize,
+    max: usize,
+    stddev: f64,
+}
+
+impl ChunkSizeStats {
+    fn from_chunks(chunks: &[FileChunk]) -> Self {
+        let sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.size).collect();
+        let count = sizes.len();
+        if count == 0 {
+            return ChunkSizeStats {
+                count: 0,
+                mean: 0.0,
+                min: 0,
+                max: 0,
+                stddev: 0.0,
+            };
+        }
+
+        let mean = sizes.iter().sum::<usize>() as f64 / count as f64;
+        let variance =
+            sizes.iter().map(|&s| (s as f64 - mean).powi(2)).sum::<f64>() / count as f64;
+
+        ChunkSizeStats {
+            count,
+            mean,
+            min: *sizes.iter().min().unwrap(),
+            max: *sizes.iter().max().unwrap(),
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Unique bytes (by chunk hash) ÷ total bytes across `chunks` - 1.0 means no
+/// duplicate chunks at all, lower means more internal redundancy was found
+fn dedup_ratio(chunks: &[FileChunk]) -> f64 {
+    let mut seen = HashSet::new();
+    let mut unique_bytes = 0u64;
+    let mut total_bytes = 0u64;
+
+    for chunk in chunks {
+        total_bytes += chunk.size as u64;
+        if seen.insert(chunk.hash.as_str()) {
+            unique_bytes += chunk.size as u64;
+        }
+    }
+
+    if total_bytes == 0 {
+        1.0
+    } else {
+        unique_bytes as f64 / total_bytes as f64
+    }
+}
+
+/// Number of `a`'s chunks (by hash) that also appear in `b` - how many
+/// chunks survived an edit unchanged
+fn shared_chunk_count(a: &[FileChunk], b: &[FileChunk]) -> usize {
+    let hashes: HashSet<&str> = b.iter().map(|chunk| chunk.hash.as_str()).collect();
+    a.iter()
+        .filter(|chunk| hashes.contains(chunk.hash.as_str()))
+        .count()
+}
+
+fn strategy_name(strategy: ChunkingStrategy) -> &'static str {
+    match strategy {
+        ChunkingStrategy::FastCdc => "FastCDC",
+        ChunkingStrategy::Fixed => "Fixed",
+    }
+}
+
+fn parse_strategy(name: &str) -> Option<ChunkingStrategy> {
+    match name.to_ascii_lowercase().as_str() {
+        "cdc" | "fastcdc" | "fast_cdc" => Some(ChunkingStrategy::FastCdc),
+        "fixed" => Some(ChunkingStrategy::Fixed),
+        _ => None,
+    }
+}
+
+/// One (strategy, avg_size) configuration's results against the base and
+/// mutated corpora
+struct AlgotestResult {
+    strategy: ChunkingStrategy,
+    avg_size: usize,
+    throughput_mb_s: f64,
+    stats: ChunkSizeStats,
+    dedup_ratio: f64,
+    shared_chunks: usize,
+    shared_fraction: f64,
+}
+
+fn run_config(
+    strategy: ChunkingStrategy,
+    avg_size: usize,
+    base_path: &Path,
+    mutated_path: &Path,
+    corpus_bytes: u64,
+) -> std::io::Result<AlgotestResult> {
+    let config = ChunkingConfig {
+        strategy,
+        avg_size,
+        min_size: (avg_size / 4).max(1),
+        max_size: avg_size * 4,
+    };
+
+    let start = Instant::now();
+    let base_chunks = chunk_file_with_config(base_path, HashAlgo::default(), &config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let elapsed = start.elapsed();
+
+    let mutated_chunks = chunk_file_with_config(mutated_path, HashAlgo::default(), &config)
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    let throughput_mb_s = if elapsed.as_secs_f64() > 0.0 {
+        (corpus_bytes as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    let shared_chunks = shared_chunk_count(&base_chunks, &mutated_chunks);
+    let shared_fraction = if base_chunks.is_empty() {
+        0.0
+    } else {
+        shared_chunks as f64 / base_chunks.len() as f64
+    };
+
+    Ok(AlgotestResult {
+        strategy,
+        avg_size,
+        throughput_mb_s,
+        stats: ChunkSizeStats::from_chunks(&base_chunks),
+        dedup_ratio: dedup_ratio(&base_chunks),
+        shared_chunks,
+        shared_fraction,
+    })
+}
+
+fn format_size(bytes: usize) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[unit_index])
+    } else {
+        format!("{:.2} {}", size, UNITS[unit_index])
+    }
+}
+
+fn print_table(results: &[AlgotestResult]) {
+    println!("\n📊 Chunking Algorithm Comparison");
+    println!("─────────────────────────────────────────────────────────────────────────────────────");
+    println!(
+        "{:<8} {:>10} {:>12} {:>10} {:>10} {:>10} {:>10} {:>14}",
+        "Strategy", "Target", "Throughput", "Chunks", "Mean", "Dedup", "Shared", "Shared %"
+    );
+
+    for result in results {
+        println!(
+            "{:<8} {:>10} {:>9.1}MB/s {:>10} {:>10} {:>9.1}% {:>10} {:>13.1}%",
+            strategy_name(result.strategy),
+            format_size(result.avg_size),
+            result.throughput_mb_s,
+            result.stats.count,
+            format_size(result.stats.mean.round() as usize),
+            result.dedup_ratio * 100.0,
+            result.shared_chunks,
+            result.shared_fraction * 100.0,
+        );
+    }
+    println!();
+}
+
+#[derive(Serialize)]
+struct ExportEntry {
+    strategy: String,
+    avg_size: usize,
+    throughput_mb_s: f64,
+    chunk_count: usize,
+    mean_size: f64,
+    min_size: usize,
+    max_size: usize,
+    stddev_size: f64,
+    dedup_ratio: f64,
+    shared_chunks: usize,
+    shared_fraction: f64,
+}
+
+impl From<&AlgotestResult> for ExportEntry {
+    fn from(result: &AlgotestResult) -> Self {
+        ExportEntry {
+            strategy: strategy_name(result.strategy).to_string(),
+            avg_size: result.avg_size,
+            throughput_mb_s: result.throughput_mb_s,
+            chunk_count: result.stats.count,
+            mean_size: result.stats.mean,
+            min_size: result.stats.min,
+            max_size: result.stats.max,
+            stddev_size: result.stats.stddev,
+            dedup_ratio: result.dedup_ratio,
+            shared_chunks: result.shared_chunks,
+            shared_fraction: result.shared_fraction,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportReport {
+    schema_version: u32,
+    entries: Vec<ExportEntry>,
+}
+
+fn export_json(results: &[AlgotestResult], path: &Path) -> std::io::Result<()> {
+    let report = ExportReport {
+        schema_version: EXPORT_SCHEMA_VERSION,
+        entries: results.iter().map(ExportEntry::from).collect(),
+    };
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, json)
+}
+
+fn export_csv(results: &[AlgotestResult], path: &Path) -> std::io::Result<()> {
+    let mut out = String::from(
+        "strategy,avg_size,throughput_mb_s,chunk_count,mean_size,min_size,max_size,stddev_size,dedup_ratio,shared_chunks,shared_fraction\n",
+    );
+
+    for result in results {
+        out.push_str(&format!(
+            "{},{},{:.3},{},{:.1},{},{},{:.1},{:.4},{},{:.4}\n",
+            strategy_name(result.strategy),
+            result.avg_size,
+            result.throughput_mb_s,
+            result.stats.count,
+            result.stats.mean,
+            result.stats.min,
+            result.stats.max,
+            result.stats.stddev,
+            result.dedup_ratio,
+            result.shared_chunks,
+            result.shared_fraction,
+        ));
+    }
+
+    std::fs::write(path, out)
+}
+
+struct Args {
+    corpus_size: usize,
+    insert_size: usize,
+    sizes: Vec<usize>,
+    strategies: Vec<ChunkingStrategy>,
+    export_json: Option<String>,
+    export_csv: Option<String>,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut corpus_size = 8 * 1024 * 1024;
+        let mut insert_size = DEFAULT_INSERT_SIZE;
+        let mut sizes = None;
+        let mut strategies = None;
+        let mut export_json = None;
+        let mut export_csv = None;
+
+        let args: Vec<String> = std::env::args().collect();
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--corpus-size" => {
+                    corpus_size = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(corpus_size);
+                    i += 2;
+                }
+                "--insert-size" => {
+                    insert_size = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(insert_size);
+                    i += 2;
+                }
+                "--sizes" => {
+                    sizes = args.get(i + 1).map(|v| {
+                        v.split(',')
+                            .filter_map(|part| part.trim().parse().ok())
+                            .collect::<Vec<usize>>()
+                    });
+                    i += 2;
+                }
+                "--strategies" => {
+                    strategies = args.get(i + 1).map(|v| {
+                        v.split(',')
+                            .filter_map(|part| parse_strategy(part.trim()))
+                            .collect::<Vec<ChunkingStrategy>>()
+                    });
+                    i += 2;
+                }
+                "--export-json" => {
+                    export_json = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--export-csv" => {
+                    export_csv = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                _ => {
+                    i += 1;
+                }
+            }
+        }
+
+        Args {
+            corpus_size,
+            insert_size,
+            sizes: sizes.filter(|s| !s.is_empty()).unwrap_or_else(|| DEFAULT_SIZES.to_vec()),
+            strategies: strategies
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| vec![ChunkingStrategy::FastCdc, ChunkingStrategy::Fixed]),
+            export_json,
+            export_csv,
+        }
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+
+    println!("Blaze Chunking Algorithm Benchmark (algotest)");
+    println!("==============================================");
+    println!(
+        "Corpus: {} (insertion: {})",
+        format_size(args.corpus_size),
+        format_size(args.insert_size)
+    );
+
+    let base = generate_corpus(args.corpus_size, 0x5EED_0001);
+    let mutated = mutate_with_insertion(&base, args.insert_size, 0x5EED_0002);
+
+    let temp_dir = TempDir::new()?;
+    let base_path = temp_dir.path().join("base.bin");
+    let mutated_path = temp_dir.path().join("mutated.bin");
+    std::fs::File::create(&base_path)?.write_all(&base)?;
+    std::fs::File::create(&mutated_path)?.write_all(&mutated)?;
+
+    let mut results = Vec::new();
+    for &strategy in &args.strategies {
+        for &avg_size in &args.sizes {
+            println!(
+                "🔹 Chunking with {} @ target {}",
+                strategy_name(strategy),
+                format_size(avg_size)
+            );
+            results.push(run_config(
+                strategy,
+                avg_size,
+                &base_path,
+                &mutated_path,
+                args.corpus_size as u64,
+            )?);
+        }
+    }
+
+    print_table(&results);
+
+    if let Some(path) = &args.export_json {
+        export_json(&results, Path::new(path))?;
+        println!("📄 Exported JSON to {}", path);
+    }
+    if let Some(path) = &args.export_csv {
+        export_csv(&results, Path::new(path))?;
+        println!("📄 Exported CSV to {}", path);
+    }
+
+    println!("\n🏁 algotest completed!");
+
+    Ok(())
+}
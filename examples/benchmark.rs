@@ -3,16 +3,27 @@
 //! FastVC Benchmark Example
 //!
 //! This example demonstrates FastVC performance by creating various types of files
-//! and measuring operations like add, commit, and checkout.
+//! and measuring operations like add, commit, and checkout. Each benchmark runs a
+//! configurable warmup phase plus N measured iterations (a fresh temp repo per
+//! iteration) and reports min/median/mean/p95 instead of a single noisy sample.
 //!
 //! Run with: cargo run --example benchmark
+//!           cargo run --example benchmark -- --iterations 10 --warmup 2
+//!           cargo run --example benchmark -- --save-baseline baseline.json
+//!           cargo run --example benchmark -- --baseline baseline.json
 
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::time::{Duration, Instant};
 use tempfile::TempDir;
 
+/// Fraction by which a benchmark's median may exceed its baseline before it's
+/// flagged as a regression.
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
 // Simple benchmark structure
 struct Benchmark {
     name: String,
@@ -33,15 +44,181 @@ impl Benchmark {
         }
     }
 
-    fn run(&self, temp_dir: &Path) -> std::io::Result<Duration> {
-        println!("Setting up benchmark: {}", self.name);
-        (self.setup)(temp_dir)?;
+    /// Set up a fresh temp repo and time a single run of the operation.
+    fn run_once(&self) -> std::io::Result<Duration> {
+        let temp_dir = TempDir::new()?;
+        let temp_path = temp_dir.path();
+        (self.setup)(temp_path)?;
+        (self.operation)(temp_path)
+    }
+
+    /// Run `warmup` untimed iterations to prime caches/filesystem, then
+    /// `iterations` measured iterations, and reduce the measured samples to
+    /// summary statistics.
+    fn run(&self, warmup: usize, iterations: usize) -> std::io::Result<BenchmarkStats> {
+        println!(
+            "Running benchmark: {} ({} warmup + {} iterations)",
+            self.name, warmup, iterations
+        );
+
+        for _ in 0..warmup {
+            self.run_once()?;
+        }
+
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            samples.push(self.run_once()?);
+        }
+
+        println!(
+            "Completed: {} ({} samples)",
+            self.name,
+            samples.len()
+        );
+
+        Ok(BenchmarkStats::from_samples(&self.name, &samples))
+    }
+}
+
+/// Summary statistics for a benchmark's measured iterations, in seconds so
+/// they round-trip cleanly through JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BenchmarkStats {
+    name: String,
+    samples: usize,
+    min_secs: f64,
+    median_secs: f64,
+    mean_secs: f64,
+    p95_secs: f64,
+}
+
+impl BenchmarkStats {
+    fn from_samples(name: &str, samples: &[Duration]) -> Self {
+        let mut secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        secs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let min_secs = secs.first().copied().unwrap_or(0.0);
+        let mean_secs = secs.iter().sum::<f64>() / secs.len().max(1) as f64;
+        let median_secs = percentile(&secs, 0.50);
+        let p95_secs = percentile(&secs, 0.95);
+
+        BenchmarkStats {
+            name: name.to_string(),
+            samples: secs.len(),
+            min_secs,
+            median_secs,
+            mean_secs,
+            p95_secs,
+        }
+    }
+}
+
+/// Percentile over an already-sorted slice of seconds: the sample at index
+/// `(len - 1) * pct`, rounded to the nearest index (no interpolation).
+fn percentile(sorted_secs: &[f64], pct: f64) -> f64 {
+    if sorted_secs.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted_secs.len() as f64 - 1.0) * pct).round() as usize;
+    sorted_secs[rank.min(sorted_secs.len() - 1)]
+}
+
+/// A set of benchmark results keyed by benchmark name, as saved to or loaded
+/// from a `--baseline`/`--save-baseline` JSON file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BaselineFile {
+    results: HashMap<String, BenchmarkStats>,
+}
+
+impl BaselineFile {
+    fn load(path: &Path) -> std::io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = File::create(path)?;
+        file.write_all(json.as_bytes())
+    }
+}
+
+/// Compare `current` against `baseline` and print a regression warning for
+/// any benchmark whose median exceeds the baseline median by more than
+/// [`REGRESSION_THRESHOLD`]. Returns the number of regressions found.
+fn check_regressions(current: &[BenchmarkStats], baseline: &BaselineFile) -> usize {
+    let mut regressions = 0;
+
+    for stats in current {
+        if let Some(base) = baseline.results.get(&stats.name) {
+            let change = (stats.median_secs - base.median_secs) / base.median_secs.max(f64::EPSILON);
+            if change > REGRESSION_THRESHOLD {
+                regressions += 1;
+                println!(
+                    "  REGRESSION: {:<35} {:.3}s -> {:.3}s ({:+.1}%)",
+                    stats.name,
+                    base.median_secs,
+                    stats.median_secs,
+                    change * 100.0
+                );
+            }
+        }
+    }
+
+    regressions
+}
+
+struct Args {
+    warmup: usize,
+    iterations: usize,
+    baseline: Option<String>,
+    save_baseline: Option<String>,
+}
 
-        println!("Running benchmark: {}", self.name);
-        let duration = (self.operation)(temp_dir)?;
+impl Args {
+    fn parse() -> Self {
+        let mut warmup = 1;
+        let mut iterations = 5;
+        let mut baseline = None;
+        let mut save_baseline = None;
+
+        let args: Vec<String> = std::env::args().collect();
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--warmup" => {
+                    warmup = args.get(i + 1).and_then(|v| v.parse().ok()).unwrap_or(warmup);
+                    i += 2;
+                }
+                "--iterations" => {
+                    iterations = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(iterations);
+                    i += 2;
+                }
+                "--baseline" => {
+                    baseline = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--save-baseline" => {
+                    save_baseline = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                _ => {
+                    i += 1;
+                }
+            }
+        }
 
-        println!("Completed: {} in {:?}", self.name, duration);
-        Ok(duration)
+        Args {
+            warmup,
+            iterations,
+            baseline,
+            save_baseline,
+        }
     }
 }
 
@@ -214,38 +391,9 @@ fn benchmark_checkout() -> Benchmark {
     )
 }
 
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
-    let mut size = bytes as f64;
-    let mut unit_index = 0;
-
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
-    }
-
-    if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
-    } else {
-        format!("{:.2} {}", size, UNITS[unit_index])
-    }
-}
-
-fn calculate_dir_size(dir: &Path) -> std::io::Result<u64> {
-    let mut total = 0;
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let metadata = entry.metadata()?;
-        if metadata.is_file() {
-            total += metadata.len();
-        } else if metadata.is_dir() {
-            total += calculate_dir_size(&entry.path())?;
-        }
-    }
-    Ok(total)
-}
-
 fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+
     println!("FastVC Performance Benchmark");
     println!("============================");
 
@@ -279,21 +427,10 @@ fn main() -> std::io::Result<()> {
     let mut results = Vec::new();
 
     for benchmark in benchmarks {
-        let temp_dir = TempDir::new()?;
-        let temp_path = temp_dir.path();
-
-        match benchmark.run(temp_path) {
-            Ok(duration) => {
-                results.push((benchmark.name.clone(), duration));
-
-                // Show repository stats if .fastvc exists
-                if temp_path.join(".fastvc").exists() {
-                    if let Ok(repo_size) = calculate_dir_size(&temp_path.join(".fastvc")) {
-                        println!("  Repository size: {}", format_size(repo_size));
-                    }
-                }
-
+        match benchmark.run(args.warmup, args.iterations) {
+            Ok(stats) => {
                 println!();
+                results.push(stats);
             }
             Err(e) => {
                 eprintln!("Benchmark '{}' failed: {}", benchmark.name, e);
@@ -305,40 +442,70 @@ fn main() -> std::io::Result<()> {
     // Print summary
     println!("Benchmark Results Summary");
     println!("========================");
-
-    let mut total_time = Duration::new(0, 0);
-    for (name, duration) in &results {
-        println!("{:<35} {:>10.3}s", name, duration.as_secs_f64());
-        total_time += *duration;
+    println!(
+        "{:<35} {:>8} {:>8} {:>8} {:>8}",
+        "Benchmark", "min", "median", "mean", "p95"
+    );
+
+    for stats in &results {
+        println!(
+            "{:<35} {:>7.3}s {:>7.3}s {:>7.3}s {:>7.3}s",
+            stats.name, stats.min_secs, stats.median_secs, stats.mean_secs, stats.p95_secs
+        );
     }
 
-    println!("{:<35} {:>10.3}s", "TOTAL", total_time.as_secs_f64());
-
     // Performance metrics
     println!("\nPerformance Analysis");
     println!("===================");
 
-    if let Some((_, init_time)) = results
-        .iter()
-        .find(|(name, _)| name.contains("Initialization"))
-    {
-        println!("Repository init overhead: {:.3}s", init_time.as_secs_f64());
+    if let Some(init_stats) = results.iter().find(|s| s.name.contains("Initialization")) {
+        println!("Repository init overhead: {:.3}s", init_stats.median_secs);
     }
 
-    if let Some((_, small_files_time)) = results
-        .iter()
-        .find(|(name, _)| name.contains("Small Files"))
-    {
-        let files_per_sec = 100.0 / small_files_time.as_secs_f64();
+    if let Some(small_files_stats) = results.iter().find(|s| s.name.contains("Small Files")) {
+        let files_per_sec = 100.0 / small_files_stats.median_secs;
         println!("Small files throughput: {:.1} files/sec", files_per_sec);
     }
 
-    if let Some((_, large_file_time)) = results.iter().find(|(name, _)| name.contains("Large File"))
-    {
-        let mb_per_sec = 10.0 / large_file_time.as_secs_f64();
+    if let Some(large_file_stats) = results.iter().find(|s| s.name.contains("Large File")) {
+        let mb_per_sec = 10.0 / large_file_stats.median_secs;
         println!("Large file throughput: {:.1} MB/sec", mb_per_sec);
     }
 
+    // Baseline comparison and/or save
+    if let Some(baseline_path) = &args.baseline {
+        match BaselineFile::load(Path::new(baseline_path)) {
+            Ok(baseline) => {
+                println!("\nBaseline Comparison ({})", baseline_path);
+                println!("=======================");
+                let regressions = check_regressions(&results, &baseline);
+                if regressions == 0 {
+                    println!("  No regressions detected.");
+                } else {
+                    println!(
+                        "\n{} benchmark(s) regressed by more than {:.0}%",
+                        regressions,
+                        REGRESSION_THRESHOLD * 100.0
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to load baseline '{}': {}", baseline_path, e);
+            }
+        }
+    }
+
+    if let Some(save_path) = &args.save_baseline {
+        let baseline = BaselineFile {
+            results: results
+                .iter()
+                .map(|s| (s.name.clone(), s.clone()))
+                .collect(),
+        };
+        baseline.save(Path::new(save_path))?;
+        println!("\nSaved baseline to {}", save_path);
+    }
+
     println!("\nBenchmark completed successfully!");
     Ok(())
 }
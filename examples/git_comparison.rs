@@ -2,34 +2,198 @@
 
 //! Blaze vs Git Performance Comparison
 //!
-//! This benchmark compares Blaze against Git for various operations
-//! to provide objective performance analysis.
+//! This benchmark runs an arbitrary list of named version-control tools
+//! (Blaze, Git, and whatever else is registered in [`default_tools`])
+//! through the same set of operations to provide objective performance
+//! analysis. Each operation runs a configurable number of warmup iterations
+//! (discarded) followed by N measured iterations against a freshly reset
+//! repo, so the reported numbers are summary statistics rather than a
+//! single noisy sample. Results are ranked relative to a `--reference` tool
+//! (the fastest tool, by default) rather than always against Git.
 //!
 //! Run with: cargo run --example git_comparison
-
+//!           cargo run --example git_comparison -- --warmup 2 --iterations 10
+//!           cargo run --example git_comparison -- --strict
+//!           cargo run --example git_comparison -- --reference Git
+//!           cargo run --example git_comparison -- --export-json results.json
+//!           cargo run --example git_comparison -- --export-csv results.csv
+//!           cargo run --example git_comparison -- --export-markdown results.md
+//!           cargo run --example git_comparison -- --save-baseline main
+//!           cargo run --example git_comparison -- --baseline main
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
+/// Directory baselines are stored under, relative to wherever the benchmark
+/// is run from (normally the Blaze repo root)
+const BASELINE_DIR: &str = ".blaze-bench";
+
+/// Default regression thresholds, as fractions: a benchmark regresses if
+/// mean time grows by more than 10% or storage size grows by more than 5%
+/// against its `--baseline`
+const DEFAULT_MAX_TIME_REGRESSION: f64 = 0.10;
+const DEFAULT_MAX_SIZE_REGRESSION: f64 = 0.05;
+
+/// Summary statistics over a tool's per-iteration timings for one operation,
+/// plus MAD-based outlier flagging so a single noisy run (cold cache,
+/// scheduler jitter) doesn't silently dominate a one-shot `Instant::now()`
+/// reading the way the original single-sample version did.
+#[derive(Debug, Clone)]
+struct SampleStats {
+    samples: Vec<Duration>,
+    mean: Duration,
+    median: Duration,
+    stddev: Duration,
+    min: Duration,
+    max: Duration,
+    /// Samples whose distance from the median exceeds `3 * 1.4826 * MAD`
+    outliers: usize,
+}
+
+impl SampleStats {
+    /// Reduce `samples` to summary statistics. When `strict` is set,
+    /// samples flagged as outliers are excluded from `mean`/`stddev` (but
+    /// still counted in `outliers` and still reflected in `min`/`max`) -
+    /// otherwise every sample counts toward the mean and the outlier count
+    /// is purely informational.
+    fn from_samples(samples: Vec<Duration>, strict: bool) -> Self {
+        assert!(
+            !samples.is_empty(),
+            "SampleStats requires at least one sample"
+        );
+
+        let mut secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        secs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let median_secs = median_of_sorted(&secs);
+
+        let mut abs_deviations: Vec<f64> = secs.iter().map(|s| (s - median_secs).abs()).collect();
+        abs_deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mad = median_of_sorted(&abs_deviations);
+        let outlier_threshold = 3.0 * 1.4826 * mad;
+
+        let is_outlier: Vec<bool> = secs
+            .iter()
+            .map(|s| (s - median_secs).abs() > outlier_threshold)
+            .collect();
+        let outliers = is_outlier.iter().filter(|flagged| **flagged).count();
+
+        let kept: Vec<f64> = if strict && outliers > 0 && outliers < secs.len() {
+            secs.iter()
+                .zip(&is_outlier)
+                .filter(|(_, flagged)| !**flagged)
+                .map(|(s, _)| *s)
+                .collect()
+        } else {
+            secs.clone()
+        };
+
+        let mean_secs = kept.iter().sum::<f64>() / kept.len() as f64;
+        let variance = kept.iter().map(|s| (s - mean_secs).powi(2)).sum::<f64>() / kept.len() as f64;
+        let stddev_secs = variance.sqrt();
+
+        SampleStats {
+            samples,
+            mean: Duration::from_secs_f64(mean_secs),
+            median: Duration::from_secs_f64(median_secs),
+            stddev: Duration::from_secs_f64(stddev_secs),
+            min: Duration::from_secs_f64(*secs.first().unwrap()),
+            max: Duration::from_secs_f64(*secs.last().unwrap()),
+            outliers,
+        }
+    }
+}
+
+/// Median of an already-sorted slice of seconds
+fn median_of_sorted(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n == 0 {
+        return 0.0;
+    }
+    if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
+/// One tool's result for a single [`BenchmarkResult`]'s operation
+struct ToolResult {
+    name: String,
+    stats: SampleStats,
+    size: u64,
+}
+
 struct BenchmarkResult {
     operation: String,
-    blaze_time: Duration,
-    git_time: Duration,
-    blaze_size: u64,
-    git_size: u64,
+    tools: Vec<ToolResult>,
 }
 
 impl BenchmarkResult {
-    fn speedup(&self) -> f64 {
-        self.git_time.as_secs_f64() / self.blaze_time.as_secs_f64()
+    /// `tools`, sorted by mean time ascending, each annotated with its
+    /// speed and uncertainty relative to `reference_name` (or, if `None` or
+    /// not found among this operation's tools, the fastest tool here).
+    /// `relative_speed_stddev` propagates both tools' measurement noise via
+    /// the standard ratio-of-means formula.
+    fn ranked(&self, reference_name: Option<&str>) -> Vec<RankedTool<'_>> {
+        let reference = reference_name
+            .and_then(|name| {
+                self.tools
+                    .iter()
+                    .find(|tool| tool.name.eq_ignore_ascii_case(name))
+            })
+            .unwrap_or_else(|| {
+                self.tools
+                    .iter()
+                    .min_by_key(|tool| tool.stats.mean)
+                    .expect("a benchmark result always has at least one tool")
+            });
+
+        let ref_mean = reference.stats.mean.as_secs_f64();
+        let ref_stddev = reference.stats.stddev.as_secs_f64();
+        let reference_name = reference.name.clone();
+
+        let mut ranked: Vec<RankedTool> = self
+            .tools
+            .iter()
+            .map(|tool| {
+                let mean = tool.stats.mean.as_secs_f64();
+                let stddev = tool.stats.stddev.as_secs_f64();
+                let relative_speed = if ref_mean > 0.0 { mean / ref_mean } else { 0.0 };
+                let relative_speed_stddev = if mean > 0.0 && ref_mean > 0.0 {
+                    relative_speed
+                        * ((stddev / mean).powi(2) + (ref_stddev / ref_mean).powi(2)).sqrt()
+                } else {
+                    0.0
+                };
+
+                RankedTool {
+                    tool,
+                    relative_speed,
+                    relative_speed_stddev,
+                    is_reference: tool.name == reference_name,
+                }
+            })
+            .collect();
+
+        ranked.sort_by_key(|ranked| ranked.tool.stats.mean);
+        ranked
     }
+}
 
-    fn size_ratio(&self) -> f64 {
-        self.blaze_size as f64 / self.git_size as f64
-    }
+/// A [`ToolResult`] annotated with its speed relative to an operation's
+/// reference tool, as computed by [`BenchmarkResult::ranked`]
+struct RankedTool<'a> {
+    tool: &'a ToolResult,
+    relative_speed: f64,
+    relative_speed_stddev: f64,
+    is_reference: bool,
 }
 
 fn format_duration(duration: Duration) -> String {
@@ -41,6 +205,45 @@ fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// "mean ± stddev", with an outlier count suffix when any sample was flagged
+fn format_mean_stddev(stats: &SampleStats) -> String {
+    let base = format!(
+        "{} ± {}",
+        format_duration(stats.mean),
+        format_duration(stats.stddev)
+    );
+    if stats.outliers > 0 {
+        format!(
+            "{} ({} outlier{})",
+            base,
+            stats.outliers,
+            if stats.outliers == 1 { "" } else { "s" }
+        )
+    } else {
+        base
+    }
+}
+
+/// "1.00 (reference)" for the reference row, otherwise "Nx ± U slower/faster"
+fn format_relative_speed(ranked: &RankedTool) -> String {
+    if ranked.is_reference {
+        return "1.00 (reference)".to_string();
+    }
+
+    if ranked.relative_speed >= 1.0 {
+        format!(
+            "{:.1}x ± {:.1} slower",
+            ranked.relative_speed, ranked.relative_speed_stddev
+        )
+    } else if ranked.relative_speed > 0.0 {
+        let inverse = 1.0 / ranked.relative_speed;
+        let inverse_stddev = ranked.relative_speed_stddev / ranked.relative_speed.powi(2);
+        format!("{:.1}x ± {:.1} faster", inverse, inverse_stddev)
+    } else {
+        "n/a".to_string()
+    }
+}
+
 fn format_size(bytes: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
     let mut size = bytes as f64;
@@ -76,394 +279,690 @@ fn calculate_dir_size(path: &Path) -> std::io::Result<u64> {
     Ok(total)
 }
 
-struct TestRepo {
-    #[allow(dead_code)]
-    temp_dir: TempDir,
-    path: PathBuf,
-    blaze_binary: PathBuf,
+/// Schema version of the `--export-json` document. Bump this whenever a
+/// field is removed or changes meaning, so downstream tooling can detect
+/// an incompatible change; purely additive fields don't need a bump.
+const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Host and provenance info stamped onto every export so a result can be
+/// traced back to the machine and Blaze build that produced it
+#[derive(Debug, Clone, Serialize)]
+struct ExportMetadata {
+    hostname: String,
+    timestamp_unix_secs: u64,
+    blaze_commit: Option<String>,
 }
 
-impl TestRepo {
-    fn new() -> std::io::Result<Self> {
-        let temp_dir = TempDir::new()?;
-        let path = temp_dir.path().to_path_buf();
-        let current_dir = std::env::current_dir()?;
-        let blaze_binary = current_dir.join("target/release/blaze");
-
-        Ok(Self {
-            temp_dir,
-            path,
-            blaze_binary,
-        })
-    }
+/// [`SampleStats`], flattened to plain seconds so it serializes without a
+/// custom `Duration` impl
+#[derive(Debug, Clone, Serialize)]
+struct ExportSampleStats {
+    samples_secs: Vec<f64>,
+    mean_secs: f64,
+    median_secs: f64,
+    stddev_secs: f64,
+    min_secs: f64,
+    max_secs: f64,
+    outliers: usize,
+}
 
-    fn create_file(&self, relative_path: &str, content: &[u8]) -> std::io::Result<()> {
-        let file_path = self.path.join(relative_path);
-        if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent)?;
+impl From<&SampleStats> for ExportSampleStats {
+    fn from(stats: &SampleStats) -> Self {
+        ExportSampleStats {
+            samples_secs: stats.samples.iter().map(Duration::as_secs_f64).collect(),
+            mean_secs: stats.mean.as_secs_f64(),
+            median_secs: stats.median.as_secs_f64(),
+            stddev_secs: stats.stddev.as_secs_f64(),
+            min_secs: stats.min.as_secs_f64(),
+            max_secs: stats.max.as_secs_f64(),
+            outliers: stats.outliers,
         }
-        fs::write(file_path, content)?;
-        Ok(())
     }
+}
 
-    fn run_blaze(&self, args: &[&str]) -> std::io::Result<Duration> {
-        let start = Instant::now();
-        let output = Command::new(&self.blaze_binary)
-            .current_dir(&self.path)
-            .args(args)
-            .output()?;
+/// One tool's exported result for one operation, mirroring [`RankedTool`]
+#[derive(Debug, Clone, Serialize)]
+struct ExportTool {
+    name: String,
+    stats: ExportSampleStats,
+    size_bytes: u64,
+    relative_speed: f64,
+    relative_speed_stddev: f64,
+    is_reference: bool,
+}
 
-        let duration = start.elapsed();
+#[derive(Debug, Clone, Serialize)]
+struct ExportOperation {
+    operation: String,
+    tools: Vec<ExportTool>,
+}
 
-        if !output.status.success() {
-            return Err(std::io::Error::other(format!(
-                "FastVC command failed: {:?}",
-                args
-            )));
-        }
+/// Full `--export-json`/`--export-csv` document for one benchmark run
+#[derive(Debug, Clone, Serialize)]
+struct ExportReport {
+    schema_version: u32,
+    metadata: ExportMetadata,
+    operations: Vec<ExportOperation>,
+}
 
-        Ok(duration)
+fn hostname() -> String {
+    Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The Blaze repository's own current commit, i.e. which build of Blaze
+/// produced this benchmark run - not anything to do with the temp repos
+/// the benchmarks create and tear down
+fn blaze_git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if hash.is_empty() {
+        None
+    } else {
+        Some(hash)
     }
+}
 
-    fn run_git(&self, args: &[&str]) -> std::io::Result<Duration> {
-        let start = Instant::now();
-        let output = Command::new("git")
-            .current_dir(&self.path)
-            .args(args)
-            .output()?;
+fn build_export_report(results: &[BenchmarkResult], reference: Option<&str>) -> ExportReport {
+    let timestamp_unix_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
 
-        let duration = start.elapsed();
+    let operations = results
+        .iter()
+        .map(|result| ExportOperation {
+            operation: result.operation.clone(),
+            tools: result
+                .ranked(reference)
+                .into_iter()
+                .map(|ranked| ExportTool {
+                    name: ranked.tool.name.clone(),
+                    stats: ExportSampleStats::from(&ranked.tool.stats),
+                    size_bytes: ranked.tool.size,
+                    relative_speed: ranked.relative_speed,
+                    relative_speed_stddev: ranked.relative_speed_stddev,
+                    is_reference: ranked.is_reference,
+                })
+                .collect(),
+        })
+        .collect();
+
+    ExportReport {
+        schema_version: EXPORT_SCHEMA_VERSION,
+        metadata: ExportMetadata {
+            hostname: hostname(),
+            timestamp_unix_secs,
+            blaze_commit: blaze_git_commit(),
+        },
+        operations,
+    }
+}
 
-        if !output.status.success() {
-            return Err(std::io::Error::other(format!(
-                "Git command failed: {:?}",
-                args
-            )));
-        }
+fn export_json(report: &ExportReport, path: &Path) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fs::write(path, json)
+}
 
-        Ok(duration)
+/// `,`/`"`/newline-safe quoting for a single CSV field
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
     }
+}
 
-    fn get_blaze_size(&self) -> std::io::Result<u64> {
-        calculate_dir_size(&self.path.join(".blaze"))
-    }
+fn export_csv(report: &ExportReport, path: &Path) -> std::io::Result<()> {
+    let mut out = String::from(
+        "operation,tool,mean_secs,stddev_secs,min_secs,max_secs,outliers,size_bytes,relative_speed,relative_speed_stddev,is_reference\n",
+    );
 
-    fn get_git_size(&self) -> std::io::Result<u64> {
-        calculate_dir_size(&self.path.join(".git"))
+    for operation in &report.operations {
+        for tool in &operation.tools {
+            out.push_str(&format!(
+                "{},{},{:.6},{:.6},{:.6},{:.6},{},{},{:.4},{:.4},{}\n",
+                csv_escape(&operation.operation),
+                csv_escape(&tool.name),
+                tool.stats.mean_secs,
+                tool.stats.stddev_secs,
+                tool.stats.min_secs,
+                tool.stats.max_secs,
+                tool.stats.outliers,
+                tool.size_bytes,
+                tool.relative_speed,
+                tool.relative_speed_stddev,
+                tool.is_reference,
+            ));
+        }
     }
-}
 
-fn benchmark_init() -> std::io::Result<BenchmarkResult> {
-    println!("🔹 Benchmarking: Repository Initialization");
+    fs::write(path, out)
+}
 
-    let blaze_repo = TestRepo::new()?;
-    let git_repo = TestRepo::new()?;
+/// GitHub-flavored Markdown tables, one per operation, with the same
+/// columns (and the same `format_mean_stddev`/`format_size`/
+/// `format_relative_speed` renderings) as [`print_results_table`] so a
+/// result can be pasted straight into a PR comment
+fn export_markdown(results: &[BenchmarkResult], reference: Option<&str>, path: &Path) -> std::io::Result<()> {
+    let mut out = String::from("# Blaze vs Git Performance Comparison\n\n");
 
-    // Benchmark Blaze init
-    let blaze_time = blaze_repo.run_blaze(&["init"])?;
-    let blaze_size = blaze_repo.get_blaze_size()?;
+    for result in results {
+        out.push_str(&format!("## {}\n\n", result.operation));
+        out.push_str("| Tool | Mean ± σ | Size | Relative |\n");
+        out.push_str("|---|---|---|---|\n");
+        for ranked in result.ranked(reference) {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                ranked.tool.name,
+                format_mean_stddev(&ranked.tool.stats),
+                format_size(ranked.tool.size),
+                format_relative_speed(&ranked)
+            ));
+        }
+        out.push('\n');
+    }
 
-    // Benchmark Git init
-    let git_time = git_repo.run_git(&["init"])?;
-    let git_size = git_repo.get_git_size()?;
+    fs::write(path, out)
+}
 
-    Ok(BenchmarkResult {
-        operation: "Repository Init".to_string(),
-        blaze_time,
-        git_time,
-        blaze_size,
-        git_size,
-    })
+/// A saved baseline's numbers for one tool on one operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaselineEntry {
+    mean_secs: f64,
+    size_bytes: u64,
 }
 
-fn benchmark_small_files() -> std::io::Result<BenchmarkResult> {
-    println!("🔹 Benchmarking: 100 Small Files (1KB each)");
+/// A named, persisted set of results (`.blaze-bench/<name>.json`) to
+/// compare future runs against. Keyed by operation name, then tool name,
+/// alongside the Blaze commit the baseline was captured at.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BaselineFile {
+    commit: Option<String>,
+    results: HashMap<String, HashMap<String, BaselineEntry>>,
+}
 
-    let blaze_repo = TestRepo::new()?;
-    let git_repo = TestRepo::new()?;
+impl BaselineFile {
+    fn path_for(name: &str) -> PathBuf {
+        Path::new(BASELINE_DIR).join(format!("{}.json", name))
+    }
 
-    // Create identical test files
-    let content = "Small file content.\n".repeat(50); // ~1KB
-    for i in 0..100 {
-        let filename = format!("small_{:03}.txt", i);
-        blaze_repo.create_file(&filename, content.as_bytes())?;
-        git_repo.create_file(&filename, content.as_bytes())?;
+    fn load(name: &str) -> std::io::Result<Self> {
+        let data = fs::read_to_string(Self::path_for(name))?;
+        serde_json::from_str(&data)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
     }
 
-    // Initialize repositories
-    blaze_repo.run_blaze(&["init"])?;
-    git_repo.run_git(&["init"])?;
-    git_repo.run_git(&["config", "user.email", "test@example.com"])?;
-    git_repo.run_git(&["config", "user.name", "Test User"])?;
+    fn save(&self, name: &str) -> std::io::Result<()> {
+        let path = Self::path_for(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json)
+    }
 
-    // Benchmark Blaze
-    let blaze_add_time = blaze_repo.run_blaze(&["add", "."])?;
-    let blaze_commit_time = blaze_repo.run_blaze(&["commit", "-m", "Add small files"])?;
-    let blaze_total_time = blaze_add_time + blaze_commit_time;
-    let blaze_size = blaze_repo.get_blaze_size()?;
+    fn from_results(results: &[BenchmarkResult]) -> Self {
+        let mut by_operation = HashMap::new();
+        for result in results {
+            let mut by_tool = HashMap::new();
+            for tool in &result.tools {
+                by_tool.insert(
+                    tool.name.clone(),
+                    BaselineEntry {
+                        mean_secs: tool.stats.mean.as_secs_f64(),
+                        size_bytes: tool.size,
+                    },
+                );
+            }
+            by_operation.insert(result.operation.clone(), by_tool);
+        }
 
-    // Benchmark Git
-    let git_add_time = git_repo.run_git(&["add", "."])?;
-    let git_commit_time = git_repo.run_git(&["commit", "-m", "Add small files"])?;
-    let git_total_time = git_add_time + git_commit_time;
-    let git_size = git_repo.get_git_size()?;
+        BaselineFile {
+            commit: blaze_git_commit(),
+            results: by_operation,
+        }
+    }
 
-    Ok(BenchmarkResult {
-        operation: "100 Small Files".to_string(),
-        blaze_time: blaze_total_time,
-        git_time: git_total_time,
-        blaze_size,
-        git_size,
-    })
+    fn entry(&self, operation: &str, tool_name: &str) -> Option<&BaselineEntry> {
+        self.results.get(operation)?.get(tool_name)
+    }
 }
 
-fn benchmark_large_file() -> std::io::Result<BenchmarkResult> {
-    println!("🔹 Benchmarking: Single Large File (10MB)");
-
-    let blaze_repo = TestRepo::new()?;
-    let git_repo = TestRepo::new()?;
-
-    // Create a 10MB file
-    let content = vec![b'X'; 10 * 1024 * 1024]; // 10MB
-    blaze_repo.create_file("large_file.dat", &content)?;
-    git_repo.create_file("large_file.dat", &content)?;
+/// Thresholds beyond which [`BaselineDelta::regressed`] is set
+struct RegressionThresholds {
+    max_time_regression: f64,
+    max_size_regression: f64,
+}
 
-    // Initialize repositories
-    blaze_repo.run_blaze(&["init"])?;
-    git_repo.run_git(&["init"])?;
-    git_repo.run_git(&["config", "user.email", "test@example.com"])?;
-    git_repo.run_git(&["config", "user.name", "Test User"])?;
+/// One tool's change in mean time and storage size against a `--baseline`,
+/// as fractions (`0.14` means "+14%")
+struct BaselineDelta {
+    time_change: f64,
+    size_change: f64,
+    regressed: bool,
+}
 
-    // Benchmark Blaze
-    let blaze_add_time = blaze_repo.run_blaze(&["add", "."])?;
-    let blaze_commit_time = blaze_repo.run_blaze(&["commit", "-m", "Add large file"])?;
-    let blaze_total_time = blaze_add_time + blaze_commit_time;
-    let blaze_size = blaze_repo.get_blaze_size()?;
+fn baseline_delta(
+    tool: &ToolResult,
+    operation: &str,
+    baseline: &BaselineFile,
+    thresholds: &RegressionThresholds,
+) -> Option<BaselineDelta> {
+    let entry = baseline.entry(operation, &tool.name)?;
+    let time_change =
+        (tool.stats.mean.as_secs_f64() - entry.mean_secs) / entry.mean_secs.max(f64::EPSILON);
+    let size_change = (tool.size as f64 - entry.size_bytes as f64)
+        / (entry.size_bytes as f64).max(f64::EPSILON);
+    let regressed = time_change > thresholds.max_time_regression
+        || size_change > thresholds.max_size_regression;
+
+    Some(BaselineDelta {
+        time_change,
+        size_change,
+        regressed,
+    })
+}
 
-    // Benchmark Git
-    let git_add_time = git_repo.run_git(&["add", "."])?;
-    let git_commit_time = git_repo.run_git(&["commit", "-m", "Add large file"])?;
-    let git_total_time = git_add_time + git_commit_time;
-    let git_size = git_repo.get_git_size()?;
+/// "+14% ⚠" for a regression, "+3%" / "-2%" otherwise, "—" when the
+/// baseline has no matching entry for this tool/operation
+fn format_baseline_delta(delta: Option<&BaselineDelta>) -> String {
+    match delta {
+        Some(delta) => {
+            let pct = delta.time_change * 100.0;
+            if delta.regressed {
+                format!("{:+.0}% ⚠", pct)
+            } else {
+                format!("{:+.0}%", pct)
+            }
+        }
+        None => "—".to_string(),
+    }
+}
 
-    Ok(BenchmarkResult {
-        operation: "10MB Large File".to_string(),
-        blaze_time: blaze_total_time,
-        git_time: git_total_time,
-        blaze_size,
-        git_size,
-    })
+/// A version-control tool under comparison: its binary, the name of the
+/// directory it stores its repository state in, and any untimed commands
+/// (e.g. git's `user.email`/`user.name`) that must run once after `init`
+/// before any of its subcommands will work.
+struct Tool {
+    name: String,
+    binary: PathBuf,
+    storage_dir: &'static str,
+    post_init: Vec<Vec<String>>,
 }
 
-fn benchmark_duplicates() -> std::io::Result<BenchmarkResult> {
-    println!("🔹 Benchmarking: Duplicate Content (Deduplication Test)");
+impl Tool {
+    fn run(&self, repo: &TestRepo, args: &[&str]) -> std::io::Result<Duration> {
+        let start = Instant::now();
+        let output = Command::new(&self.binary)
+            .current_dir(&repo.path)
+            .args(args)
+            .output()?;
 
-    let blaze_repo = TestRepo::new()?;
-    let git_repo = TestRepo::new()?;
+        let duration = start.elapsed();
 
-    // Create 50 files with identical content (should deduplicate well)
-    let content = "This is duplicate content that should compress well.\n".repeat(1000); // ~50KB per file
-    for i in 0..50 {
-        let filename = format!("dup_{:03}.txt", i);
-        blaze_repo.create_file(&filename, content.as_bytes())?;
-        git_repo.create_file(&filename, content.as_bytes())?;
-    }
+        if !output.status.success() {
+            return Err(std::io::Error::other(format!(
+                "{} command failed: {:?}",
+                self.name, args
+            )));
+        }
 
-    // Initialize repositories
-    blaze_repo.run_blaze(&["init"])?;
-    git_repo.run_git(&["init"])?;
-    git_repo.run_git(&["config", "user.email", "test@example.com"])?;
-    git_repo.run_git(&["config", "user.name", "Test User"])?;
+        Ok(duration)
+    }
 
-    // Benchmark Blaze
-    let blaze_add_time = blaze_repo.run_blaze(&["add", "."])?;
-    let blaze_commit_time = blaze_repo.run_blaze(&["commit", "-m", "Add duplicate files"])?;
-    let blaze_total_time = blaze_add_time + blaze_commit_time;
-    let blaze_size = blaze_repo.get_blaze_size()?;
+    fn storage_size(&self, repo: &TestRepo) -> std::io::Result<u64> {
+        calculate_dir_size(&repo.path.join(self.storage_dir))
+    }
+}
 
-    // Benchmark Git
-    let git_add_time = git_repo.run_git(&["add", "."])?;
-    let git_commit_time = git_repo.run_git(&["commit", "-m", "Add duplicate files"])?;
-    let git_total_time = git_add_time + git_commit_time;
-    let git_size = git_repo.get_git_size()?;
+/// The tools benchmarked by default: Blaze and Git, both exposing the same
+/// `init`/`add`/`commit` subcommand vocabulary this harness drives. Adding a
+/// future backend is a matter of appending another [`Tool`] here.
+fn default_tools(blaze_binary: PathBuf) -> Vec<Tool> {
+    vec![
+        Tool {
+            name: "Blaze".to_string(),
+            binary: blaze_binary,
+            storage_dir: ".blaze",
+            post_init: Vec::new(),
+        },
+        Tool {
+            name: "Git".to_string(),
+            binary: PathBuf::from("git"),
+            storage_dir: ".git",
+            post_init: vec![
+                vec![
+                    "config".to_string(),
+                    "user.email".to_string(),
+                    "test@example.com".to_string(),
+                ],
+                vec![
+                    "config".to_string(),
+                    "user.name".to_string(),
+                    "Test User".to_string(),
+                ],
+            ],
+        },
+    ]
+}
 
-    Ok(BenchmarkResult {
-        operation: "50 Duplicate Files".to_string(),
-        blaze_time: blaze_total_time,
-        git_time: git_total_time,
-        blaze_size,
-        git_size,
-    })
+struct TestRepo {
+    #[allow(dead_code)]
+    temp_dir: TempDir,
+    path: PathBuf,
 }
 
-fn benchmark_mixed_files() -> std::io::Result<BenchmarkResult> {
-    println!("🔹 Benchmarking: Mixed File Types (Realistic Repository)");
+impl TestRepo {
+    fn new() -> std::io::Result<Self> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().to_path_buf();
 
-    let blaze_repo = TestRepo::new()?;
-    let git_repo = TestRepo::new()?;
+        Ok(Self { temp_dir, path })
+    }
 
-    // Create a realistic mix of files
-    // Source code files
-    for i in 0..20 {
-        let content = format!(
-            "// Source file {}\nfn main() {{\n    println!(\"Hello from file {}\");\n}}\n",
-            i, i
-        );
-        let filename = format!("src/file_{:02}.rs", i);
-        blaze_repo.create_file(&filename, content.as_bytes())?;
-        git_repo.create_file(&filename, content.as_bytes())?;
-    }
-
-    // Configuration files
-    let config_content = "{\n  \"name\": \"test-project\",\n  \"version\": \"1.0.0\"\n}\n";
-    blaze_repo.create_file("package.json", config_content.as_bytes())?;
-    git_repo.create_file("package.json", config_content.as_bytes())?;
-
-    // README
-    let readme_content = "# Test Project\n\nThis is a test project for benchmarking.\n\n## Installation\n\nRun the build script.\n";
-    blaze_repo.create_file("README.md", readme_content.as_bytes())?;
-    git_repo.create_file("README.md", readme_content.as_bytes())?;
-
-    // Binary-like files
-    for i in 0..5 {
-        let binary_content = vec![i as u8; 10240]; // 10KB of binary data
-        let filename = format!("assets/image_{}.dat", i);
-        blaze_repo.create_file(&filename, &binary_content)?;
-        git_repo.create_file(&filename, &binary_content)?;
-    }
-
-    // Initialize repositories
-    blaze_repo.run_blaze(&["init"])?;
-    git_repo.run_git(&["init"])?;
-    git_repo.run_git(&["config", "user.email", "test@example.com"])?;
-    git_repo.run_git(&["config", "user.name", "Test User"])?;
-
-    // Benchmark Blaze
-    let blaze_add_time = blaze_repo.run_blaze(&["add", "."])?;
-    let blaze_commit_time = blaze_repo.run_blaze(&["commit", "-m", "Initial project setup"])?;
-    let blaze_total_time = blaze_add_time + blaze_commit_time;
-    let blaze_size = blaze_repo.get_blaze_size()?;
-
-    // Benchmark Git
-    let git_add_time = git_repo.run_git(&["add", "."])?;
-    let git_commit_time = git_repo.run_git(&["commit", "-m", "Initial project setup"])?;
-    let git_total_time = git_add_time + git_commit_time;
-    let git_size = git_repo.get_git_size()?;
+    fn create_file(&self, relative_path: &str, content: &[u8]) -> std::io::Result<()> {
+        let file_path = self.path.join(relative_path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file_path, content)?;
+        Ok(())
+    }
+}
 
-    Ok(BenchmarkResult {
-        operation: "Mixed File Types".to_string(),
-        blaze_time: blaze_total_time,
-        git_time: git_total_time,
-        blaze_size,
-        git_size,
-    })
+/// An operation benchmarked across every [`Tool`]: `write_files` prepares
+/// the working tree (untimed), then either `init` itself is the timed
+/// operation (`init_is_timed: true`, used only by the init benchmark) or
+/// `init` plus each tool's `post_init` run untimed and `timed_commands` is
+/// measured instead.
+struct BenchmarkSpec {
+    operation: &'static str,
+    write_files: fn(&TestRepo) -> std::io::Result<()>,
+    init_is_timed: bool,
+    timed_commands: Vec<Vec<&'static str>>,
 }
 
-fn print_results_table(results: &[BenchmarkResult]) {
-    println!("\n📊 Performance Comparison Results");
-    println!("═════════════════════════════════════════════════════════════════════════════");
-    println!(
-        "{:<20} {:>12} {:>12} {:>8} {:>12} {:>12} {:>8}",
-        "Operation", "Blaze", "Git", "Speedup", "Blaze Size", "Git Size", "Size Ratio"
-    );
-    println!("─────────────────────────────────────────────────────────────────────────────");
+/// Run `warmup` untimed iterations (each against a brand new [`TestRepo`],
+/// so nothing from one iteration leaks into the next) followed by
+/// `iterations` measured ones for one tool, and reduce the measured samples
+/// to [`SampleStats`].
+fn run_for_tool(
+    tool: &Tool,
+    spec: &BenchmarkSpec,
+    warmup: usize,
+    iterations: usize,
+    strict: bool,
+) -> std::io::Result<ToolResult> {
+    let setup = |repo: &TestRepo| -> std::io::Result<()> {
+        (spec.write_files)(repo)?;
+        if !spec.init_is_timed {
+            tool.run(repo, &["init"])?;
+            for command in &tool.post_init {
+                let args: Vec<&str> = command.iter().map(String::as_str).collect();
+                tool.run(repo, &args)?;
+            }
+        }
+        Ok(())
+    };
 
-    for result in results {
-        let speedup = if result.speedup() > 1.0 {
-            format!("{:.1}x faster", result.speedup())
+    let operation = |repo: &TestRepo| -> std::io::Result<Duration> {
+        let mut total = Duration::ZERO;
+        let commands = if spec.init_is_timed {
+            vec![vec!["init"]]
         } else {
-            format!("{:.1}x slower", 1.0 / result.speedup())
+            spec.timed_commands.clone()
         };
+        for command in &commands {
+            total += tool.run(repo, command)?;
+        }
+        Ok(total)
+    };
 
-        let size_ratio = if result.size_ratio() < 1.0 {
-            format!("{:.1}x smaller", 1.0 / result.size_ratio())
-        } else {
-            format!("{:.1}x larger", result.size_ratio())
-        };
+    for _ in 0..warmup {
+        let repo = TestRepo::new()?;
+        setup(&repo)?;
+        operation(&repo)?;
+    }
 
-        println!(
-            "{:<20} {:>12} {:>12} {:>8} {:>12} {:>12} {:>8}",
-            result.operation,
-            format_duration(result.blaze_time),
-            format_duration(result.git_time),
-            speedup,
-            format_size(result.blaze_size),
-            format_size(result.git_size),
-            size_ratio
-        );
+    let mut samples = Vec::with_capacity(iterations.max(1));
+    let mut size = 0;
+    for _ in 0..iterations.max(1) {
+        let repo = TestRepo::new()?;
+        setup(&repo)?;
+        samples.push(operation(&repo)?);
+        size = tool.storage_size(&repo)?;
     }
-    println!("═════════════════════════════════════════════════════════════════════════════");
-}
 
-fn print_analysis(results: &[BenchmarkResult]) {
-    println!("\n🔍 Analysis");
-    println!("══════════");
+    Ok(ToolResult {
+        name: tool.name.clone(),
+        stats: SampleStats::from_samples(samples, strict),
+        size,
+    })
+}
 
-    let mut faster_count = 0;
-    let mut smaller_count = 0;
+fn run_benchmark(
+    spec: &BenchmarkSpec,
+    tools: &[Tool],
+    warmup: usize,
+    iterations: usize,
+    strict: bool,
+) -> std::io::Result<BenchmarkResult> {
+    println!(
+        "🔹 Benchmarking: {} ({} warmup + {} iterations)",
+        spec.operation, warmup, iterations
+    );
 
-    for result in results {
-        if result.speedup() > 1.0 {
-            faster_count += 1;
-        }
-        if result.size_ratio() < 1.0 {
-            smaller_count += 1;
-        }
+    let mut results = Vec::with_capacity(tools.len());
+    for tool in tools {
+        results.push(run_for_tool(tool, spec, warmup, iterations, strict)?);
     }
 
-    println!("Blaze vs Git Performance:");
-    println!(
-        "• Blaze is faster in {}/{} test cases",
-        faster_count,
-        results.len()
-    );
-    println!(
-        "• Blaze uses less storage in {}/{} test cases",
-        smaller_count,
-        results.len()
-    );
+    Ok(BenchmarkResult {
+        operation: spec.operation.to_string(),
+        tools: results,
+    })
+}
 
-    println!("\n🎯 Key Observations:");
+fn benchmark_specs() -> Vec<BenchmarkSpec> {
+    vec![
+        BenchmarkSpec {
+            operation: "Repository Init",
+            write_files: |_repo| Ok(()),
+            init_is_timed: true,
+            timed_commands: Vec::new(),
+        },
+        BenchmarkSpec {
+            operation: "100 Small Files",
+            write_files: |repo| {
+                let content = "Small file content.\n".repeat(50); // ~1KB
+                for i in 0..100 {
+                    let filename = format!("small_{:03}.txt", i);
+                    repo.create_file(&filename, content.as_bytes())?;
+                }
+                Ok(())
+            },
+            init_is_timed: false,
+            timed_commands: vec![vec!["add", "."], vec!["commit", "-m", "Add small files"]],
+        },
+        BenchmarkSpec {
+            operation: "10MB Large File",
+            write_files: |repo| {
+                let content = vec![b'X'; 10 * 1024 * 1024]; // 10MB
+                repo.create_file("large_file.dat", &content)
+            },
+            init_is_timed: false,
+            timed_commands: vec![vec!["add", "."], vec!["commit", "-m", "Add large file"]],
+        },
+        BenchmarkSpec {
+            operation: "50 Duplicate Files",
+            write_files: |repo| {
+                let content =
+                    "This is duplicate content that should compress well.\n".repeat(1000); // ~50KB per file
+                for i in 0..50 {
+                    let filename = format!("dup_{:03}.txt", i);
+                    repo.create_file(&filename, content.as_bytes())?;
+                }
+                Ok(())
+            },
+            init_is_timed: false,
+            timed_commands: vec![
+                vec!["add", "."],
+                vec!["commit", "-m", "Add duplicate files"],
+            ],
+        },
+        BenchmarkSpec {
+            operation: "Mixed File Types",
+            write_files: |repo| {
+                // Source code files
+                for i in 0..20 {
+                    let content = format!(
+                        "// Source file {}\nfn main() {{\n    println!(\"Hello from file {}\");\n}}\n",
+                        i, i
+                    );
+                    let filename = format!("src/file_{:02}.rs", i);
+                    repo.create_file(&filename, content.as_bytes())?;
+                }
+
+                // Configuration files
+                let config_content = "{\n  \"name\": \"test-project\",\n  \"version\": \"1.0.0\"\n}\n";
+                repo.create_file("package.json", config_content.as_bytes())?;
+
+                // README
+                let readme_content = "# Test Project\n\nThis is a test project for benchmarking.\n\n## Installation\n\nRun the build script.\n";
+                repo.create_file("README.md", readme_content.as_bytes())?;
+
+                // Binary-like files
+                for i in 0..5 {
+                    let binary_content = vec![i as u8; 10240]; // 10KB of binary data
+                    let filename = format!("assets/image_{}.dat", i);
+                    repo.create_file(&filename, &binary_content)?;
+                }
+
+                Ok(())
+            },
+            init_is_timed: false,
+            timed_commands: vec![
+                vec!["add", "."],
+                vec!["commit", "-m", "Initial project setup"],
+            ],
+        },
+    ]
+}
 
-    // Find best and worst cases
-    let best_speed = results
-        .iter()
-        .max_by(|a, b| a.speedup().partial_cmp(&b.speedup()).unwrap());
-    let worst_speed = results
-        .iter()
-        .min_by(|a, b| a.speedup().partial_cmp(&b.speedup()).unwrap());
-    let best_storage = results
-        .iter()
-        .min_by(|a, b| a.size_ratio().partial_cmp(&b.size_ratio()).unwrap());
+fn print_results_table(
+    results: &[BenchmarkResult],
+    reference: Option<&str>,
+    baseline: Option<(&BaselineFile, &RegressionThresholds)>,
+) {
+    println!("\n📊 Performance Comparison Results");
 
-    if let Some(best) = best_speed {
-        if best.speedup() > 1.0 {
+    for result in results {
+        println!("\n{}", result.operation);
+        println!("─────────────────────────────────────────────────────────────────────────────");
+        if baseline.is_some() {
             println!(
-                "• Best Blaze performance: {} ({:.1}x faster than Git)",
-                best.operation,
-                best.speedup()
+                "{:<10} {:>22} {:>12} {:>24} {:>14}",
+                "Tool", "Mean ± σ", "Size", "Relative", "Δ vs baseline"
             );
-        }
-    }
-
-    if let Some(worst) = worst_speed {
-        if worst.speedup() < 1.0 {
+        } else {
             println!(
-                "• Worst Blaze performance: {} ({:.1}x slower than Git)",
-                worst.operation,
-                1.0 / worst.speedup()
+                "{:<10} {:>22} {:>12} {:>24}",
+                "Tool", "Mean ± σ", "Size", "Relative"
             );
         }
+
+        for ranked in result.ranked(reference) {
+            if let Some((baseline, thresholds)) = baseline {
+                let delta = baseline_delta(ranked.tool, &result.operation, baseline, thresholds);
+                println!(
+                    "{:<10} {:>22} {:>12} {:>24} {:>14}",
+                    ranked.tool.name,
+                    format_mean_stddev(&ranked.tool.stats),
+                    format_size(ranked.tool.size),
+                    format_relative_speed(&ranked),
+                    format_baseline_delta(delta.as_ref())
+                );
+            } else {
+                println!(
+                    "{:<10} {:>22} {:>12} {:>24}",
+                    ranked.tool.name,
+                    format_mean_stddev(&ranked.tool.stats),
+                    format_size(ranked.tool.size),
+                    format_relative_speed(&ranked)
+                );
+            }
+        }
     }
+    println!();
+}
 
-    if let Some(best) = best_storage {
-        if best.size_ratio() < 1.0 {
+/// Number of (operation, tool) pairs that regressed beyond `thresholds`
+/// against `baseline` - used both to summarize and to decide the process
+/// exit code
+fn count_regressions(
+    results: &[BenchmarkResult],
+    baseline: &BaselineFile,
+    thresholds: &RegressionThresholds,
+) -> usize {
+    results
+        .iter()
+        .flat_map(|result| {
+            result
+                .tools
+                .iter()
+                .filter_map(|tool| baseline_delta(tool, &result.operation, baseline, thresholds))
+        })
+        .filter(|delta| delta.regressed)
+        .count()
+}
+
+fn print_analysis(results: &[BenchmarkResult], reference: Option<&str>) {
+    println!("🔍 Analysis");
+    println!("══════════");
+
+    for result in results {
+        let ranked = result.ranked(reference);
+        let fastest = ranked.first().expect("at least one ranked tool");
+        let slowest = ranked.last().expect("at least one ranked tool");
+        let smallest = result
+            .tools
+            .iter()
+            .min_by_key(|tool| tool.size)
+            .expect("at least one tool result");
+
+        println!("\n{}:", result.operation);
+        println!("• Fastest: {}", fastest.tool.name);
+        if fastest.tool.name != slowest.tool.name {
+            let slowest_ranked = ranked
+                .iter()
+                .find(|r| r.tool.name == slowest.tool.name)
+                .unwrap();
             println!(
-                "• Best storage efficiency: {} ({:.1}x smaller than Git)",
-                best.operation,
-                1.0 / best.size_ratio()
+                "• Slowest: {} ({})",
+                slowest.tool.name,
+                format_relative_speed(slowest_ranked)
             );
         }
+        println!(
+            "• Smallest storage footprint: {} ({})",
+            smallest.name,
+            format_size(smallest.size)
+        );
     }
 
     println!("\n📝 Technical Notes:");
@@ -475,7 +974,121 @@ fn print_analysis(results: &[BenchmarkResult]) {
     println!("• For typical source code, Git's optimizations are hard to beat");
 }
 
+struct Args {
+    warmup: usize,
+    iterations: usize,
+    strict: bool,
+    reference: Option<String>,
+    export_json: Option<String>,
+    export_csv: Option<String>,
+    export_markdown: Option<String>,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+    max_time_regression: f64,
+    max_size_regression: f64,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut warmup = 1;
+        let mut iterations = 5;
+        let mut strict = false;
+        let mut reference = None;
+        let mut export_json = None;
+        let mut export_csv = None;
+        let mut export_markdown = None;
+        let mut save_baseline = None;
+        let mut baseline = None;
+        let mut max_time_regression = DEFAULT_MAX_TIME_REGRESSION;
+        let mut max_size_regression = DEFAULT_MAX_SIZE_REGRESSION;
+
+        let args: Vec<String> = std::env::args().collect();
+        let mut i = 1;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--warmup" => {
+                    warmup = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(warmup);
+                    i += 2;
+                }
+                "--iterations" => {
+                    iterations = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(iterations);
+                    i += 2;
+                }
+                "--strict" => {
+                    strict = true;
+                    i += 1;
+                }
+                "--reference" => {
+                    reference = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--export-json" => {
+                    export_json = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--export-csv" => {
+                    export_csv = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--export-markdown" => {
+                    export_markdown = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--save-baseline" => {
+                    save_baseline = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--baseline" => {
+                    baseline = args.get(i + 1).cloned();
+                    i += 2;
+                }
+                "--max-time-regression" => {
+                    max_time_regression = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .map(|pct| pct / 100.0)
+                        .unwrap_or(max_time_regression);
+                    i += 2;
+                }
+                "--max-size-regression" => {
+                    max_size_regression = args
+                        .get(i + 1)
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .map(|pct| pct / 100.0)
+                        .unwrap_or(max_size_regression);
+                    i += 2;
+                }
+                _ => {
+                    i += 1;
+                }
+            }
+        }
+
+        Args {
+            warmup,
+            iterations,
+            strict,
+            reference,
+            export_json,
+            export_csv,
+            export_markdown,
+            save_baseline,
+            baseline,
+            max_time_regression,
+            max_size_regression,
+        }
+    }
+}
+
 fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+
     println!("Blaze vs Git Performance Comparison");
     println!("====================================");
 
@@ -506,18 +1119,11 @@ fn main() -> std::io::Result<()> {
 
     println!("✅ Prerequisites met. Running benchmarks...\n");
 
-    let benchmarks = vec![
-        benchmark_init,
-        benchmark_small_files,
-        benchmark_large_file,
-        benchmark_duplicates,
-        benchmark_mixed_files,
-    ];
-
+    let tools = default_tools(blaze_binary);
     let mut results = Vec::new();
 
-    for benchmark in benchmarks {
-        match benchmark() {
+    for spec in benchmark_specs() {
+        match run_benchmark(&spec, &tools, args.warmup, args.iterations, args.strict) {
             Ok(result) => {
                 println!("   ✅ Completed");
                 results.push(result);
@@ -533,11 +1139,69 @@ fn main() -> std::io::Result<()> {
         return Ok(());
     }
 
-    print_results_table(&results);
-    print_analysis(&results);
+    let thresholds = RegressionThresholds {
+        max_time_regression: args.max_time_regression,
+        max_size_regression: args.max_size_regression,
+    };
+
+    let loaded_baseline = match &args.baseline {
+        Some(name) => match BaselineFile::load(name) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("⚠ Failed to load baseline '{}': {}", name, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    print_results_table(
+        &results,
+        args.reference.as_deref(),
+        loaded_baseline.as_ref().map(|b| (b, &thresholds)),
+    );
+    print_analysis(&results, args.reference.as_deref());
+
+    let mut regressions = 0;
+    if let Some(baseline) = &loaded_baseline {
+        regressions = count_regressions(&results, baseline, &thresholds);
+        if regressions > 0 {
+            println!(
+                "\n⚠ {} regression{} detected vs baseline '{}'",
+                regressions,
+                if regressions == 1 { "" } else { "s" },
+                args.baseline.as_deref().unwrap_or("?")
+            );
+        }
+    }
+
+    if let Some(name) = &args.save_baseline {
+        BaselineFile::from_results(&results).save(name)?;
+        println!("\n💾 Saved baseline '{}' to {}", name, BaselineFile::path_for(name).display());
+    }
+
+    if args.export_json.is_some() || args.export_csv.is_some() {
+        let report = build_export_report(&results, args.reference.as_deref());
+        if let Some(path) = &args.export_json {
+            export_json(&report, Path::new(path))?;
+            println!("\n📄 Exported JSON to {}", path);
+        }
+        if let Some(path) = &args.export_csv {
+            export_csv(&report, Path::new(path))?;
+            println!("📄 Exported CSV to {}", path);
+        }
+    }
+    if let Some(path) = &args.export_markdown {
+        export_markdown(&results, args.reference.as_deref(), Path::new(path))?;
+        println!("📄 Exported Markdown to {}", path);
+    }
 
     println!("\n🏁 Benchmark completed!");
     println!("Note: Results may vary based on system configuration and file system type.");
 
+    if regressions > 0 {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
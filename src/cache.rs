@@ -0,0 +1,156 @@
+//! Persistent per-file chunk cache, mirroring czkawka's duplicate-scan cache:
+//! re-scanning a tree shouldn't have to re-read and re-hash every file when
+//! most of them haven't changed since the last scan.
+
+use crate::errors::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A cached chunk list for one file, valid only as long as the file's
+/// (mtime, size, permissions) triple hasn't moved since it was recorded
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct CachedChunks {
+    mtime: u64,
+    size: u64,
+    permissions: u32,
+    chunks: Vec<String>,
+}
+
+/// On-disk cache of `path -> CachedChunks`. Consult it before chunking a
+/// file; only pay for I/O and hashing once the file's cheap metadata has
+/// actually moved.
+#[derive(Debug, Default)]
+pub struct ChunkCache {
+    entries: HashMap<String, CachedChunks>,
+    dirty: bool,
+}
+
+impl ChunkCache {
+    /// Load a cache from disk, starting empty if it doesn't exist yet or
+    /// fails to parse - a corrupt or missing cache is never fatal, it just
+    /// means every file re-hashes once
+    pub fn load<P: AsRef<Path>>(cache_path: P) -> Self {
+        let entries = fs::read_to_string(cache_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        ChunkCache {
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// Persist the cache to disk, skipping the write entirely if nothing
+    /// changed since it was loaded
+    pub fn save<P: AsRef<Path>>(&self, cache_path: P) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string(&self.entries).context("Failed to serialize chunk cache")?;
+        fs::write(cache_path, json).context("Failed to write chunk cache")?;
+
+        Ok(())
+    }
+
+    /// Look up a cached chunk list, returning it only if the file's metadata
+    /// still matches what was recorded
+    pub fn get(&self, path: &str, mtime: u64, size: u64, permissions: u32) -> Option<&Vec<String>> {
+        self.entries
+            .get(path)
+            .filter(|entry| {
+                entry.mtime == mtime && entry.size == size && entry.permissions == permissions
+            })
+            .map(|entry| &entry.chunks)
+    }
+
+    /// Record (or refresh) the chunk list for a file
+    pub fn insert(&mut self, path: String, mtime: u64, size: u64, permissions: u32, chunks: Vec<String>) {
+        self.entries.insert(
+            path,
+            CachedChunks {
+                mtime,
+                size,
+                permissions,
+                chunks,
+            },
+        );
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_hit_on_matching_metadata() {
+        let mut cache = ChunkCache::default();
+        cache.insert(
+            "a.txt".to_string(),
+            100,
+            10,
+            0o644,
+            vec!["hash1".to_string()],
+        );
+
+        assert_eq!(
+            cache.get("a.txt", 100, 10, 0o644),
+            Some(&vec!["hash1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_cache_miss_on_changed_metadata() {
+        let mut cache = ChunkCache::default();
+        cache.insert(
+            "a.txt".to_string(),
+            100,
+            10,
+            0o644,
+            vec!["hash1".to_string()],
+        );
+
+        assert_eq!(cache.get("a.txt", 101, 10, 0o644), None);
+        assert_eq!(cache.get("a.txt", 100, 11, 0o644), None);
+        assert_eq!(cache.get("a.txt", 100, 10, 0o600), None);
+        assert_eq!(cache.get("missing.txt", 100, 10, 0o644), None);
+    }
+
+    #[test]
+    fn test_cache_roundtrips_through_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("chunk_cache.json");
+
+        let mut cache = ChunkCache::load(&cache_path);
+        cache.insert(
+            "a.txt".to_string(),
+            100,
+            10,
+            0o644,
+            vec!["hash1".to_string()],
+        );
+        cache.save(&cache_path).unwrap();
+
+        let reloaded = ChunkCache::load(&cache_path);
+        assert_eq!(
+            reloaded.get("a.txt", 100, 10, 0o644),
+            Some(&vec!["hash1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_save_is_noop_when_not_dirty() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("chunk_cache.json");
+
+        let cache = ChunkCache::load(&cache_path);
+        cache.save(&cache_path).unwrap();
+
+        assert!(!cache_path.exists());
+    }
+}
@@ -1,14 +1,132 @@
 //! Chunk storage and management for Blaze VCS
 
+use crate::config::{
+    ChunkingConfig, CompressionAlgo, CompressionConfig, CompressionLevel, EncryptionConfig,
+    CHUNK_SIZE, MAX_MEMORY_BUFFER,
+};
 use crate::errors::{BlazeError, Result, ResultExt};
-use crate::files::FileChunk;
+use crate::files::{self, FileChunk, HashAlgo};
+use crate::rate_limit::RateLimiter;
+use crate::storage::{FsBackend, StorageBackend, StorageOpenOptions};
 
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use rand::{rngs::OsRng, RngCore};
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Marker identifying a bundle payload as AES-256-GCM ciphertext, encoded as
+/// `[ENCRYPTED_MARKER] || nonce (12 bytes) || ciphertext+tag`. Only ever
+/// inspected against the raw bytes physically read from a bundle, before
+/// compression's or delta-encoding's own marker bytes (which address a
+/// separate, already-decrypted namespace) come into play. An unencrypted
+/// payload goes through the same check, so this must stay outside the 0-6
+/// range the codec markers and [`DELTA_MARKER`] occupy
+const ENCRYPTED_MARKER: u8 = 7;
+
+/// Marker identifying a stored chunk record as a delta against a base chunk,
+/// encoded as `[DELTA_MARKER] || base_hash (utf-8) || 0x00 || compress_chunk_data(delta)`.
+/// Checked against the first byte of an already-decrypted chunk payload,
+/// before it's known whether that payload is compressed directly or as a
+/// delta - must stay outside the 0-5 range `compress_chunk_data`'s own codec
+/// marker bytes occupy, since a direct (non-delta) chunk's first byte is one
+/// of those codec markers
+const DELTA_MARKER: u8 = 6;
+
+/// Width of an AES-GCM nonce, in bytes
+const NONCE_LEN: usize = 12;
+
+/// Target size for a single bundle (pack) file before rolling over to a new one
+const BUNDLE_TARGET_SIZE: u64 = 64 * 1024 * 1024; // 64MB
+
+/// Bundles whose live-data ratio drops below this fraction are compacted away
+const COMPACTION_LIVE_THRESHOLD: f64 = 0.5;
+
+/// On-disk index record layout: a 1-byte hash length, the hash's own bytes
+/// (whatever length the store's [`HashAlgo`](crate::files::HashAlgo) produces
+/// - not assumed to be 64), then this fixed-width trailer of bundle id (u32)
+/// + offset (u64) + stored length (u32)
+const INDEX_RECORD_TRAILER_SIZE: usize = 4 + 8 + 4;
+
+/// Name of the directory (inside the chunk store root) holding append-only bundle files
+const BUNDLES_DIR: &str = "bundles";
+
+/// Name of the append-only index log mapping chunk hash -> bundle location
+const INDEX_LOG_FILE: &str = "index.log";
+
+/// Name of the small store-level metadata file recording the chosen hash algorithm
+const HASH_ALGO_FILE: &str = "hash_algo";
+
+/// Default number of chunks coalesced into a single prefetch batch when
+/// materializing a file whose chunk list is already known (checkout/restore)
+const DEFAULT_READ_AMPLIFICATION_BATCH: usize = 8;
+
+/// Window width for the rolling hash used to build a chunk's min-hash sketch
+const SKETCH_WINDOW: usize = 16;
+
+/// Number of smallest distinct window hashes kept per chunk as its sketch,
+/// and the number of bytes packed into a signature (one sketch element per byte)
+const SKETCH_SIZE: usize = 8;
+
+/// Maximum Hamming distance between two packed signatures for the candidate
+/// to be accepted as a delta base
+const MAX_SKETCH_HAMMING_RADIUS: u32 = 12;
+
+/// Maximum number of deltas a chunk may be chained beneath before a new
+/// delta falls back to full compression instead of deepening the chain -
+/// bounds worst-case reconstruction cost and blast radius if an intermediate
+/// base is ever lost
+const MAX_DELTA_DEPTH: usize = 8;
+
+/// Location of a chunk's compressed payload within a bundle file
+#[derive(Debug, Clone, Copy)]
+struct BundleLocation {
+    bundle_id: u32,
+    offset: u64,
+    stored_len: u32,
+}
+
+/// Original-vs-compressed byte totals accumulated for one codec, exposed via
+/// [`ChunkStore::compression_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodecStats {
+    pub chunk_count: u64,
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+}
+
+impl CodecStats {
+    /// Compressed/original size ratio - lower is better, 1.0 means the codec
+    /// didn't shrink the data at all
+    pub fn ratio(&self) -> f64 {
+        if self.original_bytes == 0 {
+            return 1.0;
+        }
+
+        self.compressed_bytes as f64 / self.original_bytes as f64
+    }
+}
+
+/// Which rate-limit tier an `append_to_active_bundle` write draws its
+/// throughput budget from. Foreground writes (flush/store) get first claim
+/// on a constrained budget; background maintenance (compaction) waits
+/// behind them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IoPriority {
+    Foreground,
+    Background,
+}
+
+/// The bundle file currently being appended to
+struct ActiveBundle {
+    id: u32,
+    file: Box<dyn crate::storage::StorageHandle>,
+    size: u64,
+}
 
 /// Delta compression data for storing similar chunks efficiently
 #[derive(Debug, Clone)]
@@ -25,99 +143,394 @@ pub enum CompressedChunk {
     Delta(ChunkDelta),
 }
 
+/// Outcome of verifying a single stored chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyStatus {
+    /// Chunk decompressed (and, if delta-encoded, reconstructed) cleanly and its
+    /// recomputed hash matches the stored hash
+    Ok,
+    /// The chunk decompressed but its content hash no longer matches
+    HashMismatch,
+    /// The bundle payload could not be decompressed or the delta could not be applied
+    DecompressFailed,
+    /// The chunk is delta-encoded against a base hash that is missing from the index
+    MissingDeltaBase,
+}
+
+/// Result of a full `ChunkStore::verify` scrub pass
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub total: usize,
+    pub ok: usize,
+    pub hash_mismatch: usize,
+    pub decompress_failed: usize,
+    pub missing_delta_base: usize,
+    /// Hashes that failed verification, paired with why
+    pub bad: Vec<(String, VerifyStatus)>,
+}
+
+/// Snapshot of how much delta chaining a store currently has outstanding,
+/// so callers can decide when to `materialize` a chunk and cap reconstruction cost
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeltaChainStats {
+    /// Number of stored chunks whose payload is a delta ([`DELTA_MARKER`])
+    pub delta_count: usize,
+    /// Longest delta chain currently on disk
+    pub max_depth: usize,
+    /// Number of distinct chunks referenced as a base by at least one live delta
+    pub referenced_bases: usize,
+}
+
+/// How much fragmented bundle storage [`ChunkStore::repack`] consolidated -
+/// or, under `dry_run`, would have
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RepackStats {
+    /// Non-active bundle files collapsed into the active bundle sequence
+    pub bundles_repacked: usize,
+    /// Live chunks copied forward during the repack
+    pub chunks_repacked: usize,
+    /// Total bytes of live chunk payload copied forward
+    pub bytes_repacked: u64,
+}
+
+/// Outcome of a mark-and-sweep GC pass over a `ChunkStore` - either an
+/// actual sweep (`garbage_collect_with_grace`) or a `--dry-run` preview of
+/// one (`gc_preview`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GcReport {
+    /// Chunks removed, or that would be removed under `--dry-run`
+    pub chunks_removed: usize,
+    /// Stored (compressed) bytes reclaimed, or that would be reclaimed
+    pub bytes_reclaimed: u64,
+    /// Otherwise-dead chunks spared by the retention grace window
+    pub chunks_retained_by_grace: usize,
+}
+
+/// One chunk's on-disk footprint, used to report [`ChunkStore::stats`]'s
+/// largest entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkFootprint {
+    pub hash: String,
+    pub stored_bytes: u64,
+}
+
+/// Snapshot of a chunk store's health, mirroring rocksdb's `live_files`
+/// metadata API - how much is stored, how well it's being reused, and
+/// whether a `gc` would reclaim anything
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChunkStoreStats {
+    /// Distinct chunks currently in the index
+    pub chunk_count: usize,
+    /// Total stored (compressed) bytes across every indexed chunk
+    pub total_bytes: u64,
+    /// Indexed chunks reachable from the `active_hashes` passed to `stats`,
+    /// directly or as a live delta base
+    pub referenced_chunks: usize,
+    /// Indexed chunks not reachable from `active_hashes` - what a `gc`
+    /// would remove
+    pub orphaned_chunks: usize,
+    /// Largest stored chunks, descending by `stored_bytes`
+    pub largest_chunks: Vec<ChunkFootprint>,
+}
+
+/// A place `ChunkStore::repair` can ask for a known-good copy of a chunk by hash,
+/// e.g. a remote, a peer repository, or a backup bundle directory
+pub trait ChunkSource {
+    /// Return the chunk's raw (uncompressed) data if this source has it
+    fn fetch_chunk(&self, hash: &str) -> Option<Vec<u8>>;
+}
+
 /// Chunk storage manager for handling chunk persistence
+///
+/// Chunks are not stored one-per-file; instead they are appended into
+/// large, sequential "bundle" (pack) files, with an on-disk index mapping
+/// each chunk hash to the bundle and byte offset holding its data. This
+/// avoids the inode/fsync overhead of millions of tiny per-chunk files.
 pub struct ChunkStore {
     /// Base directory for chunk storage
     chunks_dir: PathBuf,
+    /// Directory holding append-only bundle (pack) files
+    bundles_dir: PathBuf,
+    /// Path to the append-only on-disk index log
+    index_log_path: PathBuf,
+    /// In-memory index: chunk hash -> location within a bundle
+    index: RwLock<HashMap<String, BundleLocation>>,
+    /// Bundle currently being appended to
+    active_bundle: Mutex<ActiveBundle>,
     /// Cache of loaded chunks (hash -> data)
     chunk_cache: HashMap<String, Vec<u8>>,
     /// Maximum cache size in bytes
     max_cache_size: usize,
     /// Current cache size in bytes
     current_cache_size: usize,
-    /// Cache of chunks that are known to exist
-    existence_cache: RwLock<HashSet<String>>,
-    /// Cache of chunks that are known to NOT exist
-    negative_cache: RwLock<HashSet<String>>,
     /// Delta compression cache - maps hash to similar chunk hashes
     delta_cache: RwLock<HashMap<String, Vec<String>>>,
+    /// BK-tree over packed min-hash signatures, queried by Hamming distance to
+    /// find delta-base candidates without scanning every stored chunk
+    sketch_tree: RwLock<BkTree>,
+    /// Packed min-hash signature per stored chunk, kept alongside the tree so
+    /// it doesn't need to be recomputed from chunk data
+    chunk_sketches: RwLock<HashMap<String, u64>>,
+    /// Running original-vs-compressed byte totals per codec actually applied
+    /// by `compress_chunk_data`, so `blaze stats` can report a real
+    /// compression ratio per codec instead of just the configured policy
+    compression_stats: RwLock<HashMap<CompressionAlgo, CodecStats>>,
+    /// Hash algorithm this store verifies chunk integrity with
+    hash_algo: HashAlgo,
+    /// Compression policy applied to every chunk payload written to a bundle
+    compression: CompressionConfig,
+    /// FastCDC target/min/max size policy used when this store chunks raw
+    /// bytes itself (`store_bytes_chunked`, incremental re-chunking)
+    chunking: ChunkingConfig,
+    /// Number of chunks coalesced into a single prefetch batch by
+    /// `load_chunks_prefetched`, clamped to [`MAX_MEMORY_BUFFER`] worth of
+    /// chunks so a long file list never forces more in-flight decompression
+    /// buffers than the configured memory budget allows
+    read_amplification_batch: usize,
+    /// Shared throughput budget for bulk chunk writes and bundle compaction,
+    /// or `None` if I/O is unthrottled
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// At-rest encryption applied to every bundle payload on write (and
+    /// undone on read), transparent to compression/delta-encoding since it
+    /// wraps their already-encoded output - see [`Self::encrypt_for_bundle`]
+    encryption: EncryptionConfig,
+    /// Where bundle/index/marker object reads and writes actually go - the
+    /// on-disk `FsBackend` by default, an in-memory backend in tests
+    backend: Arc<dyn StorageBackend>,
 }
 
 impl ChunkStore {
-    /// Create a new chunk store
+    /// Create a new chunk store using the default hash algorithm (XXH3) and
+    /// the default compression policy
     pub fn new<P: AsRef<Path>>(chunks_dir: P) -> Result<Self> {
+        Self::new_with_algo(chunks_dir, HashAlgo::default())
+    }
+
+    /// Create a new chunk store that verifies chunk integrity with `algo`,
+    /// using the default compression policy.
+    ///
+    /// The chosen algorithm is recorded in a small metadata file alongside the
+    /// bundles; reopening the store with a different algorithm fails fast
+    /// instead of silently producing hash-space collisions between repos.
+    pub fn new_with_algo<P: AsRef<Path>>(chunks_dir: P, algo: HashAlgo) -> Result<Self> {
+        Self::new_with_algo_and_compression(chunks_dir, algo, CompressionConfig::default())
+    }
+
+    /// Create a new chunk store that verifies chunk integrity with `algo` and
+    /// compresses chunk payloads according to `compression`, using the
+    /// default chunking policy.
+    ///
+    /// `compression` is validated up front so a bad policy (out-of-range zstd
+    /// level, nonsensical savings ratio) fails at construction rather than on
+    /// the first `store_chunk` call.
+    pub fn new_with_algo_and_compression<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+    ) -> Result<Self> {
+        Self::new_with_config(chunks_dir, algo, compression, ChunkingConfig::default())
+    }
+
+    /// Create a new chunk store that verifies chunk integrity with `algo`,
+    /// compresses chunk payloads according to `compression`, and chunks raw
+    /// bytes (via `store_bytes_chunked`/`store_file_incremental`) according to
+    /// `chunking`.
+    ///
+    /// Both policies are validated up front so a bad config fails at
+    /// construction rather than on the first `store_chunk` call.
+    pub fn new_with_config<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+        chunking: ChunkingConfig,
+    ) -> Result<Self> {
+        Self::new_with_prefetch(
+            chunks_dir,
+            algo,
+            compression,
+            chunking,
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+        )
+    }
+
+    /// Create a new chunk store like [`Self::new_with_config`], but also
+    /// overriding how many chunks `load_chunks_prefetched` coalesces into a
+    /// single batch during checkout/restore.
+    pub fn new_with_prefetch<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+        chunking: ChunkingConfig,
+        read_amplification_batch: usize,
+    ) -> Result<Self> {
+        Self::new_with_rate_limit(
+            chunks_dir,
+            algo,
+            compression,
+            chunking,
+            read_amplification_batch,
+            None,
+        )
+    }
+
+    /// Create a new chunk store like [`Self::new_with_prefetch`], but also
+    /// capping bulk chunk writes and bundle compaction to `rate_limit`
+    /// bytes/sec (`None` leaves I/O unthrottled).
+    pub fn new_with_rate_limit<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+        chunking: ChunkingConfig,
+        read_amplification_batch: usize,
+        rate_limit: Option<u64>,
+    ) -> Result<Self> {
+        Self::new_with_encryption(
+            chunks_dir,
+            algo,
+            compression,
+            chunking,
+            read_amplification_batch,
+            rate_limit,
+            EncryptionConfig::disabled(),
+        )
+    }
+
+    /// Create a new chunk store like [`Self::new_with_rate_limit`], but also
+    /// encrypting every chunk payload at rest under `encryption` - a no-op
+    /// wrapper when `encryption` is [`EncryptionConfig::disabled`].
+    pub fn new_with_encryption<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+        chunking: ChunkingConfig,
+        read_amplification_batch: usize,
+        rate_limit: Option<u64>,
+        encryption: EncryptionConfig,
+    ) -> Result<Self> {
+        Self::new_with_backend(
+            chunks_dir,
+            algo,
+            compression,
+            chunking,
+            read_amplification_batch,
+            rate_limit,
+            encryption,
+            Arc::new(FsBackend::new()),
+        )
+    }
+
+    /// Create a chunk store backed by an arbitrary [`StorageBackend`] instead
+    /// of the default on-disk `FsBackend` - this is what every other
+    /// constructor delegates to. Tests can pass a `MemBackend` to run the
+    /// whole store in memory without touching disk.
+    pub fn new_with_backend<P: AsRef<Path>>(
+        chunks_dir: P,
+        algo: HashAlgo,
+        compression: CompressionConfig,
+        chunking: ChunkingConfig,
+        read_amplification_batch: usize,
+        rate_limit: Option<u64>,
+        encryption: EncryptionConfig,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
+        compression.validate()?;
+        chunking.validate()?;
+
         let chunks_dir = chunks_dir.as_ref().to_path_buf();
+        let bundles_dir = chunks_dir.join(BUNDLES_DIR);
+        let index_log_path = chunks_dir.join(INDEX_LOG_FILE);
 
-        // Create chunks directory if it doesn't exist
-        if !chunks_dir.exists() {
-            fs::create_dir_all(&chunks_dir).with_context(|| {
-                format!(
-                    "Failed to create chunks directory: {}",
-                    chunks_dir.display()
-                )
-            })?;
+        if !backend.exists(&bundles_dir) {
+            backend.create_dir_all(&bundles_dir)?;
         }
 
+        let hash_algo = Self::check_or_record_hash_algo(backend.as_ref(), &chunks_dir, algo)?;
+
+        let index = load_index(backend.as_ref(), &index_log_path)?;
+        let active_bundle = open_active_bundle(backend.as_ref(), &bundles_dir, &index)?;
+
         Ok(ChunkStore {
             chunks_dir,
+            bundles_dir,
+            index_log_path,
+            index: RwLock::new(index),
+            active_bundle: Mutex::new(active_bundle),
             chunk_cache: HashMap::new(),
             max_cache_size: 64 * 1024 * 1024, // 64MB cache
             current_cache_size: 0,
-            existence_cache: RwLock::new(HashSet::new()),
-            negative_cache: RwLock::new(HashSet::new()),
             delta_cache: RwLock::new(HashMap::new()),
+            sketch_tree: RwLock::new(BkTree::default()),
+            chunk_sketches: RwLock::new(HashMap::new()),
+            compression_stats: RwLock::new(HashMap::new()),
+            hash_algo,
+            compression,
+            chunking,
+            read_amplification_batch: Self::clamp_read_amplification_batch(read_amplification_batch),
+            rate_limiter: rate_limit.map(|rate| Arc::new(RateLimiter::new(rate))),
+            encryption,
+            backend,
         })
     }
 
+    /// Clamp a requested prefetch batch size so the in-flight chunks it
+    /// implies (`batch * CHUNK_SIZE`, a conservative upper bound on
+    /// uncompressed chunk size) never exceed [`MAX_MEMORY_BUFFER`]
+    fn clamp_read_amplification_batch(batch: usize) -> usize {
+        let max_batch = (MAX_MEMORY_BUFFER / CHUNK_SIZE).max(1);
+        batch.clamp(1, max_batch)
+    }
+
+    /// Compare `algo` against the algorithm recorded in this store's metadata
+    /// file, creating the file if the store is new. Errors if the store
+    /// already exists with a different algorithm on record.
+    fn check_or_record_hash_algo(
+        backend: &dyn StorageBackend,
+        chunks_dir: &Path,
+        algo: HashAlgo,
+    ) -> Result<HashAlgo> {
+        let marker_path = chunks_dir.join(HASH_ALGO_FILE);
+
+        if backend.exists(&marker_path) {
+            let recorded = backend
+                .read(&marker_path)
+                .context("Failed to read chunk store hash algorithm marker")?;
+            let recorded = String::from_utf8(recorded)
+                .map_err(|_| BlazeError::Chunk("Corrupt hash algorithm marker".to_string()))?;
+            let recorded = HashAlgo::from_marker(recorded.trim())?;
+
+            if recorded.marker() != algo.marker() {
+                return Err(BlazeError::Chunk(format!(
+                    "Chunk store was created with hash algorithm '{}', but '{}' was requested",
+                    recorded.marker(),
+                    algo.marker()
+                )));
+            }
+
+            Ok(recorded)
+        } else {
+            backend
+                .write(&marker_path, algo.marker().as_bytes())
+                .context("Failed to write chunk store hash algorithm marker")?;
+            Ok(algo)
+        }
+    }
+
     /// Store a chunk and return its hash
     pub fn store_chunk(&mut self, chunk: &FileChunk) -> Result<String> {
-        // Check cache first
         if self.chunk_exists(&chunk.hash) {
             return Ok(chunk.hash.clone());
         }
 
-        let chunk_path = self.get_chunk_path(&chunk.hash);
-
-        // Create subdirectory if needed (first 2 chars of hash)
-        if let Some(parent) = chunk_path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!("Failed to create chunk subdirectory: {}", parent.display())
-            })?;
-        }
-
-        // Write compressed chunk data with atomic operation
         let compressed_data = self.compress_chunk_data(&chunk.data)?;
-        let temp_path = chunk_path.with_extension("tmp");
-
-        {
-            let mut file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&temp_path)
-                .with_context(|| {
-                    format!("Failed to create temp chunk file: {}", temp_path.display())
-                })?;
-
-            file.write_all(&compressed_data)
-                .context("Failed to write chunk data")?;
-
-            file.sync_all().context("Failed to sync chunk data")?;
-        }
-
-        // Atomic rename
-        fs::rename(&temp_path, &chunk_path)
-            .with_context(|| format!("Failed to rename temp file: {}", chunk_path.display()))?;
-
-        // Add to cache if there's space
-        self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
+        let location = self.append_to_active_bundle(&compressed_data, IoPriority::Foreground)?;
+        self.append_index_records(&[(chunk.hash.clone(), location)])?;
 
-        // Mark chunk as existing in our existence cache
-        self.existence_cache
+        self.index
             .write()
             .unwrap()
-            .insert(chunk.hash.clone());
-        self.negative_cache.write().unwrap().remove(&chunk.hash);
+            .insert(chunk.hash.clone(), location);
+        self.register_sketch(&chunk.hash, &chunk.data);
+        self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
 
         Ok(chunk.hash.clone())
     }
@@ -129,12 +542,11 @@ impl ChunkStore {
         }
 
         // Aggressive deduplication - group by hash first
-        let mut unique_chunks: std::collections::HashMap<String, &FileChunk> =
-            std::collections::HashMap::new();
+        let mut unique_chunks: HashMap<String, &FileChunk> = HashMap::new();
         let mut dedupe_savings = 0usize;
 
         for chunk in chunks {
-            if let Some(_existing) = unique_chunks.get(&chunk.hash) {
+            if unique_chunks.contains_key(&chunk.hash) {
                 dedupe_savings += chunk.size;
             } else {
                 unique_chunks.insert(chunk.hash.clone(), chunk);
@@ -149,12 +561,10 @@ impl ChunkStore {
             .collect();
 
         if new_chunks.is_empty() {
-            // All chunks already exist - perfect deduplication!
             return Ok(chunks.iter().map(|c| c.hash.clone()).collect());
         }
 
         if dedupe_savings > 0 {
-            // Track deduplication savings for statistics
             #[cfg(debug_assertions)]
             println!(
                 "Deduplicated {} bytes across {} chunks",
@@ -163,53 +573,29 @@ impl ChunkStore {
             );
         }
 
-        // Group chunks by their subdirectory for batch directory creation
-        let mut chunks_by_subdir: HashMap<String, Vec<&FileChunk>> = HashMap::new();
-        for chunk in &new_chunks {
-            let subdir = self.get_chunk_subdir(&chunk.hash);
-            chunks_by_subdir.entry(subdir).or_default().push(chunk);
-        }
-
-        // Create all necessary subdirectories in parallel
-        let subdirs: Vec<String> = chunks_by_subdir.keys().cloned().collect();
-        subdirs.par_iter().try_for_each(|subdir| {
-            let subdir_path = self.chunks_dir.join(subdir);
-            if !subdir_path.exists() {
-                fs::create_dir_all(&subdir_path).with_context(|| {
-                    format!(
-                        "Failed to create chunk subdirectory: {}",
-                        subdir_path.display()
-                    )
-                })
-            } else {
-                Ok(())
-            }
-        })?;
-
-        // Use delta compression for better storage efficiency
+        // Compress in parallel, using delta compression for larger chunks where a
+        // similar base is known. The actual write into a bundle stays sequential.
         let compression_results: Result<Vec<_>> = new_chunks
             .par_iter()
             .map(|chunk| {
-                // Try delta compression first for better efficiency
                 if chunk.data.len() > 1024 {
-                    // Only use delta for chunks > 1KB
                     if let Some(base_hash) = self.find_similar_chunk(&chunk.hash, &chunk.data) {
-                        if let Ok(base_data) = self.load_chunk_uncached(&base_hash) {
-                            let delta = self.create_delta(&base_data, &chunk.data);
-                            if delta.len() < (chunk.data.len() * 8 / 10) {
-                                // Delta is 20%+ smaller, use it
-                                let compressed_delta = self.compress_chunk_data(&delta)?;
-                                let mut delta_data = vec![3]; // 3 = delta compressed
-                                delta_data.extend_from_slice(base_hash.as_bytes());
-                                delta_data.push(0); // null separator
-                                delta_data.extend_from_slice(&compressed_delta);
-                                return Ok((chunk.hash.clone(), delta_data, Some(base_hash)));
+                        if self.delta_depth(&base_hash).unwrap_or(0) < MAX_DELTA_DEPTH {
+                            if let Ok(base_data) = self.load_chunk_uncached(&base_hash) {
+                                let delta = self.create_delta(&base_data, &chunk.data);
+                                if delta.len() < (chunk.data.len() * 8 / 10) {
+                                    let compressed_delta = self.compress_chunk_data(&delta)?;
+                                    let mut delta_data = vec![DELTA_MARKER];
+                                    delta_data.extend_from_slice(base_hash.as_bytes());
+                                    delta_data.push(0); // null separator
+                                    delta_data.extend_from_slice(&compressed_delta);
+                                    return Ok((chunk.hash.clone(), delta_data, Some(base_hash)));
+                                }
                             }
                         }
                     }
                 }
 
-                // Fall back to regular compression
                 let compressed_data = self.compress_chunk_data(&chunk.data)?;
                 Ok((chunk.hash.clone(), compressed_data, None::<String>))
             })
@@ -217,100 +603,168 @@ impl ChunkStore {
 
         let compressed_chunks = compression_results?;
 
-        // Write all chunks in parallel with delta compression support
-        let write_results: Result<Vec<_>> = compressed_chunks
-            .par_iter()
-            .map(|(hash, compressed_data, base_hash_opt)| {
-                let chunk_path = self.get_chunk_path(hash);
+        // Append everything into the active bundle sequentially, then persist the
+        // index in one batch - a handful of large writes instead of many small ones.
+        let mut records = Vec::with_capacity(compressed_chunks.len());
+        for (hash, compressed_data, base_hash_opt) in &compressed_chunks {
+            let location = self.append_to_active_bundle(compressed_data, IoPriority::Foreground)?;
+            records.push((hash.clone(), location));
+
+            if let Some(base_hash) = base_hash_opt {
+                if let Ok(mut cache) = self.delta_cache.write() {
+                    cache
+                        .entry(base_hash.clone())
+                        .or_insert_with(Vec::new)
+                        .push(hash.clone());
+                }
+            }
+        }
 
-                // Use atomic write operations
-                let temp_path = chunk_path.with_extension("tmp");
+        self.append_index_records(&records)?;
 
-                {
-                    let mut file = OpenOptions::new()
-                        .write(true)
-                        .create(true)
-                        .truncate(true)
-                        .open(&temp_path)
-                        .with_context(|| {
-                            format!("Failed to create temp chunk file: {}", temp_path.display())
-                        })?;
+        {
+            let mut index = self.index.write().unwrap();
+            for (hash, location) in &records {
+                index.insert(hash.clone(), *location);
+            }
+        }
 
-                    file.write_all(compressed_data)
-                        .context("Failed to write chunk data")?;
+        for chunk in &new_chunks {
+            self.register_sketch(&chunk.hash, &chunk.data);
+            self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
+        }
 
-                    file.sync_all().context("Failed to sync chunk data")?;
-                }
+        Ok(chunks.iter().map(|c| c.hash.clone()).collect())
+    }
 
-                // Atomic rename
-                fs::rename(&temp_path, &chunk_path).with_context(|| {
-                    format!("Failed to rename temp file: {}", chunk_path.display())
-                })?;
-
-                // Update delta cache if this was a delta chunk
-                if let Some(base_hash) = base_hash_opt {
-                    if let Ok(mut cache) = self.delta_cache.write() {
-                        cache
-                            .entry(base_hash.clone())
-                            .or_insert_with(Vec::new)
-                            .push(hash.clone());
-                    }
-                }
+    /// Store a file's content, reusing a previous chunk list instead of
+    /// re-chunking and re-hashing from scratch when possible.
+    ///
+    /// If `current_fingerprint` matches `prev_fingerprint`, `prev_chunks` is trusted
+    /// wholesale and returned as-is - no bytes of `data` are even touched.
+    /// Otherwise the previous chunks are used to find the unchanged prefix
+    /// and suffix of the file (aligned to whole old chunk boundaries so reuse
+    /// seams line up with chunks already on disk), and only the differing
+    /// middle span is re-chunked and stored.
+    pub fn store_file_incremental(
+        &mut self,
+        data: &[u8],
+        prev_chunks: &[String],
+        prev_fingerprint: files::FileFingerprint,
+        current_fingerprint: files::FileFingerprint,
+    ) -> Result<Vec<String>> {
+        if prev_chunks.is_empty() {
+            return self.store_bytes_chunked(data);
+        }
 
-                Ok(hash.clone())
-            })
-            .collect();
+        if prev_fingerprint == current_fingerprint {
+            return Ok(prev_chunks.to_vec());
+        }
 
-        let _new_hashes = write_results?;
+        // Reconstruct the previous content and remember each old chunk's
+        // start offset within it (`old_chunk_starts[i]`, with a trailing
+        // sentinel at the total length), so a match against the new data can
+        // be snapped to an old chunk boundary rather than an arbitrary byte
+        // offset.
+        let mut old_data = Vec::new();
+        let mut old_chunk_starts = vec![0usize];
+        for hash in prev_chunks {
+            let chunk_data = match self.load_chunk(hash) {
+                Ok(d) => d,
+                // A referenced chunk is gone (e.g. garbage collected); we
+                // can't trust any seam, so fall back to a full re-chunk.
+                Err(_) => return self.store_bytes_chunked(data),
+            };
+            old_data.extend_from_slice(&chunk_data);
+            old_chunk_starts.push(old_data.len());
+        }
 
-        // Update cache for new chunks in batch
-        for chunk in &new_chunks {
-            self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
-            // Mark chunk as existing in our existence cache
-            self.existence_cache
-                .write()
-                .unwrap()
-                .insert(chunk.hash.clone());
-            self.negative_cache.write().unwrap().remove(&chunk.hash);
+        let shared_len = data.len().min(old_data.len());
+        let common_prefix = data
+            .iter()
+            .zip(old_data.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let common_suffix = data[common_prefix..]
+            .iter()
+            .rev()
+            .zip(old_data[common_prefix..].iter().rev())
+            .take(shared_len - common_prefix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let prefix_chunk_count = old_chunk_starts
+            .iter()
+            .take_while(|&&start| start <= common_prefix)
+            .count()
+            - 1;
+        let aligned_prefix_end = old_chunk_starts[prefix_chunk_count];
+
+        let mut suffix_chunk_count = 0;
+        for i in (0..prev_chunks.len()).rev() {
+            let start = old_chunk_starts[i];
+            if start < aligned_prefix_end || old_data.len() - start > common_suffix {
+                break;
+            }
+            suffix_chunk_count += 1;
         }
 
-        // Return all hashes (existing + new)
-        Ok(chunks.iter().map(|c| c.hash.clone()).collect())
+        let aligned_suffix_start = old_chunk_starts[prev_chunks.len() - suffix_chunk_count];
+        let new_suffix_start = data.len() - (old_data.len() - aligned_suffix_start);
+
+        let middle = &data[aligned_prefix_end..new_suffix_start];
+        let middle_chunks = files::chunk_bytes_with(middle, &self.chunking, self.hash_algo);
+
+        let mut result = Vec::with_capacity(
+            prefix_chunk_count + middle_chunks.len() + suffix_chunk_count,
+        );
+        result.extend(prev_chunks[..prefix_chunk_count].iter().cloned());
+        for chunk in &middle_chunks {
+            result.push(self.store_chunk(chunk)?);
+        }
+        result.extend(prev_chunks[prev_chunks.len() - suffix_chunk_count..].iter().cloned());
+
+        Ok(result)
+    }
+
+    /// Content-defined chunk and store a bare byte slice, returning the
+    /// resulting ordered chunk hashes
+    fn store_bytes_chunked(&mut self, data: &[u8]) -> Result<Vec<String>> {
+        files::chunk_bytes_with(data, &self.chunking, self.hash_algo)
+            .iter()
+            .map(|chunk| self.store_chunk(chunk))
+            .collect()
     }
 
     /// Load a chunk by its hash
     pub fn load_chunk(&mut self, hash: &str) -> Result<Vec<u8>> {
-        // Check cache first
         if let Some(data) = self.chunk_cache.get(hash) {
             return Ok(data.clone());
         }
 
         let data = self.load_chunk_uncached(hash)?;
-
-        // Cache the loaded chunk
         self.maybe_cache_chunk(hash, data.clone());
 
         Ok(data)
     }
 
     fn load_chunk_uncached(&self, hash: &str) -> Result<Vec<u8>> {
-        let chunk_path = self.get_chunk_path(hash);
-
-        // Use optimized file reading
-        let mut file = File::open(&chunk_path)
-            .with_context(|| format!("Failed to open chunk file: {}", chunk_path.display()))?;
+        let location = self
+            .index
+            .read()
+            .unwrap()
+            .get(hash)
+            .copied()
+            .ok_or_else(|| BlazeError::Chunk(format!("Chunk not found in index: {}", hash)))?;
 
-        let file_size = file.metadata()?.len() as usize;
-        let mut file_data = Vec::with_capacity(file_size);
-        file.read_to_end(&mut file_data)
-            .context("Failed to read chunk data")?;
+        let file_data = self.read_bundle_range(location)?;
 
         if file_data.is_empty() {
-            return Err(BlazeError::Chunk("Empty chunk file".to_string()));
+            return Err(BlazeError::Chunk("Empty chunk payload".to_string()));
         }
 
         let data = match file_data[0] {
-            3 => {
+            DELTA_MARKER => {
                 // Delta compressed chunk
                 let null_pos = file_data
                     .iter()
@@ -323,26 +777,17 @@ impl ChunkStore {
                 let base_hash = String::from_utf8_lossy(&file_data[1..null_pos]);
                 let compressed_delta = &file_data[null_pos + 1..];
 
-                // Load base chunk
                 let base_data = self.load_chunk_uncached(&base_hash)?;
-
-                // Decompress delta
                 let delta = self.decompress_chunk_data(compressed_delta)?;
 
-                // Apply delta to reconstruct original
                 self.apply_delta(&base_data, &delta)?
             }
-            _ => {
-                // Regular compressed chunk
-                self.decompress_chunk_data(&file_data)?
-            }
+            _ => self.decompress_chunk_data(&file_data)?,
         };
 
-        // Skip integrity check for performance in most cases
-        // Only verify on first load or if explicitly requested
         #[cfg(debug_assertions)]
         {
-            let computed_hash = crate::files::compute_chunk_hash(&data);
+            let computed_hash = files::compute_chunk_hash_with(self.hash_algo, &data);
             if computed_hash != hash {
                 return Err(BlazeError::Chunk(format!(
                     "Chunk integrity check failed: expected {}, got {}",
@@ -363,7 +808,6 @@ impl ChunkStore {
 
         let chunks_data = results?;
 
-        // Update cache for all loaded chunks
         for (hash, data) in hashes.iter().zip(chunks_data.iter()) {
             self.maybe_cache_chunk(hash, data.clone());
         }
@@ -371,657 +815,2457 @@ impl ChunkStore {
         Ok(chunks_data)
     }
 
-    /// Check if a chunk exists in storage with optimized caching
-    pub fn chunk_exists(&self, hash: &str) -> bool {
-        // Check in-memory cache first (fastest)
-        if self.chunk_cache.contains_key(hash) {
-            return true;
-        }
-
-        // Check existence cache (very fast)
-        if self.existence_cache.read().unwrap().contains(hash) {
-            return true;
-        }
-
-        // Check negative cache to avoid repeated filesystem checks
-        if self.negative_cache.read().unwrap().contains(hash) {
-            return false;
+    /// Load every chunk in `hashes`, in order, coalescing reads into batches
+    /// of up to [`Self::read_amplification_batch`] chunks so materializing a
+    /// large multi-chunk file issues a handful of batched, parallel-decompressed
+    /// fetches instead of stalling on one lookup per chunk. Each batch is
+    /// handed to the same worker pool [`Self::load_chunks`] already uses, so
+    /// decompression happens ahead of when the caller actually needs the
+    /// data.
+    pub fn load_chunks_prefetched(&mut self, hashes: &[String]) -> Result<Vec<Vec<u8>>> {
+        let mut data = Vec::with_capacity(hashes.len());
+
+        for batch in hashes.chunks(self.read_amplification_batch) {
+            data.extend(self.load_chunks(batch)?);
         }
 
-        // Finally check filesystem (slowest)
-        let exists = self.get_chunk_path(hash).exists();
+        Ok(data)
+    }
 
-        // Update caches based on result
-        if exists {
-            self.existence_cache
-                .write()
-                .unwrap()
-                .insert(hash.to_string());
-        } else {
-            self.negative_cache
-                .write()
-                .unwrap()
-                .insert(hash.to_string());
+    /// Check if a chunk exists in storage
+    pub fn chunk_exists(&self, hash: &str) -> bool {
+        if self.chunk_cache.contains_key(hash) {
+            return true;
         }
 
-        exists
+        self.index.read().unwrap().contains_key(hash)
     }
 
     /// Get the number of chunks in storage
     pub fn chunk_count(&self) -> Result<usize> {
-        let mut count = 0;
-
-        for entry in fs::read_dir(&self.chunks_dir)? {
-            let entry = entry?;
-            if entry.path().is_dir() {
-                for subentry in fs::read_dir(entry.path())? {
-                    let subentry = subentry?;
-                    if subentry.path().is_file() {
-                        count += 1;
-                    }
-                }
-            }
-        }
+        Ok(self.index.read().unwrap().len())
+    }
 
-        Ok(count)
+    /// Stored (compressed) size of every currently indexed chunk, sorted
+    /// ascending - the basis for reporting a chunk size distribution against
+    /// what's actually on disk right now, rather than a historical record of
+    /// everything ever written
+    pub fn stored_chunk_sizes(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self
+            .index
+            .read()
+            .unwrap()
+            .values()
+            .map(|location| location.stored_len as u64)
+            .collect();
+        sizes.sort_unstable();
+        sizes
     }
 
-    /// Calculate total storage size of all chunks
+    /// Calculate total storage size of all bundle files
     pub fn total_storage_size(&self) -> Result<u64> {
         let mut total_size = 0;
 
-        for entry in fs::read_dir(&self.chunks_dir)? {
-            let entry = entry?;
-            if entry.path().is_dir() {
-                for subentry in fs::read_dir(entry.path())? {
-                    let subentry = subentry?;
-                    if subentry.path().is_file() {
-                        total_size += subentry.metadata()?.len();
-                    }
-                }
+        for path in self.backend.list(&self.bundles_dir)? {
+            if parse_bundle_id(&path.file_name().unwrap_or_default().to_string_lossy()).is_some() {
+                total_size += self.backend.metadata_len(&path)?;
             }
         }
 
         Ok(total_size)
     }
 
-    /// Remove unused chunks (garbage collection)
-    pub fn garbage_collect(&mut self, active_hashes: &[String]) -> Result<usize> {
-        let active_set: std::collections::HashSet<_> = active_hashes.iter().collect();
-        let mut removed_count = 0;
-
-        for entry in fs::read_dir(&self.chunks_dir)? {
-            let entry = entry?;
-            if entry.path().is_dir() {
-                if let Some(subdir_name) = entry.file_name().to_str() {
-                    for subentry in fs::read_dir(entry.path())? {
-                        let subentry = subentry?;
-                        if subentry.path().is_file() {
-                            if let Some(filename) = subentry.file_name().to_str() {
-                                let full_hash = format!("{}{}", subdir_name, filename);
-
-                                if !active_set.contains(&full_hash) {
-                                    fs::remove_file(subentry.path())?;
-
-                                    // Update all caches to reflect removal
-                                    self.chunk_cache.remove(&full_hash);
-                                    self.existence_cache.write().unwrap().remove(&full_hash);
-                                    self.negative_cache.write().unwrap().insert(full_hash);
-
-                                    removed_count += 1;
-                                }
-                            }
-                        }
-                    }
-
-                    // Remove empty directories
-                    if fs::read_dir(entry.path())?.next().is_none() {
-                        let _ = fs::remove_dir(entry.path());
-                    }
-                }
-            }
+    /// Snapshot of chunk count, stored size, largest chunks, and how many
+    /// indexed chunks are referenced vs orphaned, mirroring rocksdb's
+    /// `live_files` metadata API. `active_hashes` is the same live set
+    /// `garbage_collect`/`gc_preview` sweep against, so a caller who wants to
+    /// know whether a `gc` is worthwhile can compute it once and pass it to
+    /// both.
+    pub fn stats(&self, active_hashes: &[String]) -> ChunkStoreStats {
+        let live_set = self.live_set(active_hashes);
+        let index = self.index.read().unwrap();
+
+        let chunk_count = index.len();
+        let total_bytes: u64 = index.values().map(|location| location.stored_len as u64).sum();
+        let referenced_chunks = index.keys().filter(|hash| live_set.contains(*hash)).count();
+        let orphaned_chunks = chunk_count - referenced_chunks;
+
+        let mut largest_chunks: Vec<ChunkFootprint> = index
+            .iter()
+            .map(|(hash, location)| ChunkFootprint {
+                hash: hash.clone(),
+                stored_bytes: location.stored_len as u64,
+            })
+            .collect();
+        largest_chunks.sort_by(|a, b| b.stored_bytes.cmp(&a.stored_bytes));
+        largest_chunks.truncate(10);
+
+        ChunkStoreStats {
+            chunk_count,
+            total_bytes,
+            referenced_chunks,
+            orphaned_chunks,
+            largest_chunks,
         }
-
-        Ok(removed_count)
     }
 
-    /// Clear all caches
-    pub fn clear_cache(&mut self) {
-        self.chunk_cache.clear();
-        self.current_cache_size = 0;
-        self.existence_cache.write().unwrap().clear();
-        self.negative_cache.write().unwrap().clear();
-        self.delta_cache.write().unwrap().clear();
+    /// Remove unused chunks (garbage collection) and compact bundles that have
+    /// dropped below the live-data threshold
+    ///
+    /// A chunk is retained if it's in `active_hashes` OR if a live delta is
+    /// chained to it, directly or transitively - otherwise collecting a base
+    /// out from under a live delta would leave that delta unreconstructible.
+    pub fn garbage_collect(&mut self, active_hashes: &[String]) -> Result<usize> {
+        Ok(self.garbage_collect_with_grace(active_hashes, None)?.chunks_removed)
     }
 
-    /// Get cache statistics
-    pub fn cache_stats(&self) -> (usize, usize, usize) {
-        (
-            self.chunk_cache.len(),
-            self.current_cache_size,
-            self.max_cache_size,
-        )
-    }
+    /// Like `garbage_collect`, but spares any otherwise-dead chunk stored in
+    /// a bundle modified within `grace` of now. Protects a chunk a
+    /// concurrent `store_chunks` call just wrote but hasn't been recorded as
+    /// referenced yet (the caller's own repo lock should already prevent
+    /// that race; `grace` is a second line of defense for a sweep run
+    /// without one).
+    pub fn garbage_collect_with_grace(
+        &mut self,
+        active_hashes: &[String],
+        grace: Option<Duration>,
+    ) -> Result<GcReport> {
+        let (dead_hashes, chunks_retained_by_grace) = self.dead_chunks(active_hashes, grace);
+
+        if dead_hashes.is_empty() {
+            return Ok(GcReport {
+                chunks_removed: 0,
+                bytes_reclaimed: 0,
+                chunks_retained_by_grace,
+            });
+        }
 
-    // Private helper methods
+        let bytes_reclaimed = self.stored_bytes_of(&dead_hashes);
 
-    fn get_chunk_path(&self, hash: &str) -> PathBuf {
-        if hash.len() < 2 {
-            return self.chunks_dir.join(hash);
+        {
+            let mut index = self.index.write().unwrap();
+            for hash in &dead_hashes {
+                index.remove(hash);
+                self.chunk_cache.remove(hash);
+            }
         }
 
-        let subdir = &hash[..2];
-        let filename = &hash[2..];
-        self.chunks_dir.join(subdir).join(filename)
-    }
+        self.compact_bundles()?;
+        self.rewrite_index_log()?;
 
-    fn get_chunk_subdir(&self, hash: &str) -> String {
-        if hash.len() < 2 {
-            "00".to_string()
-        } else {
-            hash[..2].to_string()
-        }
+        Ok(GcReport {
+            chunks_removed: dead_hashes.len(),
+            bytes_reclaimed,
+            chunks_retained_by_grace,
+        })
     }
 
-    fn compress_chunk_data(&self, data: &[u8]) -> Result<Vec<u8>> {
-        if data.len() < 128 {
-            // Don't compress very small chunks
-            let mut result = vec![0]; // 0 = uncompressed
-            result.extend_from_slice(data);
-            Ok(result)
-        } else {
-            // Use aggressive zstd compression for much better storage efficiency
-            let compression_level = if data.len() > 1024 * 1024 {
-                // For large chunks (>1MB), use higher compression
-                6
-            } else if data.len() > 64 * 1024 {
-                // For medium chunks (>64KB), use good compression
-                4
-            } else {
-                // For smaller chunks, use fast compression
-                2
-            };
+    /// Preview what `garbage_collect_with_grace` would do, without deleting
+    /// anything - the `--dry-run` counterpart
+    pub fn gc_preview(&self, active_hashes: &[String], grace: Option<Duration>) -> GcReport {
+        let (dead_hashes, chunks_retained_by_grace) = self.dead_chunks(active_hashes, grace);
+        let bytes_reclaimed = self.stored_bytes_of(&dead_hashes);
 
-            match zstd::bulk::compress(data, compression_level) {
-                Ok(compressed) if compressed.len() < (data.len() * 9 / 10) => {
-                    // Only use compression if it saves at least 10%
-                    let mut result = vec![2]; // 2 = zstd compressed
-                    result.extend_from_slice(&compressed);
-                    Ok(result)
-                }
-                Ok(_) | Err(_) => {
-                    // Try LZ4 as fallback for better compatibility
-                    match lz4_flex::compress_prepend_size(data) {
-                        compressed if compressed.len() < (data.len() * 95 / 100) => {
-                            let mut result = vec![1]; // 1 = LZ4 compressed
-                            result.extend_from_slice(&compressed);
-                            Ok(result)
-                        }
-                        _ => {
-                            // Store uncompressed if nothing helps
-                            let mut result = vec![0]; // 0 = uncompressed
-                            result.extend_from_slice(data);
-                            Ok(result)
-                        }
-                    }
-                }
-            }
+        GcReport {
+            chunks_removed: dead_hashes.len(),
+            bytes_reclaimed,
+            chunks_retained_by_grace,
         }
     }
 
-    fn decompress_chunk_data(&self, compressed: &[u8]) -> Result<Vec<u8>> {
-        if compressed.is_empty() {
-            return Err(BlazeError::Chunk("Empty compressed data".to_string()));
-        }
-
-        match compressed[0] {
-            0 => Ok(compressed[1..].to_vec()), // Uncompressed
-            1 => {
-                // LZ4 compressed
-                lz4_flex::decompress_size_prepended(&compressed[1..])
-                    .map_err(|e| BlazeError::Chunk(format!("LZ4 decompression failed: {}", e)))
-            }
-            2 => {
-                // zstd compressed with automatic size detection
-                zstd::bulk::decompress(&compressed[1..], 16 * 1024 * 1024) // 16MB max decompressed size
-                    .map_err(|e| BlazeError::Chunk(format!("zstd decompression failed: {}", e)))
+    /// Every hash reachable from `active_hashes`, directly or by following
+    /// `delta_base_of` chains - a delta's base must stay live as long as the
+    /// delta itself does, or the delta can no longer be reconstructed
+    fn live_set(&self, active_hashes: &[String]) -> HashSet<String> {
+        let mut live_set: HashSet<String> = active_hashes.iter().cloned().collect();
+
+        for hash in active_hashes {
+            let mut current = hash.clone();
+            while let Ok(Some(base)) = self.delta_base_of(&current) {
+                if !live_set.insert(base.clone()) {
+                    break; // already retained, and therefore its own base too
+                }
+                current = base;
             }
-            _ => Err(BlazeError::Chunk("Unknown compression type".to_string())),
         }
-    }
 
-    /// Create delta between two chunks for superior compression
-    fn create_delta(&self, base_data: &[u8], new_data: &[u8]) -> Vec<u8> {
-        if base_data.is_empty() || new_data.is_empty() {
-            return new_data.to_vec();
-        }
+        live_set
+    }
 
-        // Simple delta compression using XOR and run-length encoding
-        let mut delta = Vec::new();
-        delta.extend_from_slice(&(new_data.len() as u32).to_le_bytes());
+    /// Chunks no longer reachable from `active_hashes` (directly or via a
+    /// live delta chain), minus any spared by the `grace` window, alongside
+    /// how many were spared
+    fn dead_chunks(&self, active_hashes: &[String], grace: Option<Duration>) -> (Vec<String>, usize) {
+        let live_set = self.live_set(active_hashes);
 
-        let max_len = std::cmp::max(base_data.len(), new_data.len());
-        let mut i = 0;
-
-        while i < max_len {
-            let base_byte = if i < base_data.len() { base_data[i] } else { 0 };
-            let new_byte = if i < new_data.len() { new_data[i] } else { 0 };
-            let diff = base_byte ^ new_byte;
-
-            if diff == 0 {
-                // Count consecutive matching bytes
-                let mut count = 0u16;
-                while i + (count as usize) < max_len && count < u16::MAX {
-                    let b_base = if i + (count as usize) < base_data.len() {
-                        base_data[i + (count as usize)]
-                    } else {
-                        0
-                    };
-                    let b_new = if i + (count as usize) < new_data.len() {
-                        new_data[i + (count as usize)]
-                    } else {
-                        0
-                    };
-
-                    if b_base != b_new {
-                        break;
-                    }
-                    count += 1;
-                }
+        let cutoff = grace.and_then(|grace| SystemTime::now().checked_sub(grace));
 
-                // Store "same" marker + count
-                delta.push(0); // 0 = same bytes
-                delta.extend_from_slice(&count.to_le_bytes());
-                i += count as usize;
-            } else {
-                // Count consecutive different bytes
-                let start_i = i;
-                while i < max_len && i - start_i < 255 {
-                    let b_base = if i < base_data.len() { base_data[i] } else { 0 };
-                    let b_new = if i < new_data.len() { new_data[i] } else { 0 };
-
-                    if b_base == b_new {
-                        break;
-                    }
-                    i += 1;
-                }
+        let index = self.index.read().unwrap();
+        let mut dead_hashes = Vec::new();
+        let mut spared = 0;
 
-                let diff_count = (i - start_i) as u8;
-                delta.push(1); // 1 = different bytes
-                delta.push(diff_count);
+        for (hash, location) in index.iter() {
+            if live_set.contains(hash) {
+                continue;
+            }
 
-                // Store the different bytes from new data
-                for j in start_i..i {
-                    if j < new_data.len() {
-                        delta.push(new_data[j]);
-                    } else {
-                        delta.push(0);
-                    }
+            if let Some(cutoff) = cutoff {
+                let within_grace = matches!(
+                    self.bundle_modified(location.bundle_id),
+                    Some(modified) if modified >= cutoff
+                );
+                if within_grace {
+                    spared += 1;
+                    continue;
                 }
             }
+
+            dead_hashes.push(hash.clone());
         }
 
-        delta
+        (dead_hashes, spared)
     }
 
-    /// Apply delta to reconstruct original data
-    fn apply_delta(&self, base_data: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
-        if delta.len() < 4 {
-            return Ok(delta.to_vec());
-        }
+    /// Total stored (compressed) size of the given chunk hashes, as
+    /// currently recorded in the index
+    fn stored_bytes_of(&self, hashes: &[String]) -> u64 {
+        let index = self.index.read().unwrap();
+        hashes
+            .iter()
+            .filter_map(|hash| index.get(hash))
+            .map(|location| location.stored_len as u64)
+            .sum()
+    }
 
-        let original_size = u32::from_le_bytes([delta[0], delta[1], delta[2], delta[3]]) as usize;
-        let mut result = Vec::with_capacity(original_size);
-        let mut delta_pos = 4;
-        let mut base_pos = 0;
+    /// Best-effort last-modified time of a bundle file, used only to apply
+    /// the GC grace window - unavailable (e.g. the in-memory test backend)
+    /// simply means the grace window doesn't protect that bundle
+    fn bundle_modified(&self, bundle_id: u32) -> Option<SystemTime> {
+        std::fs::metadata(self.bundle_path(bundle_id))
+            .ok()?
+            .modified()
+            .ok()
+    }
 
-        while delta_pos < delta.len() && result.len() < original_size {
-            let command = delta[delta_pos];
-            delta_pos += 1;
+    /// Scrub every stored chunk in parallel, decompressing (and resolving delta
+    /// chains) and recomputing its hash, without relying on the debug-only
+    /// integrity check in `load_chunk_uncached`. Use this to detect bit-rot or
+    /// truncated bundle files in release builds.
+    pub fn verify(&self) -> VerifyReport {
+        let hashes: Vec<String> = self.index.read().unwrap().keys().cloned().collect();
 
-            if command == 0 {
-                // Same bytes - copy from base
-                if delta_pos + 2 > delta.len() {
-                    break;
-                }
-                let count = u16::from_le_bytes([delta[delta_pos], delta[delta_pos + 1]]) as usize;
-                delta_pos += 2;
-
-                for _ in 0..count {
-                    if base_pos < base_data.len() && result.len() < original_size {
-                        result.push(base_data[base_pos]);
-                    } else if result.len() < original_size {
-                        result.push(0);
-                    }
-                    base_pos += 1;
+        let results: Vec<(String, VerifyStatus)> = hashes
+            .par_iter()
+            .map(|hash| (hash.clone(), self.verify_single(hash)))
+            .collect();
+
+        let mut report = VerifyReport {
+            total: results.len(),
+            ..Default::default()
+        };
+
+        for (hash, status) in results {
+            match status {
+                VerifyStatus::Ok => report.ok += 1,
+                VerifyStatus::HashMismatch => {
+                    report.hash_mismatch += 1;
+                    report.bad.push((hash, status));
                 }
-            } else if command == 1 {
-                // Different bytes - copy from delta
-                if delta_pos >= delta.len() {
-                    break;
+                VerifyStatus::DecompressFailed => {
+                    report.decompress_failed += 1;
+                    report.bad.push((hash, status));
                 }
-                let count = delta[delta_pos] as usize;
-                delta_pos += 1;
-
-                for _ in 0..count {
-                    if delta_pos < delta.len() && result.len() < original_size {
-                        result.push(delta[delta_pos]);
-                        delta_pos += 1;
-                    } else if result.len() < original_size {
-                        result.push(0);
-                    }
-                    base_pos += 1;
+                VerifyStatus::MissingDeltaBase => {
+                    report.missing_delta_base += 1;
+                    report.bad.push((hash, status));
                 }
             }
         }
 
-        result.resize(original_size, 0);
-        Ok(result)
+        report
     }
 
-    /// Find similar chunk for delta compression
-    fn find_similar_chunk(&self, chunk_hash: &str, chunk_data: &[u8]) -> Option<String> {
-        // Check delta cache first
-        if let Ok(cache) = self.delta_cache.read() {
-            if let Some(similar_hashes) = cache.get(chunk_hash) {
-                for similar_hash in similar_hashes {
-                    if self.chunk_exists(similar_hash) {
-                        return Some(similar_hash.clone());
-                    }
+    fn verify_single(&self, hash: &str) -> VerifyStatus {
+        match self.load_for_verify(hash) {
+            Ok(data) => {
+                if files::compute_chunk_hash_with(self.hash_algo, &data) == hash {
+                    VerifyStatus::Ok
+                } else {
+                    VerifyStatus::HashMismatch
                 }
             }
+            Err(status) => status,
         }
+    }
 
-        // Simple similarity check - find chunks with similar size
-        let target_size = chunk_data.len();
-        let size_tolerance = target_size / 10; // 10% tolerance
+    /// Same resolution path as `load_chunk_uncached`, but classifies every
+    /// failure instead of collapsing it into a single `BlazeError::Chunk`
+    fn load_for_verify(&self, hash: &str) -> std::result::Result<Vec<u8>, VerifyStatus> {
+        let location = self
+            .index
+            .read()
+            .unwrap()
+            .get(hash)
+            .copied()
+            .ok_or(VerifyStatus::MissingDeltaBase)?;
 
-        // Check recently stored chunks for similarity
-        if let Ok(cache) = self.delta_cache.read() {
-            for (existing_hash, _) in cache.iter() {
-                if existing_hash == chunk_hash {
-                    continue;
-                }
+        let file_data = self
+            .read_bundle_range(location)
+            .map_err(|_| VerifyStatus::DecompressFailed)?;
 
-                // Load existing chunk to compare
-                if let Ok(existing_data) = self.load_chunk_uncached(existing_hash) {
-                    let size_diff = if existing_data.len() > target_size {
-                        existing_data.len() - target_size
-                    } else {
-                        target_size - existing_data.len()
-                    };
-
-                    if size_diff <= size_tolerance {
-                        // Calculate simple similarity score
-                        let similarity = Self::calculate_similarity(&existing_data, chunk_data);
-                        if similarity > 0.7 {
-                            // 70% similarity threshold
-                            return Some(existing_hash.clone());
-                        }
-                    }
-                }
+        if file_data.is_empty() {
+            return Err(VerifyStatus::DecompressFailed);
+        }
+
+        match file_data[0] {
+            DELTA_MARKER => {
+                let null_pos = file_data
+                    .iter()
+                    .position(|&x| x == 0)
+                    .unwrap_or(file_data.len());
+                if null_pos >= file_data.len() - 1 {
+                    return Err(VerifyStatus::DecompressFailed);
+                }
+
+                let base_hash = String::from_utf8_lossy(&file_data[1..null_pos]).into_owned();
+                let compressed_delta = &file_data[null_pos + 1..];
+
+                let base_data = self.load_for_verify(&base_hash)?;
+                let delta = self
+                    .decompress_chunk_data(compressed_delta)
+                    .map_err(|_| VerifyStatus::DecompressFailed)?;
+
+                self.apply_delta(&base_data, &delta)
+                    .map_err(|_| VerifyStatus::DecompressFailed)
+            }
+            _ => self
+                .decompress_chunk_data(&file_data)
+                .map_err(|_| VerifyStatus::DecompressFailed),
+        }
+    }
+
+    /// Attempt to fix chunks flagged by `verify` by re-fetching known-good data
+    /// from `sources` (tried in order) and rewriting them into the active
+    /// bundle, swapping the index entry over only once the replacement data's
+    /// hash checks out. Returns the number of chunks actually repaired.
+    pub fn repair<S: ChunkSource>(&mut self, sources: &[S]) -> Result<usize> {
+        let report = self.verify();
+        if report.bad.is_empty() {
+            return Ok(0);
+        }
+
+        let mut records = Vec::new();
+        let mut repaired = 0;
+
+        for (hash, _status) in &report.bad {
+            let fixed = sources.iter().find_map(|source| {
+                source
+                    .fetch_chunk(hash)
+                    .filter(|data| &files::compute_chunk_hash_with(self.hash_algo, data) == hash)
+            });
+
+            if let Some(data) = fixed {
+                let compressed = self.compress_chunk_data(&data)?;
+                let location = self.append_to_active_bundle(&compressed, IoPriority::Foreground)?;
+
+                self.index.write().unwrap().insert(hash.clone(), location);
+                self.chunk_cache.remove(hash);
+                self.register_sketch(hash, &data);
+
+                records.push((hash.clone(), location));
+                repaired += 1;
+            }
+        }
+
+        if !records.is_empty() {
+            self.append_index_records(&records)?;
+        }
+
+        Ok(repaired)
+    }
+
+    /// Clear all caches
+    pub fn clear_cache(&mut self) {
+        self.chunk_cache.clear();
+        self.current_cache_size = 0;
+        self.delta_cache.write().unwrap().clear();
+    }
+
+    /// Get cache statistics
+    pub fn cache_stats(&self) -> (usize, usize, usize) {
+        (
+            self.chunk_cache.len(),
+            self.current_cache_size,
+            self.max_cache_size,
+        )
+    }
+
+    /// The effective compression policy this store applies to new chunks
+    pub fn compression_policy(&self) -> &CompressionConfig {
+        &self.compression
+    }
+
+    /// The effective FastCDC sizing policy this store applies when chunking
+    /// raw bytes itself
+    pub fn chunking_policy(&self) -> &ChunkingConfig {
+        &self.chunking
+    }
+
+    /// The content hash algorithm this store addresses its chunks with
+    pub fn hash_algo(&self) -> HashAlgo {
+        self.hash_algo
+    }
+
+    /// The number of chunks `load_chunks_prefetched` coalesces into a single
+    /// batch, already clamped to fit [`MAX_MEMORY_BUFFER`]
+    pub fn read_amplification_batch(&self) -> usize {
+        self.read_amplification_batch
+    }
+
+    /// The throughput cap, in bytes/sec, this store enforces on bulk chunk
+    /// writes and bundle compaction - `None` if I/O is unthrottled
+    pub fn rate_limit(&self) -> Option<u64> {
+        self.rate_limiter.as_ref().map(|limiter| limiter.rate_bytes_per_sec())
+    }
+
+    /// Original-vs-compressed byte totals observed so far, broken down by
+    /// the codec actually applied to each chunk (not just the configured
+    /// policy - `Auto` resolves to whichever codec it picked per chunk)
+    pub fn compression_stats(&self) -> HashMap<CompressionAlgo, CodecStats> {
+        self.compression_stats.read().unwrap().clone()
+    }
+
+    // Private helper methods
+
+    fn bundle_path(&self, bundle_id: u32) -> PathBuf {
+        self.bundles_dir.join(format!("bundle_{:010}.pack", bundle_id))
+    }
+
+    /// Append already-compressed bytes to the active bundle, rolling over to a
+    /// fresh bundle file once the target size is exceeded. Spends `priority`
+    /// worth of the store's rate-limit budget (a no-op if unthrottled).
+    fn append_to_active_bundle(&self, data: &[u8], priority: IoPriority) -> Result<BundleLocation> {
+        let data = self.encrypt_for_bundle(data)?;
+        let data = data.as_slice();
+
+        if let Some(limiter) = &self.rate_limiter {
+            match priority {
+                IoPriority::Foreground => limiter.acquire(data.len()),
+                IoPriority::Background => limiter.acquire_background(data.len()),
+            }
+        }
+
+        let mut active = self.active_bundle.lock().unwrap();
+
+        if active.size > 0 && active.size + data.len() as u64 > BUNDLE_TARGET_SIZE {
+            active.file.sync().context("Failed to sync bundle")?;
+            let next_id = active.id + 1;
+            let next_path = self.bundle_path(next_id);
+            let next_file = self
+                .backend
+                .open(&next_path, &StorageOpenOptions::new().create(true).append(true))
+                .with_context(|| format!("Failed to create bundle: {}", next_path.display()))?;
+            *active = ActiveBundle {
+                id: next_id,
+                file: next_file,
+                size: 0,
+            };
+        }
+
+        let offset = active.size;
+        active
+            .file
+            .write_all(data)
+            .context("Failed to append chunk to bundle")?;
+        active.size += data.len() as u64;
+
+        Ok(BundleLocation {
+            bundle_id: active.id,
+            offset,
+            stored_len: data.len() as u32,
+        })
+    }
+
+    fn read_bundle_range(&self, location: BundleLocation) -> Result<Vec<u8>> {
+        let path = self.bundle_path(location.bundle_id);
+        let mut file = self
+            .backend
+            .open(&path, &StorageOpenOptions::new().read(true))
+            .with_context(|| format!("Failed to open bundle: {}", path.display()))?;
+
+        file.seek(SeekFrom::Start(location.offset))
+            .context("Failed to seek within bundle")?;
+
+        let mut buf = vec![0u8; location.stored_len as usize];
+        file.read_exact(&mut buf)
+            .context("Failed to read chunk from bundle")?;
+
+        self.decrypt_from_bundle(buf)
+    }
+
+    fn append_index_records(&self, records: &[(String, BundleLocation)]) -> Result<()> {
+        let mut file = self
+            .backend
+            .open(
+                &self.index_log_path,
+                &StorageOpenOptions::new().create(true).append(true),
+            )
+            .context("Failed to open chunk index log")?;
+
+        for (hash, location) in records {
+            file.write_all(&encode_index_record(hash, *location))
+                .context("Failed to append chunk index record")?;
+        }
+
+        file.sync().context("Failed to sync chunk index log")?;
+        Ok(())
+    }
+
+    fn rewrite_index_log(&self) -> Result<()> {
+        let index = self.index.read().unwrap();
+        let tmp_path = self.index_log_path.with_extension("log.tmp");
+
+        {
+            let mut file = self
+                .backend
+                .open(
+                    &tmp_path,
+                    &StorageOpenOptions::new()
+                        .create(true)
+                        .write(true)
+                        .truncate(true),
+                )
+                .context("Failed to create temp chunk index log")?;
+
+            for (hash, location) in index.iter() {
+                file.write_all(&encode_index_record(hash, *location))
+                    .context("Failed to write chunk index record")?;
+            }
+
+            file.sync().context("Failed to sync temp chunk index log")?;
+        }
+
+        self.backend
+            .rename(&tmp_path, &self.index_log_path)
+            .context("Failed to replace chunk index log")?;
+
+        Ok(())
+    }
+
+    /// Every chunk hash currently indexed, grouped by the bundle it lives in
+    fn live_entries_by_bundle(&self) -> HashMap<u32, Vec<(String, BundleLocation)>> {
+        let mut live_by_bundle: HashMap<u32, Vec<(String, BundleLocation)>> = HashMap::new();
+        let index = self.index.read().unwrap();
+        for (hash, location) in index.iter() {
+            live_by_bundle
+                .entry(location.bundle_id)
+                .or_default()
+                .push((hash.clone(), *location));
+        }
+        live_by_bundle
+    }
+
+    /// IDs of every bundle file on disk other than the one still being
+    /// appended to
+    fn closed_bundle_ids(&self, active_bundle_id: u32) -> Result<HashSet<u32>> {
+        let mut bundle_ids = HashSet::new();
+        for path in self.backend.list(&self.bundles_dir)? {
+            if let Some(id) = parse_bundle_id(&path.file_name().unwrap_or_default().to_string_lossy()) {
+                if id != active_bundle_id {
+                    bundle_ids.insert(id);
+                }
+            }
+        }
+        Ok(bundle_ids)
+    }
+
+    /// Rewrite bundles whose live-data ratio has dropped below the compaction
+    /// threshold, copying surviving chunks forward and dropping the rest
+    fn compact_bundles(&mut self) -> Result<()> {
+        let active_bundle_id = self.active_bundle.lock().unwrap().id;
+        let mut live_by_bundle = self.live_entries_by_bundle();
+
+        for bundle_id in self.closed_bundle_ids(active_bundle_id)? {
+            let bundle_path = self.bundle_path(bundle_id);
+            let total_bytes = self.backend.metadata_len(&bundle_path)?;
+            if total_bytes == 0 {
+                continue;
+            }
+
+            let mut live_entries = live_by_bundle.remove(&bundle_id).unwrap_or_default();
+            let live_bytes: u64 = live_entries.iter().map(|(_, loc)| loc.stored_len as u64).sum();
+
+            if (live_bytes as f64) / (total_bytes as f64) >= COMPACTION_LIVE_THRESHOLD {
+                continue;
+            }
+
+            live_entries.sort_by_key(|(_, loc)| loc.offset);
+
+            let mut new_locations = Vec::with_capacity(live_entries.len());
+            for (hash, location) in &live_entries {
+                let data = self.read_bundle_range(*location)?;
+                let new_location = self.append_to_active_bundle(&data, IoPriority::Background)?;
+                new_locations.push((hash.clone(), new_location));
+            }
+
+            {
+                let mut index = self.index.write().unwrap();
+                for (hash, new_location) in &new_locations {
+                    index.insert(hash.clone(), *new_location);
+                }
+            }
+
+            self.backend
+                .remove(&bundle_path)
+                .with_context(|| format!("Failed to remove compacted bundle: {}", bundle_path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Force a full consolidation of every closed bundle into the active
+    /// bundle sequence, regardless of [`COMPACTION_LIVE_THRESHOLD`] - unlike
+    /// `compact_bundles` (only touches bundles that have fragmented past the
+    /// threshold), this repacks every closed bundle that still holds live
+    /// data, so a long-lived repo ends up with as few bundle files as its
+    /// live data actually needs.
+    ///
+    /// A bundle with no indexed chunks left is skipped rather than repacked
+    /// - that's dead weight for `garbage_collect` to drop, not data to carry
+    /// forward. `dry_run` reports what would be repacked without touching
+    /// any bundle or the index.
+    pub fn repack(&mut self, dry_run: bool) -> Result<RepackStats> {
+        let active_bundle_id = self.active_bundle.lock().unwrap().id;
+        let mut live_by_bundle = self.live_entries_by_bundle();
+        let mut stats = RepackStats::default();
+
+        for bundle_id in self.closed_bundle_ids(active_bundle_id)? {
+            let bundle_path = self.bundle_path(bundle_id);
+            if self.backend.metadata_len(&bundle_path)? == 0 {
+                continue;
+            }
+
+            let mut live_entries = match live_by_bundle.remove(&bundle_id) {
+                Some(entries) if !entries.is_empty() => entries,
+                _ => continue,
+            };
+
+            live_entries.sort_by_key(|(_, loc)| loc.offset);
+            let live_bytes: u64 = live_entries.iter().map(|(_, loc)| loc.stored_len as u64).sum();
+
+            stats.bundles_repacked += 1;
+            stats.chunks_repacked += live_entries.len();
+            stats.bytes_repacked += live_bytes;
+
+            if dry_run {
+                continue;
+            }
+
+            let mut new_locations = Vec::with_capacity(live_entries.len());
+            for (hash, location) in &live_entries {
+                let data = self.read_bundle_range(*location)?;
+                let new_location = self.append_to_active_bundle(&data, IoPriority::Background)?;
+                new_locations.push((hash.clone(), new_location));
+            }
+
+            {
+                let mut index = self.index.write().unwrap();
+                for (hash, new_location) in &new_locations {
+                    index.insert(hash.clone(), *new_location);
+                }
+            }
+
+            self.backend
+                .remove(&bundle_path)
+                .with_context(|| format!("Failed to remove repacked bundle: {}", bundle_path.display()))?;
+        }
+
+        if !dry_run && stats.bundles_repacked > 0 {
+            self.rewrite_index_log()?;
+        }
+
+        Ok(stats)
+    }
+
+    /// zstd level to use for a payload of `len` bytes under the current policy
+    fn zstd_level_for(&self, len: usize) -> i32 {
+        self.level_for(len, 22)
+    }
+
+    /// gzip/deflate level (1-9) to use for a payload of `len` bytes under the
+    /// current policy
+    fn deflate_level_for(&self, len: usize) -> u32 {
+        self.level_for(len, 9).clamp(1, 9) as u32
+    }
+
+    /// Brotli quality (0-11) to use for a payload of `len` bytes under the
+    /// current policy
+    fn brotli_level_for(&self, len: usize) -> i32 {
+        self.level_for(len, 11).clamp(0, 11)
+    }
+
+    /// Shared Auto/Fixed heuristic behind the per-codec `*_level_for` helpers:
+    /// bigger payloads get a lower level so compression time scales with the
+    /// data rather than always paying for the strongest setting
+    fn level_for(&self, len: usize, max_level: i32) -> i32 {
+        match self.compression.level {
+            CompressionLevel::Fixed(level) => level,
+            CompressionLevel::Auto => {
+                if len > 1024 * 1024 {
+                    max_level / 3
+                } else if len > 64 * 1024 {
+                    max_level / 2
+                } else {
+                    max_level
+                }
+            }
+        }
+    }
+
+    fn compress_chunk_data(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let policy = &self.compression;
+        let uncompressed = |data: &[u8]| {
+            let mut result = vec![0]; // 0 = uncompressed
+            result.extend_from_slice(data);
+            result
+        };
+
+        if data.len() < policy.min_size || policy.algo == CompressionAlgo::None {
+            return Ok(uncompressed(data));
+        }
+
+        // A compressed payload must shave off at least `min_savings_ratio` of
+        // the original size to be kept over storing it raw.
+        let max_compressed_len = (data.len() as f64 * (1.0 - policy.min_savings_ratio)) as usize;
+
+        let try_zstd = |data: &[u8]| -> Option<Vec<u8>> {
+            let level = self.zstd_level_for(data.len());
+            match zstd::bulk::compress(data, level) {
+                Ok(compressed) if compressed.len() <= max_compressed_len => {
+                    let mut result = vec![2]; // 2 = zstd compressed
+                    result.extend_from_slice(&compressed);
+                    Some(result)
+                }
+                _ => None,
+            }
+        };
+
+        let try_lz4 = |data: &[u8]| -> Option<Vec<u8>> {
+            let compressed = lz4_flex::compress_prepend_size(data);
+            if compressed.len() <= max_compressed_len {
+                let mut result = vec![1]; // 1 = LZ4 compressed
+                result.extend_from_slice(&compressed);
+                Some(result)
+            } else {
+                None
+            }
+        };
+
+        let try_gzip = |data: &[u8]| -> Option<Vec<u8>> {
+            use flate2::{write::GzEncoder, Compression};
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.deflate_level_for(data.len())));
+            encoder.write_all(data).ok()?;
+            let compressed = encoder.finish().ok()?;
+            if compressed.len() <= max_compressed_len {
+                let mut result = vec![3]; // 3 = gzip compressed
+                result.extend_from_slice(&compressed);
+                Some(result)
+            } else {
+                None
+            }
+        };
+
+        let try_deflate = |data: &[u8]| -> Option<Vec<u8>> {
+            use flate2::{write::DeflateEncoder, Compression};
+            let mut encoder =
+                DeflateEncoder::new(Vec::new(), Compression::new(self.deflate_level_for(data.len())));
+            encoder.write_all(data).ok()?;
+            let compressed = encoder.finish().ok()?;
+            if compressed.len() <= max_compressed_len {
+                let mut result = vec![4]; // 4 = raw DEFLATE compressed
+                result.extend_from_slice(&compressed);
+                Some(result)
+            } else {
+                None
+            }
+        };
+
+        let try_brotli = |data: &[u8]| -> Option<Vec<u8>> {
+            let mut compressed = Vec::new();
+            let params = brotli::enc::BrotliEncoderParams {
+                quality: self.brotli_level_for(data.len()),
+                ..Default::default()
+            };
+            brotli::BrotliCompress(&mut std::io::Cursor::new(data), &mut compressed, &params).ok()?;
+            if compressed.len() <= max_compressed_len {
+                let mut result = vec![5]; // 5 = Brotli compressed
+                result.extend_from_slice(&compressed);
+                Some(result)
+            } else {
+                None
+            }
+        };
+
+        let result = match policy.algo {
+            CompressionAlgo::None => None,
+            CompressionAlgo::Lz4 => try_lz4(data),
+            CompressionAlgo::Zstd => try_zstd(data),
+            CompressionAlgo::Gzip => try_gzip(data),
+            CompressionAlgo::Deflate => try_deflate(data),
+            CompressionAlgo::Brotli => try_brotli(data),
+            CompressionAlgo::Auto => try_zstd(data).or_else(|| try_lz4(data)),
+        };
+
+        let stored = result.unwrap_or_else(|| uncompressed(data));
+        self.record_compression_stats(codec_for_marker(stored[0]), data.len(), stored.len());
+
+        Ok(stored)
+    }
+
+    /// Tally `original_len` -> `stored_len` (including the marker byte) under
+    /// `codec` for later reporting via [`Self::compression_stats`]
+    fn record_compression_stats(&self, codec: CompressionAlgo, original_len: usize, stored_len: usize) {
+        let mut stats = self.compression_stats.write().unwrap();
+        let entry = stats.entry(codec).or_default();
+        entry.chunk_count += 1;
+        entry.original_bytes += original_len as u64;
+        entry.compressed_bytes += stored_len as u64;
+    }
+
+    fn decompress_chunk_data(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        if compressed.is_empty() {
+            return Err(BlazeError::Chunk("Empty compressed data".to_string()));
+        }
+
+        match compressed[0] {
+            0 => Ok(compressed[1..].to_vec()),
+            1 => lz4_flex::decompress_size_prepended(&compressed[1..])
+                .map_err(|e| BlazeError::Chunk(format!("LZ4 decompression failed: {}", e))),
+            2 => zstd::bulk::decompress(&compressed[1..], 16 * 1024 * 1024)
+                .map_err(|e| BlazeError::Chunk(format!("zstd decompression failed: {}", e))),
+            3 => {
+                let mut out = Vec::new();
+                flate2::read::GzDecoder::new(&compressed[1..])
+                    .read_to_end(&mut out)
+                    .map_err(|e| BlazeError::Chunk(format!("gzip decompression failed: {}", e)))?;
+                Ok(out)
+            }
+            4 => {
+                let mut out = Vec::new();
+                flate2::read::DeflateDecoder::new(&compressed[1..])
+                    .read_to_end(&mut out)
+                    .map_err(|e| BlazeError::Chunk(format!("deflate decompression failed: {}", e)))?;
+                Ok(out)
+            }
+            5 => {
+                let mut out = Vec::new();
+                brotli::BrotliDecompress(&mut std::io::Cursor::new(&compressed[1..]), &mut out)
+                    .map_err(|e| BlazeError::Chunk(format!("Brotli decompression failed: {}", e)))?;
+                Ok(out)
+            }
+            _ => Err(BlazeError::Chunk("Unknown compression type".to_string())),
+        }
+    }
+
+    /// Encrypt `data` (already compression/delta-encoded, marker byte and
+    /// all) with AES-256-GCM under a fresh random nonce if encryption is
+    /// configured, returning it untouched otherwise. The nonce is never
+    /// reused: a new one is drawn from the OS RNG on every call, including
+    /// when `compact_bundles`/`repack` re-encrypt a chunk under a new
+    /// location.
+    fn encrypt_for_bundle(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let Some(key) = self.encryption.key() else {
+            return Ok(data.to_vec());
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext = cipher
+            .encrypt(nonce, data)
+            .map_err(|_| BlazeError::Chunk("Failed to encrypt chunk payload".to_string()))?;
+
+        let mut wrapped = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+        wrapped.push(ENCRYPTED_MARKER);
+        wrapped.extend_from_slice(&nonce_bytes);
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    /// Undo [`Self::encrypt_for_bundle`]: pass `data` through unchanged if it
+    /// isn't marked as encrypted, otherwise verify its AEAD tag and decrypt
+    /// it, failing rather than returning tampered or corrupt data.
+    fn decrypt_from_bundle(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        if data.first() != Some(&ENCRYPTED_MARKER) {
+            return Ok(data);
+        }
+
+        let Some(key) = self.encryption.key() else {
+            return Err(BlazeError::Chunk(
+                "Chunk payload is encrypted but no decryption key is configured".to_string(),
+            ));
+        };
+
+        if data.len() < 1 + NONCE_LEN {
+            return Err(BlazeError::Chunk("Truncated encrypted chunk payload".to_string()));
+        }
+
+        let nonce = Nonce::from_slice(&data[1..1 + NONCE_LEN]);
+        let ciphertext = &data[1 + NONCE_LEN..];
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        cipher.decrypt(nonce, ciphertext).map_err(|_| {
+            BlazeError::Chunk(
+                "Chunk payload failed authentication - corrupt or tampered".to_string(),
+            )
+        })
+    }
+
+    /// Create a copy/insert delta (xdelta/bsdiff style) that reconstructs
+    /// `new_data` from `base_data` plus a stream of COPY and INSERT ops.
+    ///
+    /// A rolling hash is computed over every fixed-width window of `base_data`
+    /// and indexed; `new_data` is then scanned with the same window, and hash
+    /// hits are verified byte-for-byte and greedily extended into COPY ops so a
+    /// single inserted/deleted byte doesn't blow up the whole rest of the delta.
+    fn create_delta(&self, base_data: &[u8], new_data: &[u8]) -> Vec<u8> {
+        const WINDOW: usize = 16;
+
+        let mut delta = Vec::new();
+        delta.extend_from_slice(&(new_data.len() as u32).to_le_bytes());
+
+        if base_data.len() < WINDOW || new_data.is_empty() {
+            encode_insert_op(&mut delta, new_data);
+            return delta;
+        }
+
+        let base_index = build_rolling_hash_index(base_data, WINDOW);
+
+        let mut pos = 0;
+        let mut pending_insert_start = 0;
+
+        while pos < new_data.len() {
+            let mut matched = false;
+
+            if pos + WINDOW <= new_data.len() {
+                let window_hash = rolling_hash(&new_data[pos..pos + WINDOW]);
+
+                if let Some(candidates) = base_index.get(&window_hash) {
+                    // Extend every verified candidate and keep the one giving
+                    // the longest total copy, not just the first that matches -
+                    // a short accidental match earlier in the base would
+                    // otherwise win over a much longer one later in it.
+                    let mut best: Option<(usize, usize, usize)> = None; // (base_off, match_len, back)
+
+                    for &base_off in candidates {
+                        if base_data[base_off..base_off + WINDOW] != new_data[pos..pos + WINDOW] {
+                            continue; // hash collision - always verify bytes
+                        }
+
+                        let mut match_len = WINDOW;
+                        while base_off + match_len < base_data.len()
+                            && pos + match_len < new_data.len()
+                            && base_data[base_off + match_len] == new_data[pos + match_len]
+                        {
+                            match_len += 1;
+                        }
+
+                        let mut back = 0;
+                        let max_back = std::cmp::min(base_off, pos - pending_insert_start);
+                        while back < max_back
+                            && base_data[base_off - back - 1] == new_data[pos - back - 1]
+                        {
+                            back += 1;
+                        }
+
+                        if best.map_or(true, |(_, len, b)| match_len + back > len + b) {
+                            best = Some((base_off, match_len, back));
+                        }
+                    }
+
+                    if let Some((base_off, match_len, back)) = best {
+                        if pending_insert_start < pos - back {
+                            encode_insert_op(&mut delta, &new_data[pending_insert_start..pos - back]);
+                        }
+
+                        encode_copy_op(&mut delta, (base_off - back) as u64, (match_len + back) as u64);
+
+                        pos += match_len;
+                        pending_insert_start = pos;
+                        matched = true;
+                    }
+                }
+            }
+
+            if !matched {
+                pos += 1;
+            }
+        }
+
+        if pending_insert_start < new_data.len() {
+            encode_insert_op(&mut delta, &new_data[pending_insert_start..]);
+        }
+
+        delta
+    }
+
+    /// Apply a copy/insert delta to reconstruct the original data
+    fn apply_delta(&self, base_data: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+        if delta.len() < 4 {
+            return Ok(delta.to_vec());
+        }
+
+        let original_size = u32::from_le_bytes([delta[0], delta[1], delta[2], delta[3]]) as usize;
+        let mut result = Vec::with_capacity(original_size);
+        let mut pos = 4;
+
+        while pos < delta.len() && result.len() < original_size {
+            let tag = delta[pos];
+            pos += 1;
+
+            match tag {
+                0 => {
+                    let (base_off, new_pos) = read_varint(delta, pos)?;
+                    let (len, new_pos) = read_varint(delta, new_pos)?;
+                    pos = new_pos;
+
+                    let base_off = base_off as usize;
+                    let len = len as usize;
+                    if base_off + len > base_data.len() {
+                        return Err(BlazeError::Chunk(
+                            "Delta COPY op out of bounds".to_string(),
+                        ));
+                    }
+                    result.extend_from_slice(&base_data[base_off..base_off + len]);
+                }
+                1 => {
+                    let (len, new_pos) = read_varint(delta, pos)?;
+                    let len = len as usize;
+                    if new_pos + len > delta.len() {
+                        return Err(BlazeError::Chunk(
+                            "Delta INSERT op out of bounds".to_string(),
+                        ));
+                    }
+                    result.extend_from_slice(&delta[new_pos..new_pos + len]);
+                    pos = new_pos + len;
+                }
+                _ => return Err(BlazeError::Chunk(format!("Unknown delta op tag: {}", tag))),
+            }
+        }
+
+        result.resize(original_size, 0);
+        Ok(result)
+    }
+
+    /// If `hash`'s stored payload is a delta (marked with [`DELTA_MARKER`]),
+    /// return the base hash it's chained to - without decompressing or
+    /// applying anything
+    fn delta_base_of(&self, hash: &str) -> Result<Option<String>> {
+        let location = self
+            .index
+            .read()
+            .unwrap()
+            .get(hash)
+            .copied()
+            .ok_or_else(|| BlazeError::Chunk(format!("Chunk not found in index: {}", hash)))?;
+
+        let file_data = self.read_bundle_range(location)?;
+        if file_data.first() != Some(&DELTA_MARKER) {
+            return Ok(None);
+        }
+
+        let null_pos = file_data
+            .iter()
+            .position(|&x| x == 0)
+            .unwrap_or(file_data.len());
+        if null_pos >= file_data.len() - 1 {
+            return Err(BlazeError::Chunk("Invalid delta format".to_string()));
+        }
+
+        Ok(Some(
+            String::from_utf8_lossy(&file_data[1..null_pos]).into_owned(),
+        ))
+    }
+
+    /// Number of deltas chained beneath `hash` (0 if it isn't a delta itself)
+    fn delta_depth(&self, hash: &str) -> Result<usize> {
+        let mut depth = 0;
+        let mut current = hash.to_string();
+
+        // Chains are never meant to loop; bail out rather than spin forever
+        // if bookkeeping ever lets one sneak in.
+        while depth <= MAX_DELTA_DEPTH * 2 {
+            match self.delta_base_of(&current)? {
+                Some(base) => {
+                    depth += 1;
+                    current = base;
+                }
+                None => break,
+            }
+        }
+
+        Ok(depth)
+    }
+
+    /// Rewrite `hash` as a standalone compressed chunk if it's currently a
+    /// delta, so reading it no longer has to walk and resolve a chain. Used
+    /// to cap worst-case read amplification on chunks whose chain has grown
+    /// too deep. A no-op if `hash` isn't a delta.
+    pub fn materialize(&mut self, hash: &str) -> Result<()> {
+        if self.delta_base_of(hash)?.is_none() {
+            return Ok(());
+        }
+
+        let data = self.load_chunk_uncached(hash)?;
+        let compressed = self.compress_chunk_data(&data)?;
+        let location = self.append_to_active_bundle(&compressed, IoPriority::Foreground)?;
+
+        self.index.write().unwrap().insert(hash.to_string(), location);
+        self.append_index_records(&[(hash.to_string(), location)])?;
+        self.chunk_cache.remove(hash);
+
+        Ok(())
+    }
+
+    /// Summarize how much delta chaining this store currently has on disk
+    pub fn delta_chain_stats(&self) -> Result<DeltaChainStats> {
+        let hashes: Vec<String> = self.index.read().unwrap().keys().cloned().collect();
+
+        let mut stats = DeltaChainStats::default();
+        let mut referenced_bases = HashSet::new();
+
+        for hash in &hashes {
+            if let Some(base) = self.delta_base_of(hash)? {
+                stats.delta_count += 1;
+                referenced_bases.insert(base);
+
+                let depth = self.delta_depth(hash)?;
+                stats.max_depth = stats.max_depth.max(depth);
+            }
+        }
+
+        stats.referenced_bases = referenced_bases.len();
+        Ok(stats)
+    }
+
+    /// Find similar chunk for delta compression
+    ///
+    /// First checks `delta_cache` for a chunk already known to derive from a
+    /// live base (cheap fast path for repeated near-duplicates). Otherwise
+    /// packs `chunk_data`'s min-hash sketch into a signature and queries the
+    /// BK-tree for the closest indexed signature within
+    /// `MAX_SKETCH_HAMMING_RADIUS`, turning base selection into a
+    /// bounded-radius tree lookup instead of a scan over every stored chunk.
+    /// Returns `None` when nothing is within radius, so the caller falls back
+    /// to full compression.
+    fn find_similar_chunk(&self, chunk_hash: &str, chunk_data: &[u8]) -> Option<String> {
+        if let Ok(cache) = self.delta_cache.read() {
+            if let Some(similar_hashes) = cache.get(chunk_hash) {
+                for similar_hash in similar_hashes {
+                    if self.chunk_exists(similar_hash) {
+                        return Some(similar_hash.clone());
+                    }
+                }
+            }
+        }
+
+        let signature = compute_minhash_signature(chunk_data)?;
+
+        let tree = self.sketch_tree.read().unwrap();
+        tree.query(signature, MAX_SKETCH_HAMMING_RADIUS)
+            .into_iter()
+            .filter(|(_, hash)| *hash != chunk_hash)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, hash)| hash.to_string())
+    }
+
+    /// Compute and register the packed min-hash signature for a newly stored
+    /// chunk, inserting it into the BK-tree so future calls to
+    /// `find_similar_chunk` can find it as a delta base.
+    fn register_sketch(&self, hash: &str, data: &[u8]) {
+        let Some(signature) = compute_minhash_signature(data) else {
+            return;
+        };
+
+        self.sketch_tree
+            .write()
+            .unwrap()
+            .insert(signature, hash.to_string());
+
+        self.chunk_sketches
+            .write()
+            .unwrap()
+            .insert(hash.to_string(), signature);
+    }
+
+    /// Store chunk with delta compression if beneficial
+    pub fn store_chunk_with_delta(&mut self, chunk: &FileChunk) -> Result<String> {
+        if self.chunk_exists(&chunk.hash) {
+            return Ok(chunk.hash.clone());
+        }
+
+        if let Some(base_hash) = self.find_similar_chunk(&chunk.hash, &chunk.data) {
+            let base_depth = self.delta_depth(&base_hash).unwrap_or(0);
+
+            if base_depth < MAX_DELTA_DEPTH {
+                if let Ok(base_data) = self.load_chunk_uncached(&base_hash) {
+                    let delta = self.create_delta(&base_data, &chunk.data);
+
+                    if delta.len() < (chunk.data.len() * 7 / 10) {
+                        let compressed_delta = self.compress_chunk_data(&delta)?;
+
+                        let mut delta_file_data = vec![DELTA_MARKER];
+                        delta_file_data.extend_from_slice(base_hash.as_bytes());
+                        delta_file_data.push(0); // null separator
+                        delta_file_data.extend_from_slice(&compressed_delta);
+
+                        let location = self.append_to_active_bundle(&delta_file_data, IoPriority::Foreground)?;
+                        self.append_index_records(&[(chunk.hash.clone(), location)])?;
+                        self.index
+                            .write()
+                            .unwrap()
+                            .insert(chunk.hash.clone(), location);
+
+                        self.register_sketch(&chunk.hash, &chunk.data);
+                        self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
+
+                        self.delta_cache
+                            .write()
+                            .unwrap()
+                            .entry(base_hash)
+                            .or_default()
+                            .push(chunk.hash.clone());
+
+                        return Ok(chunk.hash.clone());
+                    }
+                }
+            }
+        }
+
+        self.store_chunk(chunk)
+    }
+
+    fn maybe_cache_chunk(&mut self, hash: &str, data: Vec<u8>) {
+        let data_size = data.len();
+
+        if data_size > self.max_cache_size / 4 {
+            return;
+        }
+
+        while self.current_cache_size + data_size > self.max_cache_size
+            && !self.chunk_cache.is_empty()
+        {
+            if let Some((old_hash, old_data)) = self.chunk_cache.iter().next() {
+                let old_hash = old_hash.clone();
+                let old_size = old_data.len();
+                self.chunk_cache.remove(&old_hash);
+                self.current_cache_size -= old_size;
+            } else {
+                break;
+            }
+        }
+
+        self.chunk_cache.insert(hash.to_string(), data);
+        self.current_cache_size += data_size;
+    }
+}
+
+/// Map a `compress_chunk_data` marker byte back to the codec that produced
+/// it, for stats purposes - this only ever sees the marker `compress_chunk_data`
+/// itself just wrote, never a byte read back from a bundle.
+fn codec_for_marker(marker: u8) -> CompressionAlgo {
+    match marker {
+        1 => CompressionAlgo::Lz4,
+        2 => CompressionAlgo::Zstd,
+        3 => CompressionAlgo::Gzip,
+        4 => CompressionAlgo::Deflate,
+        5 => CompressionAlgo::Brotli,
+        _ => CompressionAlgo::None,
+    }
+}
+
+fn parse_bundle_id(file_name: &str) -> Option<u32> {
+    file_name
+        .strip_prefix("bundle_")?
+        .strip_suffix(".pack")?
+        .parse()
+        .ok()
+}
+
+fn encode_index_record(hash: &str, location: BundleLocation) -> Vec<u8> {
+    let hash_bytes = hash.as_bytes();
+    debug_assert!(hash_bytes.len() <= u8::MAX as usize, "hash too long to index");
+
+    let mut record = Vec::with_capacity(1 + hash_bytes.len() + INDEX_RECORD_TRAILER_SIZE);
+    record.push(hash_bytes.len() as u8);
+    record.extend_from_slice(hash_bytes);
+    record.extend_from_slice(&location.bundle_id.to_le_bytes());
+    record.extend_from_slice(&location.offset.to_le_bytes());
+    record.extend_from_slice(&location.stored_len.to_le_bytes());
+    record
+}
+
+/// Decode one index record from the front of `buf`, returning it along with
+/// how many bytes it consumed - or `None` if `buf` doesn't hold a complete
+/// record, which is expected at the tail of a log left by a torn write
+fn decode_index_record(buf: &[u8]) -> Option<(String, BundleLocation, usize)> {
+    let hash_len = *buf.first()? as usize;
+    let record_len = 1 + hash_len + INDEX_RECORD_TRAILER_SIZE;
+    if buf.len() < record_len {
+        return None;
+    }
+
+    let hash = String::from_utf8(buf[1..1 + hash_len].to_vec()).ok()?;
+
+    let mut pos = 1 + hash_len;
+    let bundle_id = u32::from_le_bytes(buf[pos..pos + 4].try_into().ok()?);
+    pos += 4;
+    let offset = u64::from_le_bytes(buf[pos..pos + 8].try_into().ok()?);
+    pos += 8;
+    let stored_len = u32::from_le_bytes(buf[pos..pos + 4].try_into().ok()?);
+
+    Some((
+        hash,
+        BundleLocation {
+            bundle_id,
+            offset,
+            stored_len,
+        },
+        record_len,
+    ))
+}
+
+/// Adler-style rolling hash over a fixed-width window, used to index and scan
+/// for matching regions between the delta base and the new chunk data
+fn rolling_hash(window: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    for &byte in window {
+        a = a.wrapping_add(byte as u32);
+        b = b.wrapping_add(a);
+    }
+
+    (b << 16) | (a & 0xffff)
+}
+
+/// Index every fixed-width window of `data` by its rolling hash, so candidate
+/// match offsets can be looked up while scanning the new data
+fn build_rolling_hash_index(data: &[u8], window: usize) -> HashMap<u32, Vec<usize>> {
+    let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+
+    if data.len() < window {
+        return index;
+    }
+
+    for offset in 0..=(data.len() - window) {
+        let hash = rolling_hash(&data[offset..offset + window]);
+        index.entry(hash).or_default().push(offset);
+    }
+
+    index
+}
+
+/// Build a min-hash style sketch for `data`: slide `SKETCH_WINDOW` over the
+/// data, and keep the `SKETCH_SIZE` smallest distinct window hashes widened
+/// to u64. Two chunks sharing many sketch elements are likely to have high
+/// Jaccard overlap, which makes the sketch a cheap stand-in for full
+/// similarity comparison when picking a delta base.
+fn compute_sketch(data: &[u8]) -> Vec<u64> {
+    if data.len() < SKETCH_WINDOW {
+        return Vec::new();
+    }
+
+    let mut distinct: HashSet<u64> = HashSet::new();
+    for offset in 0..=(data.len() - SKETCH_WINDOW) {
+        let hash = rolling_hash(&data[offset..offset + SKETCH_WINDOW]) as u64;
+        distinct.insert(hash);
+    }
+
+    let mut hashes: Vec<u64> = distinct.into_iter().collect();
+    hashes.sort_unstable();
+    hashes.truncate(SKETCH_SIZE);
+    hashes
+}
+
+/// Pack a chunk's min-hash sketch into a single 64-bit signature (one byte
+/// per sketch element) that can be indexed in a `BkTree` and compared by
+/// Hamming distance. `None` if the chunk is too small to sketch at all.
+fn compute_minhash_signature(data: &[u8]) -> Option<u64> {
+    let sketch = compute_sketch(data);
+    if sketch.is_empty() {
+        return None;
+    }
+
+    let mut signature: u64 = 0;
+    for (i, &element) in sketch.iter().enumerate().take(SKETCH_SIZE) {
+        signature |= (element as u8 as u64) << (i * 8);
+    }
+
+    Some(signature)
+}
+
+/// Hamming distance between two packed signatures: the number of bits that differ
+fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Node in a `BkTree`, holding one chunk's signature and hash plus children
+/// keyed by their Hamming distance from this node
+struct BkNode {
+    signature: u64,
+    hash: String,
+    children: HashMap<u32, Box<BkNode>>,
+}
+
+/// BK-tree (Burkhard-Keller tree) over packed min-hash signatures, letting
+/// `find_similar_chunk` query "chunks within Hamming radius N" in roughly
+/// O(log n) rather than comparing a candidate against every stored chunk.
+#[derive(Default)]
+struct BkTree {
+    root: Option<Box<BkNode>>,
+}
+
+impl BkTree {
+    /// Insert a chunk's signature into the tree
+    fn insert(&mut self, signature: u64, hash: String) {
+        let Some(root) = &mut self.root else {
+            self.root = Some(Box::new(BkNode {
+                signature,
+                hash,
+                children: HashMap::new(),
+            }));
+            return;
+        };
+
+        let mut current = root.as_mut();
+        loop {
+            let distance = hamming_distance(current.signature, signature);
+            if distance == 0 {
+                // Exact signature collision - keep the existing node and let
+                // `find_similar_chunk` discover this hash via `delta_cache`
+                // or a future, slightly different chunk instead.
+                return;
+            }
+
+            match current.children.entry(distance) {
+                std::collections::hash_map::Entry::Occupied(entry) => {
+                    current = entry.into_mut();
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    entry.insert(Box::new(BkNode {
+                        signature,
+                        hash,
+                        children: HashMap::new(),
+                    }));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Every indexed (distance, hash) pair whose signature is within `radius`
+    /// Hamming distance of `signature`
+    fn query(&self, signature: u64, radius: u32) -> Vec<(u32, &str)> {
+        let mut results = Vec::new();
+        if let Some(root) = &self.root {
+            Self::query_node(root, signature, radius, &mut results);
+        }
+        results
+    }
+
+    fn query_node<'a>(
+        node: &'a BkNode,
+        signature: u64,
+        radius: u32,
+        results: &mut Vec<(u32, &'a str)>,
+    ) {
+        let distance = hamming_distance(node.signature, signature);
+        if distance <= radius {
+            results.push((distance, node.hash.as_str()));
+        }
+
+        let lower = distance.saturating_sub(radius);
+        let upper = distance + radius;
+        for (edge, child) in &node.children {
+            if *edge >= lower && *edge <= upper {
+                Self::query_node(child, signature, radius, results);
             }
         }
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn read_varint(data: &[u8], mut pos: usize) -> Result<(u64, usize)> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+
+    loop {
+        if pos >= data.len() {
+            return Err(BlazeError::Chunk(
+                "Truncated varint in delta stream".to_string(),
+            ));
+        }
+
+        let byte = data[pos];
+        pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+
+    Ok((result, pos))
+}
+
+fn encode_copy_op(delta: &mut Vec<u8>, base_off: u64, len: u64) {
+    delta.push(0); // 0 = copy
+    write_varint(delta, base_off);
+    write_varint(delta, len);
+}
+
+fn encode_insert_op(delta: &mut Vec<u8>, literal: &[u8]) {
+    if literal.is_empty() {
+        return;
+    }
+    delta.push(1); // 1 = insert
+    write_varint(delta, literal.len() as u64);
+    delta.extend_from_slice(literal);
+}
+
+fn load_index(
+    backend: &dyn StorageBackend,
+    index_log_path: &Path,
+) -> Result<HashMap<String, BundleLocation>> {
+    let mut index = HashMap::new();
+
+    if !backend.exists(index_log_path) {
+        return Ok(index);
+    }
+
+    let buf = backend
+        .read(index_log_path)
+        .with_context(|| format!("Failed to read chunk index log: {}", index_log_path.display()))?;
+
+    let mut pos = 0;
+    while pos < buf.len() {
+        let Some((hash, location, consumed)) = decode_index_record(&buf[pos..]) else {
+            // A torn trailing record from a crash mid-append; anything
+            // before it is still valid and already in `index`.
+            break;
+        };
+        index.insert(hash, location);
+        pos += consumed;
+    }
+
+    Ok(index)
+}
+
+fn open_active_bundle(
+    backend: &dyn StorageBackend,
+    bundles_dir: &Path,
+    index: &HashMap<String, BundleLocation>,
+) -> Result<ActiveBundle> {
+    let mut max_id = index.values().map(|loc| loc.bundle_id).max();
+
+    // Also consider bundle files that exist on disk but aren't (yet) referenced
+    // by any live index entry, so we never pick an id that collides.
+    if backend.exists(bundles_dir) {
+        for path in backend.list(bundles_dir)? {
+            if let Some(id) = parse_bundle_id(&path.file_name().unwrap_or_default().to_string_lossy()) {
+                max_id = Some(max_id.map_or(id, |m| m.max(id)));
+            }
+        }
+    }
+
+    let id = max_id.unwrap_or(0);
+    let path = bundles_dir.join(format!("bundle_{:010}.pack", id));
+    let file = backend
+        .open(&path, &StorageOpenOptions::new().create(true).append(true))
+        .with_context(|| format!("Failed to open bundle: {}", path.display()))?;
+
+    let size = backend.metadata_len(&path)?;
+
+    Ok(ActiveBundle { id, file, size })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::FileChunk;
+    use crate::storage::MemBackend;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chunk_store_creation() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let _store = ChunkStore::new(&chunks_dir).unwrap();
+        assert!(chunks_dir.join(BUNDLES_DIR).exists());
+    }
+
+    #[test]
+    fn test_chunk_store_with_mem_backend_roundtrips_without_touching_disk() {
+        let chunks_dir = PathBuf::from("/mem/chunks");
+        let mut store = ChunkStore::new_with_backend(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            None,
+            EncryptionConfig::disabled(),
+            Arc::new(MemBackend::new()),
+        )
+        .unwrap();
+
+        let chunk = FileChunk {
+            hash: "abc123".to_string(),
+            size: 17,
+            data: b"hello from memory".to_vec(),
+        };
+
+        store.store_chunk(&chunk).unwrap();
+        assert!(store.chunk_exists(&chunk.hash));
+        assert_eq!(store.load_chunk(&chunk.hash).unwrap(), chunk.data);
+        assert!(!chunks_dir.exists());
+    }
+
+    #[test]
+    fn test_store_and_load_chunk() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let data = b"Hello, world!".to_vec();
+        let chunk = FileChunk::new(data.clone());
+
+        let hash = store.store_chunk(&chunk).unwrap();
+        assert_eq!(hash, chunk.hash);
+
+        let loaded_data = store.load_chunk(&hash).unwrap();
+        assert_eq!(loaded_data, data);
+    }
+
+    #[test]
+    fn test_chunk_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let data = b"Test data".to_vec();
+        let chunk = FileChunk::new(data);
+
+        assert!(!store.chunk_exists(&chunk.hash));
+
+        store.store_chunk(&chunk).unwrap();
+        assert!(store.chunk_exists(&chunk.hash));
+    }
+
+    #[test]
+    fn test_store_multiple_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let chunks = vec![
+            FileChunk::new(b"Chunk 1".to_vec()),
+            FileChunk::new(b"Chunk 2".to_vec()),
+            FileChunk::new(b"Chunk 3".to_vec()),
+        ];
+
+        let hashes = store.store_chunks(&chunks).unwrap();
+        assert_eq!(hashes.len(), 3);
+
+        for (chunk, hash) in chunks.iter().zip(hashes.iter()) {
+            assert_eq!(&chunk.hash, hash);
+            assert!(store.chunk_exists(hash));
+        }
+    }
+
+    #[test]
+    fn test_chunk_integrity() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let data = b"Integrity test data".to_vec();
+        let chunk = FileChunk::new(data.clone());
+
+        store.store_chunk(&chunk).unwrap();
+
+        // Corrupt the chunk's bytes directly inside its bundle file
+        let location = *store.index.read().unwrap().get(&chunk.hash).unwrap();
+        let bundle_path = store.bundle_path(location.bundle_id);
+        let mut bundle_data = std::fs::read(&bundle_path).unwrap();
+        let start = location.offset as usize;
+        for byte in bundle_data.iter_mut().skip(start).take(location.stored_len as usize) {
+            *byte ^= 0xff;
+        }
+        std::fs::write(&bundle_path, bundle_data).unwrap();
+
+        store.clear_cache();
+
+        let result = store.load_chunk(&chunk.hash);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cache_functionality() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let data = b"Cache test data".to_vec();
+        let chunk = FileChunk::new(data.clone());
+
+        store.store_chunk(&chunk).unwrap();
+
+        let (cached_count, _, _) = store.cache_stats();
+        assert!(cached_count > 0);
+
+        let loaded_data = store.load_chunk(&chunk.hash).unwrap();
+        assert_eq!(loaded_data, data);
+    }
+
+    #[test]
+    fn test_garbage_collection() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let chunks = vec![
+            FileChunk::new(b"Keep this chunk".to_vec()),
+            FileChunk::new(b"Remove this chunk".to_vec()),
+        ];
+
+        for chunk in &chunks {
+            store.store_chunk(chunk).unwrap();
+        }
+
+        let active_hashes = vec![chunks[0].hash.clone()];
+        let removed_count = store.garbage_collect(&active_hashes).unwrap();
+
+        assert_eq!(removed_count, 1);
+        assert!(store.chunk_exists(&chunks[0].hash));
+        assert!(!store.chunk_exists(&chunks[1].hash));
+    }
+
+    #[test]
+    fn test_gc_preview_reports_without_deleting() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let chunks = vec![
+            FileChunk::new(b"Keep this chunk".to_vec()),
+            FileChunk::new(b"Remove this chunk".to_vec()),
+        ];
+        for chunk in &chunks {
+            store.store_chunk(chunk).unwrap();
+        }
 
-        None
+        let active_hashes = vec![chunks[0].hash.clone()];
+        let report = store.gc_preview(&active_hashes, None);
+
+        assert_eq!(report.chunks_removed, 1);
+        assert!(report.bytes_reclaimed > 0);
+        assert_eq!(report.chunks_retained_by_grace, 0);
+        // Nothing was actually deleted.
+        assert!(store.chunk_exists(&chunks[0].hash));
+        assert!(store.chunk_exists(&chunks[1].hash));
     }
 
-    /// Store chunk with delta compression if beneficial
-    pub fn store_chunk_with_delta(&mut self, chunk: &FileChunk) -> Result<String> {
-        // Check if chunk already exists
-        if self.chunk_exists(&chunk.hash) {
-            return Ok(chunk.hash.clone());
-        }
+    #[test]
+    fn test_garbage_collect_with_grace_spares_recently_written_bundles() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        // Try to find similar chunk for delta compression
-        if let Some(base_hash) = self.find_similar_chunk(&chunk.hash, &chunk.data) {
-            if let Ok(base_data) = self.load_chunk_uncached(&base_hash) {
-                let delta = self.create_delta(&base_data, &chunk.data);
+        let chunks = vec![
+            FileChunk::new(b"Keep this chunk".to_vec()),
+            FileChunk::new(b"Remove this chunk".to_vec()),
+        ];
+        for chunk in &chunks {
+            store.store_chunk(chunk).unwrap();
+        }
 
-                // Only use delta if it's significantly smaller
-                if delta.len() < (chunk.data.len() * 7 / 10) {
-                    // Delta is 30%+ smaller, use it
-                    let compressed_delta = self.compress_chunk_data(&delta)?;
+        let active_hashes = vec![chunks[0].hash.clone()];
 
-                    let chunk_path = self.get_chunk_path(&chunk.hash);
-                    if let Some(parent) = chunk_path.parent() {
-                        fs::create_dir_all(parent)?;
-                    }
+        // The bundle holding `chunks[1]` was just written, so a generous
+        // grace window should spare it rather than sweep it.
+        let report = store
+            .garbage_collect_with_grace(&active_hashes, Some(Duration::from_secs(86_400)))
+            .unwrap();
+
+        assert_eq!(report.chunks_removed, 0);
+        assert_eq!(report.chunks_retained_by_grace, 1);
+        assert!(store.chunk_exists(&chunks[1].hash));
+
+        // Without a grace window, the same dead chunk is swept normally.
+        let report = store
+            .garbage_collect_with_grace(&active_hashes, None)
+            .unwrap();
+        assert_eq!(report.chunks_removed, 1);
+        assert!(!store.chunk_exists(&chunks[1].hash));
+    }
 
-                    // Store with delta marker
-                    let mut delta_file_data = vec![3]; // 3 = delta compressed
-                    delta_file_data.extend_from_slice(base_hash.as_bytes());
-                    delta_file_data.push(0); // null separator
-                    delta_file_data.extend_from_slice(&compressed_delta);
-
-                    let temp_path = chunk_path.with_extension("tmp");
-                    {
-                        let mut file = OpenOptions::new()
-                            .write(true)
-                            .create(true)
-                            .truncate(true)
-                            .open(&temp_path)?;
-                        file.write_all(&delta_file_data)?;
-                        file.sync_all()?;
-                    }
+    #[test]
+    fn test_stats_reports_referenced_orphaned_and_largest_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-                    fs::rename(&temp_path, &chunk_path)?;
-
-                    // Update caches
-                    self.maybe_cache_chunk(&chunk.hash, chunk.data.clone());
-                    self.existence_cache
-                        .write()
-                        .unwrap()
-                        .insert(chunk.hash.clone());
-                    self.negative_cache.write().unwrap().remove(&chunk.hash);
-
-                    // Update delta cache
-                    self.delta_cache
-                        .write()
-                        .unwrap()
-                        .entry(base_hash)
-                        .or_default()
-                        .push(chunk.hash.clone());
-
-                    return Ok(chunk.hash.clone());
-                }
-            }
+        let chunks = vec![
+            FileChunk::new(b"short".to_vec()),
+            FileChunk::new(b"a much longer orphaned chunk of data".to_vec()),
+        ];
+        for chunk in &chunks {
+            store.store_chunk(chunk).unwrap();
         }
 
-        // Fall back to regular compression
-        self.store_chunk(chunk)
+        let active_hashes = vec![chunks[0].hash.clone()];
+        let stats = store.stats(&active_hashes);
+
+        assert_eq!(stats.chunk_count, 2);
+        assert_eq!(stats.referenced_chunks, 1);
+        assert_eq!(stats.orphaned_chunks, 1);
+        assert!(stats.total_bytes > 0);
+        assert_eq!(stats.largest_chunks.len(), 2);
+        assert!(stats.largest_chunks[0].stored_bytes >= stats.largest_chunks[1].stored_bytes);
     }
 
-    /// Calculate similarity between two byte arrays (0.0 to 1.0)
-    fn calculate_similarity(data1: &[u8], data2: &[u8]) -> f32 {
-        if data1.is_empty() && data2.is_empty() {
-            return 1.0;
-        }
-        if data1.is_empty() || data2.is_empty() {
-            return 0.0;
-        }
+    #[test]
+    fn test_repack_consolidates_closed_bundles_and_drops_dead_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
 
-        let max_len = std::cmp::max(data1.len(), data2.len());
-        let min_len = std::cmp::min(data1.len(), data2.len());
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        let mut matching_bytes = 0;
-        for i in 0..min_len {
-            if data1[i] == data2[i] {
-                matching_bytes += 1;
-            }
-        }
+        let keep = FileChunk::new(b"survives the repack".to_vec());
+        store.store_chunk(&keep).unwrap();
+
+        // Force the next write to roll over into a second bundle, leaving
+        // the first one closed
+        store.active_bundle.lock().unwrap().size = BUNDLE_TARGET_SIZE + 1;
+
+        let dead = FileChunk::new(b"garbage-collected before repack".to_vec());
+        store.store_chunk(&dead).unwrap();
+        store.garbage_collect(&[keep.hash.clone()]).unwrap();
+
+        // `keep`'s bundle is now closed and holds only a single live chunk -
+        // a dry run should report it without touching anything
+        let dry_run_stats = store.repack(true).unwrap();
+        assert_eq!(dry_run_stats.bundles_repacked, 1);
+        assert_eq!(dry_run_stats.chunks_repacked, 1);
+        assert!(store.chunk_exists(&keep.hash));
+
+        let stats = store.repack(false).unwrap();
+        assert_eq!(stats.bundles_repacked, 1);
+        assert_eq!(stats.chunks_repacked, 1);
+        assert_eq!(stats.bytes_repacked, dry_run_stats.bytes_repacked);
+
+        // Still readable after being copied into the active bundle, and the
+        // original bundle file is gone
+        assert_eq!(store.load_chunk(&keep.hash).unwrap(), keep.data);
+        assert!(!store.bundle_path(0).exists());
+
+        // Nothing left to repack
+        let empty_stats = store.repack(false).unwrap();
+        assert_eq!(empty_stats.bundles_repacked, 0);
+    }
 
-        // Penalize size differences
-        let size_penalty = (max_len - min_len) as f32 / max_len as f32;
-        let base_similarity = matching_bytes as f32 / min_len as f32;
+    #[test]
+    fn test_store_with_non_default_hash_algo() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
 
-        base_similarity * (1.0 - size_penalty * 0.5)
-    }
+        let mut store = ChunkStore::new_with_algo(&chunks_dir, crate::files::HashAlgo::Xxh3).unwrap();
 
-    fn maybe_cache_chunk(&mut self, hash: &str, data: Vec<u8>) {
-        let data_size = data.len();
+        let data = b"xxh3 hashed chunk".to_vec();
+        let hash = crate::files::compute_chunk_hash_with(crate::files::HashAlgo::Xxh3, &data);
+        let chunk = FileChunk {
+            hash: hash.clone(),
+            size: data.len(),
+            data: data.clone(),
+        };
 
-        // Don't cache if data is too large for cache
-        if data_size > self.max_cache_size / 4 {
-            return;
-        }
+        store.store_chunk(&chunk).unwrap();
+        assert_eq!(store.load_chunk(&hash).unwrap(), data);
 
-        // Evict old entries if cache is getting full
-        while self.current_cache_size + data_size > self.max_cache_size
-            && !self.chunk_cache.is_empty()
-        {
-            if let Some((old_hash, old_data)) = self.chunk_cache.iter().next() {
-                let old_hash = old_hash.clone();
-                let old_size = old_data.len();
-                self.chunk_cache.remove(&old_hash);
-                self.current_cache_size -= old_size;
-            } else {
-                break;
+        let report = store.verify();
+        assert_eq!(report.ok, 1);
+        assert!(report.bad.is_empty());
+    }
+
+    #[test]
+    fn test_chunk_survives_store_reopen_for_every_hash_algo() {
+        use crate::files::HashAlgo;
+
+        for algo in [
+            HashAlgo::Blake3,
+            HashAlgo::Blake2b,
+            HashAlgo::Sha256,
+            HashAlgo::Xxh3,
+            HashAlgo::Crc32,
+        ] {
+            let temp_dir = TempDir::new().unwrap();
+            let chunks_dir = temp_dir.path().join("chunks");
+
+            let data = b"data that outlives the process".to_vec();
+            let hash = crate::files::compute_chunk_hash_with(algo, &data);
+
+            {
+                let mut store = ChunkStore::new_with_algo(&chunks_dir, algo).unwrap();
+                let chunk = FileChunk {
+                    hash: hash.clone(),
+                    size: data.len(),
+                    data: data.clone(),
+                };
+                store.store_chunk(&chunk).unwrap();
             }
-        }
 
-        // Add to cache
-        self.chunk_cache.insert(hash.to_string(), data);
-        self.current_cache_size += data_size;
+            // Reopening rebuilds the in-memory index purely from `index.log`,
+            // the same way every new CLI process does.
+            let store = ChunkStore::new_with_algo(&chunks_dir, algo).unwrap();
+            assert!(
+                store.chunk_exists(&hash),
+                "{algo:?}: chunk not found after reopen"
+            );
+            assert_eq!(store.load_chunk(&hash).unwrap(), data, "{algo:?}: data mismatch after reopen");
+        }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::files::FileChunk;
-    use tempfile::TempDir;
 
     #[test]
-    fn test_chunk_store_creation() {
+    fn test_reopen_with_mismatched_hash_algo_fails() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
 
         let _store = ChunkStore::new(&chunks_dir).unwrap();
-        assert!(chunks_dir.exists());
+
+        let reopened =
+            ChunkStore::new_with_algo(&chunks_dir, crate::files::HashAlgo::Blake3);
+        assert!(reopened.is_err());
     }
 
     #[test]
-    fn test_store_and_load_chunk() {
+    fn test_store_with_compression_disabled_roundtrips() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
 
-        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+        let compression = crate::config::CompressionConfig {
+            algo: crate::config::CompressionAlgo::None,
+            ..crate::config::CompressionConfig::default()
+        };
+        let mut store = ChunkStore::new_with_algo_and_compression(
+            &chunks_dir,
+            crate::files::HashAlgo::default(),
+            compression,
+        )
+        .unwrap();
+        assert_eq!(store.compression_policy().algo, crate::config::CompressionAlgo::None);
 
-        let data = b"Hello, world!".to_vec();
+        let data = vec![b'a'; 4096];
         let chunk = FileChunk::new(data.clone());
+        store.store_chunk(&chunk).unwrap();
 
-        let hash = store.store_chunk(&chunk).unwrap();
-        assert_eq!(hash, chunk.hash);
+        assert_eq!(store.load_chunk(&chunk.hash).unwrap(), data);
+    }
 
-        let loaded_data = store.load_chunk(&hash).unwrap();
-        assert_eq!(loaded_data, data);
+    #[test]
+    fn test_store_and_load_chunk_roundtrips_every_compression_algo() {
+        for algo in [
+            crate::config::CompressionAlgo::None,
+            crate::config::CompressionAlgo::Lz4,
+            crate::config::CompressionAlgo::Zstd,
+            crate::config::CompressionAlgo::Gzip,
+            crate::config::CompressionAlgo::Deflate,
+            crate::config::CompressionAlgo::Brotli,
+            crate::config::CompressionAlgo::Auto,
+        ] {
+            let temp_dir = TempDir::new().unwrap();
+            let chunks_dir = temp_dir.path().join("chunks");
+
+            let compression = crate::config::CompressionConfig {
+                algo,
+                ..crate::config::CompressionConfig::default()
+            };
+            let mut store = ChunkStore::new_with_algo_and_compression(
+                &chunks_dir,
+                crate::files::HashAlgo::default(),
+                compression,
+            )
+            .unwrap();
+
+            let data = vec![b'a'; 4096];
+            let chunk = FileChunk::new(data.clone());
+            let hash = store.store_chunk(&chunk).unwrap();
+            assert_eq!(hash, chunk.hash);
+
+            assert_eq!(
+                store.load_chunk(&hash).unwrap(),
+                data,
+                "roundtrip failed for {algo:?}"
+            );
+        }
     }
 
     #[test]
-    fn test_chunk_exists() {
+    fn test_store_rejects_invalid_compression_config() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
 
-        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+        let compression = crate::config::CompressionConfig {
+            level: crate::config::CompressionLevel::Fixed(99),
+            ..crate::config::CompressionConfig::default()
+        };
+        let result = ChunkStore::new_with_algo_and_compression(
+            &chunks_dir,
+            crate::files::HashAlgo::default(),
+            compression,
+        );
+        assert!(result.is_err());
+    }
 
-        let data = b"Test data".to_vec();
-        let chunk = FileChunk::new(data);
+    #[test]
+    fn test_store_with_custom_chunking_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
 
-        assert!(!store.chunk_exists(&chunk.hash));
+        let chunking = crate::config::ChunkingConfig {
+            strategy: crate::config::ChunkingStrategy::FastCdc,
+            avg_size: 1024,
+            min_size: 256,
+            max_size: 4096,
+        };
+        let store = ChunkStore::new_with_config(
+            &chunks_dir,
+            crate::files::HashAlgo::default(),
+            crate::config::CompressionConfig::default(),
+            chunking,
+        )
+        .unwrap();
+        assert_eq!(store.chunking_policy().avg_size, 1024);
+    }
 
-        store.store_chunk(&chunk).unwrap();
-        assert!(store.chunk_exists(&chunk.hash));
+    #[test]
+    fn test_store_rejects_invalid_chunking_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+
+        let chunking = crate::config::ChunkingConfig {
+            strategy: crate::config::ChunkingStrategy::FastCdc,
+            avg_size: 50,
+            min_size: 100,
+            max_size: 200,
+        };
+        let result = ChunkStore::new_with_config(
+            &chunks_dir,
+            crate::files::HashAlgo::default(),
+            crate::config::CompressionConfig::default(),
+            chunking,
+        );
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_store_multiple_chunks() {
+    fn test_incremental_reuses_wholesale_on_unchanged_metadata() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let data = vec![b'x'; 20_000];
+        let prev_hashes = store.store_bytes_chunked(&data).unwrap();
+
+        let fingerprint = crate::files::FileFingerprint {
+            size: data.len() as u64,
+            mtime: 1_000,
+            inode: Some(42),
+        };
+
+        // Pass deliberately wrong bytes - since the fingerprint is unchanged
+        // they should never be consulted.
+        let wrong_data = vec![b'y'; 5];
+        let hashes = store
+            .store_file_incremental(&wrong_data, &prev_hashes, fingerprint, fingerprint)
+            .unwrap();
 
+        assert_eq!(hashes, prev_hashes);
+    }
+
+    #[test]
+    fn test_incremental_reuses_unchanged_head_and_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
         let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        let chunks = vec![
-            FileChunk::new(b"Chunk 1".to_vec()),
-            FileChunk::new(b"Chunk 2".to_vec()),
-            FileChunk::new(b"Chunk 3".to_vec()),
-        ];
+        let mut original = vec![0u8; 60_000];
+        for (i, byte) in original.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+        let prev_hashes = store.store_bytes_chunked(&original).unwrap();
 
-        let hashes = store.store_chunks(&chunks).unwrap();
-        assert_eq!(hashes.len(), 3);
+        // Insert a few bytes in the middle; head and tail stay byte-identical.
+        let mut edited = original[..30_000].to_vec();
+        edited.extend_from_slice(b"inserted");
+        edited.extend_from_slice(&original[30_000..]);
 
-        for (chunk, hash) in chunks.iter().zip(hashes.iter()) {
-            assert_eq!(&chunk.hash, hash);
-            assert!(store.chunk_exists(hash));
+        let old_fp = crate::files::FileFingerprint {
+            size: original.len() as u64,
+            mtime: 1,
+            inode: None,
+        };
+        let new_fp = crate::files::FileFingerprint {
+            size: edited.len() as u64,
+            mtime: 2,
+            inode: None,
+        };
+
+        let hashes = store
+            .store_file_incremental(&edited, &prev_hashes, old_fp, new_fp)
+            .unwrap();
+
+        // At least the untouched head chunks should have been carried over
+        // verbatim rather than re-hashed under a new identity.
+        let reused = hashes.iter().filter(|h| prev_hashes.contains(h)).count();
+        assert!(reused > 0);
+
+        let reconstructed: Vec<u8> = store
+            .load_chunks(&hashes)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(reconstructed, edited);
+    }
+
+    #[test]
+    fn test_delta_finds_similar_chunk_via_sketch_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let mut base = vec![0u8; 4096];
+        for (i, byte) in base.iter_mut().enumerate() {
+            *byte = (i % 200) as u8;
         }
+        store.store_chunk_with_delta(&FileChunk::new(base.clone())).unwrap();
+
+        // Near-duplicate with a handful of inserted bytes shifting everything after it.
+        let mut similar = base[..2048].to_vec();
+        similar.extend_from_slice(b"edit-marker");
+        similar.extend_from_slice(&base[2048..]);
+        let chunk = FileChunk::new(similar.clone());
+
+        let hash = store.store_chunk_with_delta(&chunk).unwrap();
+        assert_eq!(store.load_chunk(&hash).unwrap(), similar);
     }
 
     #[test]
-    fn test_chunk_integrity() {
+    fn test_create_delta_roundtrips_through_apply_delta() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
+        let store = ChunkStore::new(&chunks_dir).unwrap();
+
+        let mut base = vec![0u8; 2048];
+        for (i, byte) in base.iter_mut().enumerate() {
+            *byte = (i % 200) as u8;
+        }
+
+        // Insert a short run in the middle - everything after it is shifted,
+        // so a position-locked scheme would degrade to near-total literals
+        // while arbitrary-offset COPY commands should still find the tail.
+        let mut target = base[..1024].to_vec();
+        target.extend_from_slice(b"newly-inserted-bytes");
+        target.extend_from_slice(&base[1024..]);
+
+        let delta = store.create_delta(&base, &target);
+        assert!(delta.len() < target.len() / 2);
+
+        let reconstructed = store.apply_delta(&base, &delta).unwrap();
+        assert_eq!(reconstructed, target);
+    }
+
+    /// Store `new_data` as a delta chained directly to `base_hash`, bypassing
+    /// `find_similar_chunk`'s nearest-neighbor search so tests can build a
+    /// specific chain deterministically.
+    fn store_delta_chunk(
+        store: &mut ChunkStore,
+        base_hash: &str,
+        base_data: &[u8],
+        new_data: &[u8],
+    ) -> String {
+        let hash = crate::files::compute_chunk_hash_with(store.hash_algo, new_data);
+        let delta = store.create_delta(base_data, new_data);
+        let compressed_delta = store.compress_chunk_data(&delta).unwrap();
+
+        let mut delta_file_data = vec![DELTA_MARKER];
+        delta_file_data.extend_from_slice(base_hash.as_bytes());
+        delta_file_data.push(0);
+        delta_file_data.extend_from_slice(&compressed_delta);
+
+        let location = store.append_to_active_bundle(&delta_file_data, IoPriority::Foreground).unwrap();
+        store.append_index_records(&[(hash.clone(), location)]).unwrap();
+        store.index.write().unwrap().insert(hash.clone(), location);
+
+        hash
+    }
 
+    #[test]
+    fn test_delta_chain_stats_and_materialize() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
         let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        let data = b"Integrity test data".to_vec();
-        let chunk = FileChunk::new(data.clone());
+        let a = vec![7u8; 4096];
+        let a_hash = store.store_chunk(&FileChunk::new(a.clone())).unwrap();
 
-        store.store_chunk(&chunk).unwrap();
+        let mut b = a.clone();
+        b[100] = 9;
+        let b_hash = store_delta_chunk(&mut store, &a_hash, &a, &b);
 
-        // Corrupt the stored chunk file
-        let chunk_path = store.get_chunk_path(&chunk.hash);
-        std::fs::write(&chunk_path, b"corrupted data").unwrap();
+        let mut c = b.clone();
+        c[200] = 11;
+        let c_hash = store_delta_chunk(&mut store, &b_hash, &b, &c);
 
-        // Clear cache to force reload from disk
-        store.clear_cache();
+        let stats = store.delta_chain_stats().unwrap();
+        assert_eq!(stats.delta_count, 2);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.referenced_bases, 2);
 
-        // Loading should fail due to integrity check
-        let result = store.load_chunk(&chunk.hash);
-        assert!(result.is_err());
+        store.materialize(&c_hash).unwrap();
+        assert!(store.delta_base_of(&c_hash).unwrap().is_none());
+        assert_eq!(store.load_chunk(&c_hash).unwrap(), c);
     }
 
     #[test]
-    fn test_cache_functionality() {
+    fn test_garbage_collect_retains_delta_base_transitively() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
-
         let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        let data = b"Cache test data".to_vec();
-        let chunk = FileChunk::new(data.clone());
+        let a = vec![3u8; 2048];
+        let a_hash = store.store_chunk(&FileChunk::new(a.clone())).unwrap();
 
-        store.store_chunk(&chunk).unwrap();
+        let mut b = a.clone();
+        b[50] = 1;
+        let b_hash = store_delta_chunk(&mut store, &a_hash, &a, &b);
 
-        let (cached_count, _, _) = store.cache_stats();
-        assert!(cached_count > 0);
+        // Only `b` is reachable from the working tree, but it's stored as a
+        // delta chained to `a`, so `a` must survive collection too.
+        store.garbage_collect(&[b_hash.clone()]).unwrap();
 
-        // Load again - should come from cache
-        let loaded_data = store.load_chunk(&chunk.hash).unwrap();
-        assert_eq!(loaded_data, data);
+        assert!(store.chunk_exists(&a_hash));
+        assert_eq!(store.load_chunk(&b_hash).unwrap(), b);
     }
 
     #[test]
-    fn test_garbage_collection() {
+    fn test_load_chunks_prefetched_preserves_order_across_batches() {
         let temp_dir = TempDir::new().unwrap();
         let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new_with_prefetch(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            2, // force multiple batches for a handful of chunks
+        )
+        .unwrap();
 
-        let mut store = ChunkStore::new(&chunks_dir).unwrap();
+        let chunks: Vec<FileChunk> = (0..5u8)
+            .map(|i| FileChunk::new(vec![i; 16]))
+            .collect();
+        let hashes: Vec<String> = chunks.iter().map(|c| c.hash.clone()).collect();
+        store.store_chunks(&chunks).unwrap();
 
-        let chunks = vec![
-            FileChunk::new(b"Keep this chunk".to_vec()),
-            FileChunk::new(b"Remove this chunk".to_vec()),
-        ];
+        let loaded = store.load_chunks_prefetched(&hashes).unwrap();
+        let expected: Vec<Vec<u8>> = chunks.into_iter().map(|c| c.data).collect();
+        assert_eq!(loaded, expected);
+    }
+
+    #[test]
+    fn test_read_amplification_batch_is_clamped_to_memory_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let store = ChunkStore::new_with_prefetch(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert!(store.read_amplification_batch() <= MAX_MEMORY_BUFFER / CHUNK_SIZE);
+        assert!(store.read_amplification_batch() > 0);
+    }
+
+    #[test]
+    fn test_unthrottled_store_reports_no_rate_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let store = ChunkStore::new(&chunks_dir).unwrap();
+
+        assert_eq!(store.rate_limit(), None);
+    }
+
+    #[test]
+    fn test_rate_limited_store_throttles_bulk_chunk_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        // A tiny budget forces store_chunks to block on the limiter.
+        let mut store = ChunkStore::new_with_rate_limit(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            Some(1024),
+        )
+        .unwrap();
+        assert_eq!(store.rate_limit(), Some(1024));
+
+        let chunks: Vec<FileChunk> = (0..4u8).map(|i| FileChunk::new(vec![i; 4096])).collect();
+
+        // Throttling must not corrupt data - it should only slow writes down.
+        store.store_chunks(&chunks).unwrap();
 
         for chunk in &chunks {
-            store.store_chunk(chunk).unwrap();
+            assert_eq!(store.load_chunk(&chunk.hash).unwrap(), chunk.data);
         }
+    }
 
-        // Only keep the first chunk
-        let active_hashes = vec![chunks[0].hash.clone()];
-        let removed_count = store.garbage_collect(&active_hashes).unwrap();
+    #[test]
+    fn test_compression_stats_tracks_codec_used_per_chunk() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let mut store = ChunkStore::new(&chunks_dir).unwrap();
 
-        assert_eq!(removed_count, 1);
-        assert!(store.chunk_exists(&chunks[0].hash));
-        assert!(!store.chunk_exists(&chunks[1].hash));
+        // Highly compressible, well past `min_size` - the default `Auto`
+        // policy should pick zstd for this.
+        let data = vec![7u8; 8192];
+        store.store_chunk(&FileChunk::new(data.clone())).unwrap();
+
+        let stats = store.compression_stats();
+        let zstd_stats = stats.get(&CompressionAlgo::Zstd).expect("zstd codec should have been used");
+
+        assert_eq!(zstd_stats.chunk_count, 1);
+        assert_eq!(zstd_stats.original_bytes, data.len() as u64);
+        assert!(zstd_stats.compressed_bytes < zstd_stats.original_bytes);
+        assert!(zstd_stats.ratio() < 1.0);
+    }
+
+    #[test]
+    fn test_encrypted_store_round_trips_chunk_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let encryption = EncryptionConfig::from_passphrase("hunter2");
+
+        let mut store = ChunkStore::new_with_encryption(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            None,
+            encryption,
+        )
+        .unwrap();
+
+        let chunk = FileChunk::new(b"top secret payload".to_vec());
+        store.store_chunk(&chunk).unwrap();
+        store.clear_cache();
+
+        assert_eq!(store.load_chunk(&chunk.hash).unwrap(), chunk.data);
+    }
+
+    #[test]
+    fn test_encrypted_bundle_bytes_never_contain_the_plaintext() {
+        let temp_dir = TempDir::new().unwrap();
+        let chunks_dir = temp_dir.path().join("chunks");
+        let encryption = EncryptionConfig::with_key([7u8; 32]);
+
+        let mut store = ChunkStore::new_with_encryption(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig {
+                algo: CompressionAlgo::None,
+                ..CompressionConfig::default()
+            },
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            None,
+            encryption,
+        )
+        .unwrap();
+
+        let plaintext = b"the quick brown fox jumps over the lazy dog, repeatedly";
+        store.store_chunk(&FileChunk::new(plaintext.to_vec())).unwrap();
+
+        let bundle_path = chunks_dir.join(BUNDLES_DIR).join("bundle_0000000000.pack");
+        let on_disk = std::fs::read(bundle_path).unwrap();
+        assert!(
+            !on_disk.windows(plaintext.len()).any(|window| window == plaintext),
+            "encrypted bundle must not contain the chunk's plaintext bytes"
+        );
+    }
+
+    #[test]
+    fn test_decrypting_without_the_key_fails_instead_of_returning_garbage() {
+        let chunks_dir = PathBuf::from("/mem/locked-chunks");
+        let backend = Arc::new(MemBackend::new());
+
+        let mut store = ChunkStore::new_with_backend(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            None,
+            EncryptionConfig::with_key([9u8; 32]),
+            backend.clone(),
+        )
+        .unwrap();
+
+        let chunk = FileChunk::new(b"needs the right key".to_vec());
+        store.store_chunk(&chunk).unwrap();
+
+        let mut locked_out = ChunkStore::new_with_backend(
+            &chunks_dir,
+            HashAlgo::default(),
+            CompressionConfig::default(),
+            ChunkingConfig::default(),
+            DEFAULT_READ_AMPLIFICATION_BATCH,
+            None,
+            EncryptionConfig::disabled(),
+            backend,
+        )
+        .unwrap();
+
+        assert!(locked_out.load_chunk(&chunk.hash).is_err());
     }
 }
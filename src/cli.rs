@@ -2,7 +2,11 @@
 
 use crate::core::Blaze;
 use crate::errors::{BlazeError, Result};
+use crate::messages::{msg, msg_n};
+use crate::settings::Settings;
+use crate::utils::format_size;
 use clap::{Parser, Subcommand};
+use std::path::Path;
 
 /// Blaze - A blazingly fast, chunk-based version control system
 #[derive(Parser)]
@@ -17,6 +21,76 @@ while maintaining data integrity and ease of use."
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Output format for command results (`log`, `status`, `stats`, `commit`, `verify`)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text, overrides_with = "json")]
+    pub format: OutputFormat,
+
+    /// Shorthand for `--format json`; whichever of `--format`/`--json` is given last wins
+    #[arg(long, global = true, overrides_with = "format")]
+    pub json: bool,
+
+    /// Suppress the status lines `run()` prints for successful commands
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// When to colorize output
+    ///
+    /// The choice is recorded on `OutputMode`; no command currently emits ANSI
+    /// color codes, so all three settings produce the same output.
+    #[arg(long, global = true, value_enum, default_value_t = ColorMode::Auto)]
+    pub color: ColorMode,
+}
+
+/// Rendering mode for command output
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text output (default)
+    #[default]
+    Text,
+    /// Stable, compact machine-readable JSON output
+    Json,
+}
+
+/// When to emit ANSI color codes
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colorize when stdout is a terminal (default)
+    #[default]
+    Auto,
+    /// Always colorize, even when piped
+    Always,
+    /// Never colorize
+    Never,
+}
+
+/// Output settings derived from the global `--format`/`--json`, `--quiet`
+/// and `--color` flags, computed once in `run()` and threaded through
+/// instead of each command arm re-deriving them from `Cli`
+pub struct OutputMode {
+    pub format: OutputFormat,
+    pub quiet: bool,
+    pub color: ColorMode,
+}
+
+impl OutputMode {
+    fn from_cli(cli: &Cli) -> OutputMode {
+        OutputMode {
+            // `--format` and `--json` mutually `overrides_with` each other,
+            // so whichever was actually given last is the one still set
+            format: if cli.json { OutputFormat::Json } else { cli.format },
+            quiet: cli.quiet,
+            color: cli.color,
+        }
+    }
+
+    /// Print a status line unless `--quiet` is set or the active format is
+    /// JSON (which renders its own structured report instead)
+    fn status(&self, line: &str) {
+        if !self.quiet && self.format == OutputFormat::Text {
+            println!("{}", line);
+        }
+    }
 }
 
 /// Available Blaze commands
@@ -29,11 +103,20 @@ pub enum Commands {
         #[arg(default_value = ".")]
         path: String,
         /// Don't create .blazeignore file
-        #[arg(long)]
+        #[arg(long, overrides_with = "ignore")]
         no_ignore: bool,
+        /// Create .blazeignore file (default); overrides an earlier `--no-ignore`
+        #[arg(long, overrides_with = "no_ignore")]
+        ignore: bool,
         /// Set custom chunk size in KB (default: 64)
         #[arg(long, value_name = "SIZE")]
         chunk_size: Option<usize>,
+        /// Use fixed-size chunking instead of content-defined (FastCDC)
+        /// chunking - cut points won't survive edits near the start of a
+        /// file, but may suit content that's already compressed/encrypted
+        /// and so never content-aligns anyway
+        #[arg(long)]
+        fixed_chunking: bool,
     },
 
     /// Add files to the staging area
@@ -42,8 +125,11 @@ pub enum Commands {
         /// Files or patterns to add
         files: Vec<String>,
         /// Show verbose output
-        #[arg(short, long)]
+        #[arg(short, long, overrides_with = "no_verbose")]
         verbose: bool,
+        /// Suppress verbose output; overrides an earlier `--verbose`
+        #[arg(long, overrides_with = "verbose")]
+        no_verbose: bool,
         /// Add all files (including ignored ones)
         #[arg(long)]
         all: bool,
@@ -95,9 +181,10 @@ pub enum Commands {
         /// Show ignored files
         #[arg(long)]
         ignored: bool,
-        /// Show untracked files
-        #[arg(short, long, default_value = "normal")]
-        untracked_files: UntrackedFiles,
+        /// Show untracked files (defaults to the repo/global config's
+        /// `[status] default_untracked_files`, itself defaulting to `normal`)
+        #[arg(short, long)]
+        untracked_files: Option<UntrackedFiles>,
     },
 
     /// Checkout a specific commit or restore files
@@ -118,6 +205,9 @@ pub enum Commands {
     Branch {
         /// Branch name to create or delete
         name: Option<String>,
+        /// Commit to point the new branch at (full hash or unambiguous
+        /// prefix); defaults to HEAD
+        target: Option<String>,
         /// Delete the specified branch
         #[arg(short = 'd', long)]
         delete: bool,
@@ -141,6 +231,11 @@ pub enum Commands {
         /// Show storage efficiency metrics
         #[arg(long)]
         storage: bool,
+        /// Show byte-level deduplication effectiveness: logical vs. unique
+        /// stored bytes, the top-10 most-referenced chunks, and
+        /// exact-duplicate file groups
+        #[arg(long)]
+        dedup: bool,
     },
 
     /// Verify repository integrity and fix issues
@@ -169,57 +264,391 @@ pub enum Commands {
         /// Show what would be optimized without doing it
         #[arg(long)]
         dry_run: bool,
+        /// With --gc, spare an otherwise-dead chunk if its bundle was
+        /// modified within this many days, so a sweep can't race a
+        /// concurrent add. 0 disables the window.
+        #[arg(long, default_value_t = 0)]
+        keep_days: u64,
+    },
+
+    /// Rewrite HEAD's commit in place, keeping its change ID
+    #[command(alias = "am")]
+    Amend {
+        /// New commit message (defaults to the amended commit's message)
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Automatically stage all modified files
+        #[arg(short = 'a', long)]
+        all: bool,
+        /// Show files being committed
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Re-parent orphaned changes onto their rewritten parents
+    #[command(alias = "rb")]
+    Rebase {},
+
+    /// Export a commit's tree as a portable tar archive
+    Export {
+        /// Commit hash, branch name, or other revision to export
+        target: String,
+        /// Path to the archive to write
+        #[arg(short, long)]
+        output: String,
+        /// Archive format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Tar)]
+        format: ExportFormat,
+    },
+
+    /// Import a tar archive as a new commit
+    Import {
+        /// Path to the tar (or tar.gz) archive to unpack
+        archive: String,
+        /// Commit message (defaults to a generic "Import" message)
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+
+    /// Report duplicate files and cross-file chunk reuse
+    Dups {
+        /// Ignore files smaller than this many bytes when looking for
+        /// identical files
+        #[arg(long, default_value_t = 0)]
+        min_size: u64,
+        /// List every file in each duplicate group instead of just the count
+        #[arg(long)]
+        files: bool,
     },
 }
 
+/// Archive format for `blaze export`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Uncompressed tar archive
+    Tar,
+    /// Gzip-compressed tar archive
+    #[value(name = "tar.gz")]
+    TarGz,
+}
+
 /// Options for showing untracked files
-#[derive(clap::ValueEnum, Clone, Debug)]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum UntrackedFiles {
     /// Hide untracked files
     No,
     /// Show untracked files (default)
+    #[default]
     Normal,
     /// Show all untracked files including those in ignored directories
     All,
 }
 
+/// Machine-readable mirrors of the text output emitted by `log`, `status`,
+/// `stats`, `commit`, and `verify`, serialized when `--format json` is set
+mod report {
+    use crate::database::CommitRecord;
+    use crate::files::changes::{FileChange, FileChangeType};
+    use crate::files::FileStats;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    pub struct CommitReport {
+        pub hash: String,
+        pub parent: Option<String>,
+        pub message: String,
+        pub timestamp: u64,
+        pub file_count: usize,
+    }
+
+    impl CommitReport {
+        pub fn new(hash: &str, record: Option<&CommitRecord>) -> Self {
+            match record {
+                Some(record) => CommitReport {
+                    hash: hash.to_string(),
+                    parent: record.parent.clone(),
+                    message: record.message.clone(),
+                    timestamp: record.timestamp,
+                    file_count: record.files.len(),
+                },
+                None => CommitReport {
+                    hash: hash.to_string(),
+                    parent: None,
+                    message: String::new(),
+                    timestamp: 0,
+                    file_count: 0,
+                },
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct LogEntryReport {
+        pub hash: String,
+        pub parent: Option<String>,
+        pub message: String,
+        pub timestamp: u64,
+        pub file_count: usize,
+        pub orphan: bool,
+    }
+
+    impl LogEntryReport {
+        pub fn new(record: &CommitRecord, orphan: bool) -> Self {
+            LogEntryReport {
+                hash: record.hash.clone(),
+                parent: record.parent.clone(),
+                message: record.message.clone(),
+                timestamp: record.timestamp,
+                file_count: record.files.len(),
+                orphan,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct FileChangeReport {
+        pub path: String,
+        pub change: String,
+    }
+
+    impl From<&FileChange> for FileChangeReport {
+        fn from(change: &FileChange) -> Self {
+            let label = match &change.change_type {
+                FileChangeType::Added => "added".to_string(),
+                FileChangeType::Modified => "modified".to_string(),
+                FileChangeType::Deleted => "deleted".to_string(),
+                FileChangeType::Renamed(old_path) => format!("renamed:{}", old_path),
+            };
+            FileChangeReport {
+                path: change.path.clone(),
+                change: label,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct StatusReport {
+        pub staged: Vec<FileChangeReport>,
+        pub modified: Vec<FileChangeReport>,
+        pub untracked: Vec<String>,
+        pub ignored: Vec<String>,
+        pub head_orphan: bool,
+    }
+
+    #[derive(Serialize)]
+    pub struct StorageReport {
+        pub total_chunk_size: u64,
+        pub total_file_size: u64,
+        pub efficiency_pct: Option<f64>,
+        pub dedup: ChunkSharingReport,
+    }
+
+    #[derive(Serialize)]
+    pub struct ChunkSharingReport {
+        pub unique_chunks_referenced: usize,
+        pub total_chunk_references: usize,
+        pub dedup_ratio: f64,
+        pub chunks_shared_across_multiple_commits: usize,
+    }
+
+    impl From<crate::database::ChunkSharingDistribution> for ChunkSharingReport {
+        fn from(dist: crate::database::ChunkSharingDistribution) -> Self {
+            ChunkSharingReport {
+                unique_chunks_referenced: dist.unique_chunks_referenced,
+                total_chunk_references: dist.total_chunk_references,
+                dedup_ratio: dist.dedup_ratio(),
+                chunks_shared_across_multiple_commits: dist.chunks_shared_across_multiple_commits,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct ChunkReport {
+        pub physical_chunks: usize,
+        pub physical_storage: u64,
+        pub size_distribution: ChunkSizeDistributionReport,
+        pub compression_by_codec: Vec<CodecStatsReport>,
+        pub referenced_chunks: usize,
+        pub orphaned_chunks: usize,
+        pub largest_chunks: Vec<ChunkFootprintReport>,
+    }
+
+    #[derive(Serialize)]
+    pub struct ChunkFootprintReport {
+        pub hash: String,
+        pub stored_bytes: u64,
+    }
+
+    impl From<&crate::chunks::ChunkFootprint> for ChunkFootprintReport {
+        fn from(chunk: &crate::chunks::ChunkFootprint) -> Self {
+            ChunkFootprintReport {
+                hash: chunk.hash.clone(),
+                stored_bytes: chunk.stored_bytes,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct CodecStatsReport {
+        pub codec: String,
+        pub chunk_count: u64,
+        pub original_bytes: u64,
+        pub compressed_bytes: u64,
+        pub ratio: f64,
+    }
+
+    #[derive(Serialize)]
+    pub struct ChunkSizeDistributionReport {
+        pub count: usize,
+        pub min: u64,
+        pub max: u64,
+        pub avg: u64,
+        pub median: u64,
+    }
+
+    impl From<crate::database::ChunkSizeDistribution> for ChunkSizeDistributionReport {
+        fn from(dist: crate::database::ChunkSizeDistribution) -> Self {
+            ChunkSizeDistributionReport {
+                count: dist.count,
+                min: dist.min,
+                max: dist.max,
+                avg: dist.avg,
+                median: dist.median,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct FileStatsReport {
+        pub total_files: usize,
+        pub total_chunks: usize,
+        pub total_bytes: u64,
+        pub binary_files: usize,
+        pub executable_files: usize,
+        pub largest_file: u64,
+        pub average_file_size: f64,
+    }
+
+    impl From<&FileStats> for FileStatsReport {
+        fn from(stats: &FileStats) -> Self {
+            FileStatsReport {
+                total_files: stats.total_files,
+                total_chunks: stats.total_chunks,
+                total_bytes: stats.total_bytes,
+                binary_files: stats.binary_files,
+                executable_files: stats.executable_files,
+                largest_file: stats.largest_file,
+                average_file_size: stats.average_file_size,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct StatsReport {
+        pub commits: usize,
+        pub files_tracked: usize,
+        pub chunks_stored: usize,
+        pub references: usize,
+        pub storage: Option<StorageReport>,
+        pub chunks: Option<ChunkReport>,
+        pub files: Option<FileStatsReport>,
+        pub dedup: Option<DedupReport>,
+    }
+
+    #[derive(Serialize)]
+    pub struct TopChunkReport {
+        pub hash: String,
+        pub size: u64,
+        pub reference_count: u64,
+    }
+
+    #[derive(Serialize)]
+    pub struct DedupReport {
+        pub total_logical_bytes: u64,
+        pub unique_stored_bytes: u64,
+        pub ratio: f64,
+        pub top_chunks: Vec<TopChunkReport>,
+        pub duplicate_file_groups: usize,
+    }
+
+    impl From<&crate::core::DedupStats> for DedupReport {
+        fn from(stats: &crate::core::DedupStats) -> Self {
+            DedupReport {
+                total_logical_bytes: stats.total_logical_bytes,
+                unique_stored_bytes: stats.unique_stored_bytes,
+                ratio: stats.ratio(),
+                top_chunks: stats
+                    .top_chunks
+                    .iter()
+                    .map(|chunk| TopChunkReport {
+                        hash: chunk.hash.clone(),
+                        size: chunk.size,
+                        reference_count: chunk.reference_count,
+                    })
+                    .collect(),
+                duplicate_file_groups: stats.duplicate_files.len(),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    pub struct VerifyReport {
+        pub issues_found: usize,
+        pub fixed: bool,
+    }
+
+    #[derive(Serialize)]
+    pub struct RebaseReport {
+        pub rebased: usize,
+    }
+}
+
+/// Serialize a report to compact JSON and print it as a single line
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
+
 /// Main entry point for the CLI application
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
+    let mode = OutputMode::from_cli(&cli);
 
-    // Initialize the Blaze instance
-    let mut blaze = Blaze::new(".")?;
+    // Initialize the Blaze instance, tuned by .blaze/config.toml and the
+    // user-global config file layered over the built-in defaults
+    let settings = Settings::discover(".")?;
+    let mut blaze = Blaze::new_with_settings(".", settings.clone())?;
 
     // Execute the requested command
     match cli.command {
         Commands::Init {
             path,
             no_ignore,
+            ignore: _,
             chunk_size,
+            fixed_chunking,
         } => {
-            println!("🔥 Initializing Blaze repository in '{}'", path);
-            let mut blaze = Blaze::new(&path)?;
-            blaze.init(no_ignore, chunk_size)?;
-            println!("✅ Blaze repository initialized successfully!");
+            mode.status(&msg("init.start", &[path.as_str()]));
+            let mut blaze = Blaze::new_with_settings(&path, Settings::discover(&path)?)?;
+            blaze.init(no_ignore, chunk_size, fixed_chunking)?;
+            mode.status(&msg("init.done", &[]));
         }
 
         Commands::Add {
             files,
             verbose,
+            no_verbose: _,
             all,
             dry_run,
         } => {
             if dry_run {
-                println!("🔍 Dry run - showing files that would be added:");
+                mode.status("🔍 Dry run - showing files that would be added:");
             }
             let added_files = blaze.add(files, verbose, all, dry_run)?;
 
             if !dry_run {
-                println!(
-                    "✅ Added {} file{}",
-                    added_files,
-                    if added_files == 1 { "" } else { "s" }
-                );
+                mode.status(&msg_n("add.result", added_files as i64, &[]));
             }
         }
 
@@ -229,9 +658,15 @@ pub fn run() -> Result<()> {
             verbose,
             allow_empty,
         } => {
-            println!("📝 Creating commit...");
+            mode.status(&msg("commit.start", &[]));
             let commit_hash = blaze.commit(message, all, verbose, allow_empty)?;
-            println!("✅ Created commit: {}", commit_hash);
+            match mode.format {
+                OutputFormat::Text => mode.status(&msg("commit.done", &[commit_hash.as_str()])),
+                OutputFormat::Json => {
+                    let record = blaze.get_commit(&commit_hash)?;
+                    print_json(&report::CommitReport::new(&commit_hash, record.as_ref()))?;
+                }
+            }
         }
 
         Commands::Log {
@@ -239,16 +674,47 @@ pub fn run() -> Result<()> {
             oneline,
             stat,
             since,
-        } => {
-            blaze.log(limit, oneline, stat, since)?;
-        }
+        } => match mode.format {
+            OutputFormat::Text => blaze.log(limit, oneline, stat, since)?,
+            OutputFormat::Json => {
+                let entries = blaze.log_entries(limit, since)?;
+                let report: Vec<report::LogEntryReport> = entries
+                    .iter()
+                    .map(|record| {
+                        let orphan = blaze.is_orphan_commit(&record.hash).unwrap_or(false);
+                        report::LogEntryReport::new(record, orphan)
+                    })
+                    .collect();
+                print_json(&report)?;
+            }
+        },
 
         Commands::Status {
             short,
             ignored,
             untracked_files,
         } => {
-            blaze.status(short, ignored, untracked_files)?;
+            let untracked_files = untracked_files.unwrap_or(settings.default_untracked_files);
+            match mode.format {
+                OutputFormat::Text => blaze.status(short, ignored, untracked_files)?,
+                OutputFormat::Json => {
+                    let (staged, working) = blaze.status_changes()?;
+                    let (untracked, ignored) = blaze.untracked_status()?;
+                    let head_orphan = blaze
+                        .get_head_commit_hash()?
+                        .map(|hash| blaze.is_orphan_commit(&hash))
+                        .transpose()?
+                        .unwrap_or(false);
+                    let report = report::StatusReport {
+                        staged: staged.iter().map(report::FileChangeReport::from).collect(),
+                        modified: working.iter().map(report::FileChangeReport::from).collect(),
+                        untracked,
+                        ignored,
+                        head_orphan,
+                    };
+                    print_json(&report)?;
+                }
+            }
         }
 
         Commands::Checkout {
@@ -257,17 +723,18 @@ pub fn run() -> Result<()> {
             new_branch,
         } => {
             if let Some(branch_name) = new_branch {
-                println!("🌿 Creating new branch '{}'", branch_name);
-                blaze.create_branch(&branch_name)?;
+                mode.status(&format!("🌿 Creating new branch '{}'", branch_name));
+                blaze.create_branch(&branch_name, None)?;
             }
 
-            println!("📂 Checking out '{}'", target);
+            mode.status(&format!("📂 Checking out '{}'", target));
             blaze.checkout(&target, force)?;
-            println!("✅ Checkout complete");
+            mode.status("✅ Checkout complete");
         }
 
         Commands::Branch {
             name,
+            target,
             delete,
             force_delete,
             all,
@@ -275,10 +742,10 @@ pub fn run() -> Result<()> {
             if let Some(branch_name) = name {
                 if delete || force_delete {
                     blaze.delete_branch(&branch_name, force_delete)?;
-                    println!("🗑️  Deleted branch '{}'", branch_name);
+                    mode.status(&format!("🗑️  Deleted branch '{}'", branch_name));
                 } else {
-                    blaze.create_branch(&branch_name)?;
-                    println!("🌿 Created branch '{}'", branch_name);
+                    blaze.create_branch(&branch_name, target.as_deref())?;
+                    mode.status(&format!("🌿 Created branch '{}'", branch_name));
                 }
             } else {
                 blaze.list_branches(all)?;
@@ -289,30 +756,116 @@ pub fn run() -> Result<()> {
             chunks,
             files,
             storage,
-        } => {
-            blaze.show_stats(chunks, files, storage)?;
-        }
+            dedup,
+        } => match mode.format {
+            OutputFormat::Text => blaze.show_stats(chunks, files, storage, dedup)?,
+            OutputFormat::Json => {
+                let db_stats = blaze.stats_snapshot()?;
+
+                let storage_report = if storage {
+                    let efficiency_pct = if db_stats.total_file_size > 0 {
+                        let ratio =
+                            db_stats.total_chunk_size as f64 / db_stats.total_file_size as f64;
+                        Some((1.0 - ratio) * 100.0)
+                    } else {
+                        None
+                    };
+                    Some(report::StorageReport {
+                        total_chunk_size: db_stats.total_chunk_size,
+                        total_file_size: db_stats.total_file_size,
+                        efficiency_pct,
+                        dedup: blaze.chunk_sharing_distribution()?.into(),
+                    })
+                } else {
+                    None
+                };
+
+                let chunks_report = if chunks {
+                    let (physical_chunks, physical_storage) = blaze.chunk_store_stats()?;
+                    let size_distribution = blaze.chunk_size_distribution()?.into();
+                    let compression_by_codec = blaze
+                        .compression_stats()
+                        .into_iter()
+                        .map(|(codec, stats)| report::CodecStatsReport {
+                            codec: format!("{:?}", codec),
+                            chunk_count: stats.chunk_count,
+                            original_bytes: stats.original_bytes,
+                            compressed_bytes: stats.compressed_bytes,
+                            ratio: stats.ratio(),
+                        })
+                        .collect();
+                    let health = blaze.chunk_store_health()?;
+                    Some(report::ChunkReport {
+                        physical_chunks,
+                        physical_storage,
+                        size_distribution,
+                        compression_by_codec,
+                        referenced_chunks: health.referenced_chunks,
+                        orphaned_chunks: health.orphaned_chunks,
+                        largest_chunks: health.largest_chunks.iter().map(Into::into).collect(),
+                    })
+                } else {
+                    None
+                };
+
+                let files_report = if files {
+                    let working_files = blaze.working_files()?;
+                    let mut file_stats = crate::files::FileStats::new();
+                    for file in working_files.values() {
+                        file_stats.add_file(file);
+                    }
+                    Some(report::FileStatsReport::from(&file_stats))
+                } else {
+                    None
+                };
+
+                let dedup_report = if dedup {
+                    Some(report::DedupReport::from(&blaze.dedup_stats(10)?))
+                } else {
+                    None
+                };
+
+                print_json(&report::StatsReport {
+                    commits: db_stats.commit_count,
+                    files_tracked: db_stats.file_count,
+                    chunks_stored: db_stats.chunk_count,
+                    references: db_stats.ref_count,
+                    storage: storage_report,
+                    chunks: chunks_report,
+                    files: files_report,
+                    dedup: dedup_report,
+                })?;
+            }
+        },
 
         Commands::Verify {
             fix,
             chunks,
             verbose,
         } => {
-            println!("🔍 Verifying repository integrity...");
+            if mode.format == OutputFormat::Text {
+                mode.status("🔍 Verifying repository integrity...");
+            }
             let issues = blaze.verify(fix, chunks, verbose)?;
 
-            if issues == 0 {
-                println!("✅ Repository integrity verified - no issues found");
-            } else {
-                println!(
-                    "⚠️  Found {} issue{}",
-                    issues,
-                    if issues == 1 { "" } else { "s" }
-                );
-                if fix {
-                    println!("🔧 Issues have been fixed");
-                } else {
-                    println!("💡 Run with --fix to attempt automatic repairs");
+            match mode.format {
+                OutputFormat::Text => {
+                    if issues == 0 {
+                        mode.status("✅ Repository integrity verified - no issues found");
+                    } else {
+                        mode.status(&msg_n("verify.issues", issues as i64, &[]));
+                        if fix {
+                            mode.status("🔧 Issues have been fixed");
+                        } else {
+                            mode.status("💡 Run with --fix to attempt automatic repairs");
+                        }
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&report::VerifyReport {
+                        issues_found: issues,
+                        fixed: fix,
+                    })?;
                 }
             }
         }
@@ -321,15 +874,92 @@ pub fn run() -> Result<()> {
             gc,
             repack,
             dry_run,
+            keep_days,
         } => {
             if dry_run {
-                println!("🔍 Dry run - showing optimization opportunities:");
+                mode.status("🔍 Dry run - showing optimization opportunities:");
             }
 
-            let stats = blaze.optimize(gc, repack, dry_run)?;
+            let stats = blaze.optimize(gc, repack, dry_run, keep_days)?;
 
             if !dry_run {
-                println!("✅ Optimization complete: {}", stats);
+                mode.status(&format!("✅ Optimization complete: {}", stats));
+            }
+        }
+
+        Commands::Amend {
+            message,
+            all,
+            verbose,
+        } => {
+            mode.status("📝 Amending commit...");
+            let commit_hash = blaze.amend(message, all, verbose)?;
+            match mode.format {
+                OutputFormat::Text => mode.status(&format!("✅ Amended commit: {}", commit_hash)),
+                OutputFormat::Json => {
+                    let record = blaze.get_commit(&commit_hash)?;
+                    print_json(&report::CommitReport::new(&commit_hash, record.as_ref()))?;
+                }
+            }
+        }
+
+        Commands::Rebase {} => {
+            let rebased = blaze.rebase()?;
+            match mode.format {
+                OutputFormat::Text => {
+                    if rebased == 0 {
+                        mode.status("✅ Nothing to rebase - no orphaned changes found");
+                    } else {
+                        mode.status(&msg_n("rebase.result", rebased as i64, &[]));
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&report::RebaseReport { rebased })?;
+                }
+            }
+        }
+
+        Commands::Export {
+            target,
+            output,
+            format,
+        } => {
+            mode.status(&format!("📦 Exporting '{}' to {}", target, output));
+            blaze.export(&target, Path::new(&output), format)?;
+            mode.status("✅ Export complete");
+        }
+
+        Commands::Import { archive, message } => {
+            mode.status(&format!("📥 Importing {}", archive));
+            let commit_hash = blaze.import(Path::new(&archive), message)?;
+            mode.status(&format!("✅ Imported as commit: {}", commit_hash));
+        }
+
+        Commands::Dups { min_size, files } => {
+            let summary = blaze.dups(min_size)?;
+
+            mode.status("🔁 Duplicate Report");
+            mode.status("═══════════════════");
+            mode.status(&format!(
+                "Duplicate file groups: {} ({} reclaimable)",
+                summary.duplicate_files.len(),
+                format_size(summary.redundant_file_bytes)
+            ));
+            mode.status(&format!(
+                "Shared chunks: {} ({} already deduplicated)",
+                summary.duplicate_chunks.len(),
+                format_size(summary.deduplicated_chunk_bytes)
+            ));
+
+            if files {
+                mode.status("\n📁 Identical Files");
+                mode.status("──────────────────");
+                for group in &summary.duplicate_files {
+                    mode.status(&format!("{} x{}:", format_size(group.size), group.paths.len()));
+                    for path in &group.paths {
+                        mode.status(&format!("  {}", path));
+                    }
+                }
             }
         }
     }
@@ -395,6 +1025,7 @@ mod tests {
                 path,
                 no_ignore,
                 chunk_size,
+                ..
             } => {
                 assert_eq!(path, ".");
                 assert!(!no_ignore);
@@ -411,6 +1042,7 @@ mod tests {
                 verbose,
                 all,
                 dry_run,
+                ..
             } => {
                 assert_eq!(files, vec!["file.txt"]);
                 assert!(verbose);
@@ -448,4 +1080,111 @@ mod tests {
         let normal = UntrackedFiles::from_str("normal", true).unwrap();
         matches!(normal, UntrackedFiles::Normal);
     }
+
+    #[test]
+    fn test_format_flag_defaults_to_text() {
+        let cli = Cli::try_parse_from(["blaze", "log"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Text);
+    }
+
+    #[test]
+    fn test_format_flag_accepts_json() {
+        let cli = Cli::try_parse_from(["blaze", "--format", "json", "status"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Json);
+
+        // The flag is global, so it also parses after the subcommand
+        let cli = Cli::try_parse_from(["blaze", "verify", "--format", "json"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_json_and_format_last_one_wins() {
+        let cli = Cli::try_parse_from(["blaze", "--json", "status"]).unwrap();
+        assert_eq!(OutputMode::from_cli(&cli).format, OutputFormat::Json);
+
+        // --format given after --json should win back to text
+        let cli = Cli::try_parse_from(["blaze", "--json", "--format", "text", "status"]).unwrap();
+        assert_eq!(OutputMode::from_cli(&cli).format, OutputFormat::Text);
+
+        // --json given after --format should win
+        let cli = Cli::try_parse_from(["blaze", "--format", "text", "--json", "status"]).unwrap();
+        assert_eq!(OutputMode::from_cli(&cli).format, OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_quiet_and_color_flags() {
+        let cli = Cli::try_parse_from(["blaze", "-q", "--color", "never", "status"]).unwrap();
+        assert!(cli.quiet);
+        assert_eq!(cli.color, ColorMode::Never);
+
+        let cli = Cli::try_parse_from(["blaze", "status"]).unwrap();
+        assert!(!cli.quiet);
+        assert_eq!(cli.color, ColorMode::Auto);
+    }
+
+    #[test]
+    fn test_ignore_and_verbose_overrides_last_one_wins() {
+        let cli =
+            Cli::try_parse_from(["blaze", "init", "--no-ignore", "--ignore"]).unwrap();
+        match cli.command {
+            Commands::Init { no_ignore, .. } => assert!(!no_ignore),
+            _ => panic!("Expected Init command"),
+        }
+
+        let cli = Cli::try_parse_from(["blaze", "add", "--verbose", "--no-verbose"]).unwrap();
+        match cli.command {
+            Commands::Add { verbose, .. } => assert!(!verbose),
+            _ => panic!("Expected Add command"),
+        }
+    }
+
+    #[test]
+    fn test_amend_and_rebase_aliases() {
+        let cli = Cli::try_parse_from(["blaze", "am", "-m", "fixup"]).unwrap();
+        match cli.command {
+            Commands::Amend { message, all, .. } => {
+                assert_eq!(message, Some("fixup".to_string()));
+                assert!(!all);
+            }
+            _ => panic!("Expected Amend command"),
+        }
+
+        let cli = Cli::try_parse_from(["blaze", "rb"]).unwrap();
+        assert!(matches!(cli.command, Commands::Rebase {}));
+    }
+
+    #[test]
+    fn test_dups_defaults() {
+        let cli = Cli::try_parse_from(["blaze", "dups"]).unwrap();
+        match cli.command {
+            Commands::Dups { min_size, files } => {
+                assert_eq!(min_size, 0);
+                assert!(!files);
+            }
+            _ => panic!("Expected Dups command"),
+        }
+
+        let cli = Cli::try_parse_from(["blaze", "dups", "--min-size", "1024", "--files"]).unwrap();
+        match cli.command {
+            Commands::Dups { min_size, files } => {
+                assert_eq!(min_size, 1024);
+                assert!(files);
+            }
+            _ => panic!("Expected Dups command"),
+        }
+    }
+
+    #[test]
+    fn test_report_structs_serialize_to_json() {
+        let commit = report::CommitReport::new("abc123", None);
+        let json = serde_json::to_string(&commit).unwrap();
+        assert!(json.contains("\"hash\":\"abc123\""));
+
+        let verify = report::VerifyReport {
+            issues_found: 2,
+            fixed: false,
+        };
+        let json = serde_json::to_string(&verify).unwrap();
+        assert_eq!(json, "{\"issues_found\":2,\"fixed\":false}");
+    }
 }
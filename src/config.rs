@@ -1,11 +1,105 @@
 //! Configuration constants and settings for Blaze VCS
 
+use crate::errors::{BlazeError, Result};
+use crate::files::HashAlgo;
+use serde::{Deserialize, Serialize};
+
 /// Size of chunks for file processing (2MB - optimized for storage efficiency)
 pub const CHUNK_SIZE: usize = 2 * 1024 * 1024;
 
+/// Target average chunk size for FastCDC content-defined chunking (8KB -
+/// small enough to keep dedup effective across localized edits)
+pub const CDC_AVG_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Smallest chunk FastCDC will cut; boundary testing is skipped entirely
+/// below this offset so a single byte flip can't produce a flood of
+/// tiny chunks
+pub const CDC_MIN_CHUNK_SIZE: usize = CDC_AVG_CHUNK_SIZE / 4;
+
+/// Largest chunk FastCDC will emit before forcing a cut, bounding worst-case
+/// chunk size when content never satisfies the gear-hash boundary condition
+pub const CDC_MAX_CHUNK_SIZE: usize = CDC_AVG_CHUNK_SIZE * 4;
+
 /// Threshold for considering a file "large" (10MB - optimized for memory mapping with larger chunks)
 pub const LARGE_FILE_THRESHOLD: u64 = 10 * 1024 * 1024;
 
+/// How a file (or byte buffer) is cut into chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkingStrategy {
+    /// Content-defined chunking via the gear-hash boundary detector - cut
+    /// points shift with the content, so edits only touch nearby chunks
+    FastCdc,
+    /// Cut every `avg_size` bytes regardless of content, the way chunking
+    /// worked before FastCDC. Kept around so repos created before FastCDC was
+    /// the default stay readable: chunk boundaries (and therefore existing
+    /// chunk hashes) are only stable across re-chunks if the strategy is
+    /// never switched under a live repo.
+    Fixed,
+}
+
+/// Target/min/max sizes for FastCDC content-defined chunking, exposed as
+/// per-store config instead of being baked into the chunker so callers can
+/// trade dedup granularity for chunk-count overhead (e.g. a larger average
+/// for archival stores dominated by big media files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    /// Which cutting algorithm to use
+    pub strategy: ChunkingStrategy,
+    /// Target average chunk size the normalized masks aim for, or the fixed
+    /// chunk size when `strategy` is [`ChunkingStrategy::Fixed`]
+    pub avg_size: usize,
+    /// Smallest chunk emitted; boundary testing is skipped below this offset.
+    /// Unused under [`ChunkingStrategy::Fixed`].
+    pub min_size: usize,
+    /// Largest chunk emitted before a cut is forced
+    pub max_size: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkingStrategy::FastCdc,
+            avg_size: CDC_AVG_CHUNK_SIZE,
+            min_size: CDC_MIN_CHUNK_SIZE,
+            max_size: CDC_MAX_CHUNK_SIZE,
+        }
+    }
+}
+
+impl ChunkingConfig {
+    /// Validate the policy, rejecting size bounds that don't leave FastCDC
+    /// room to normalize chunk sizes around the average. [`ChunkingStrategy::Fixed`]
+    /// only uses `avg_size`, so `min_size`/`max_size` aren't constrained against it.
+    pub fn validate(&self) -> Result<()> {
+        if self.avg_size == 0 {
+            return Err(BlazeError::Config(
+                "chunking avg_size must be greater than zero".to_string(),
+            ));
+        }
+
+        if matches!(self.strategy, ChunkingStrategy::Fixed) {
+            return Ok(());
+        }
+
+        if self.min_size == 0 {
+            return Err(BlazeError::Config(
+                "chunking min_size must be greater than zero".to_string(),
+            ));
+        }
+
+        if !(self.min_size < self.avg_size && self.avg_size < self.max_size) {
+            return Err(BlazeError::Config(format!(
+                "chunking sizes must satisfy min_size < avg_size < max_size, got {} < {} < {}",
+                self.min_size, self.avg_size, self.max_size
+            )));
+        }
+
+        Ok(())
+    }
+}
+
 /// Name of the Blaze repository directory
 pub const BLAZE_DIR: &str = ".blaze";
 
@@ -18,6 +112,13 @@ pub const CHUNKS_DIR: &str = "chunks";
 /// Name of the repository lock file
 pub const LOCK_FILE: &str = "repo.lock";
 
+/// Name of the persistent working-tree chunk cache file
+pub const CHUNK_CACHE_FILE: &str = "chunk_cache.json";
+
+/// Name of the persistent dirstate file that lets `status`/`find_modified_files`
+/// trust a tracked path's last-known (size, mtime) instead of re-chunking it
+pub const DIRSTATE_FILE: &str = "dirstate.json";
+
 /// Default commit message when none is provided
 pub const DEFAULT_COMMIT_MESSAGE: &str = "Quick commit";
 
@@ -80,7 +181,32 @@ pub mod app_info {
     pub const HOMEPAGE: &str = "https://github.com/blazevcs/blaze";
 }
 
+/// Underlying storage media a repository's `.blaze` directory sits on.
+/// Spinning and solid-state media reward opposite tradeoffs: an HDD wants
+/// large sequential buffers and few concurrent I/O streams competing for the
+/// head, while an SSD has cheap random access and wins from more
+/// parallelism instead. See [`PerformanceConfig::tune_for_storage`] and
+/// [`DatabaseConfig::tune_for_storage`] for where this actually changes
+/// behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageProfile {
+    /// Tuned for solid-state media: smaller buffers, more aggressive
+    /// parallelism
+    Ssd,
+    /// Tuned for spinning media: larger sequential buffers, fewer
+    /// concurrent I/O streams
+    Hdd,
+    /// Probe the repository's mount at open time and resolve to [`Ssd`](StorageProfile::Ssd)
+    /// or [`Hdd`](StorageProfile::Hdd) - see `Settings::discover` for the
+    /// actual probing
+    #[default]
+    Auto,
+}
+
 /// Performance tuning configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PerformanceConfig {
     /// Number of worker threads for parallel processing
     pub worker_threads: usize,
@@ -92,6 +218,39 @@ pub struct PerformanceConfig {
     pub use_memory_mapping: bool,
     /// Enable compression for chunk storage
     pub enable_compression: bool,
+    /// Content-defined chunking policy - the knob that actually selects
+    /// FastCDC vs. fixed-size cutting and its size bounds for new chunk
+    /// stores, so callers tune dedup granularity in the same place as every
+    /// other performance knob instead of constructing a [`ChunkingConfig`]
+    /// separately
+    pub chunking: ChunkingConfig,
+    /// Chunk compression codec and level - which [`CompressionAlgo`] to use
+    /// (zstd, gzip, deflate, Brotli, ...) and at what level, selectable
+    /// alongside every other performance knob instead of constructing a
+    /// [`CompressionConfig`] separately
+    pub compression: CompressionConfig,
+    /// Content hash algorithm chunks are addressed by. Defaults to XXH3, a
+    /// non-cryptographic hash several times faster than any of the
+    /// cryptographic options, which matters more for the common case of
+    /// hashing every chunk of a large working tree than collision
+    /// resistance does; `Blake3` is offered for users who want that
+    /// resistance back, and `Blake2b`/`Sha256` for deployments with a
+    /// specific compliance requirement
+    pub hash_algo: HashAlgo,
+    /// Number of chunks coalesced into a single prefetch batch when
+    /// materializing a file during checkout/restore, so a large multi-chunk
+    /// file saturates I/O instead of stalling on one lookup per chunk. The
+    /// chunk store clamps this to [`MAX_MEMORY_BUFFER`] worth of chunks
+    /// regardless of what's configured here, bounding how much decompressed
+    /// data can be in flight at once.
+    pub read_amplification_batch: usize,
+    /// Optional cap, in bytes/sec, on aggregate throughput for bulk chunk
+    /// writes and bundle compaction, so a large commit doesn't saturate disk
+    /// bandwidth on a machine doing other work at the same time. `None`
+    /// leaves I/O unthrottled. Flush/write traffic is given precedence over
+    /// background compaction when the budget is constrained - see
+    /// [`crate::rate_limit::RateLimiter`].
+    pub rate_limit: Option<u64>,
 }
 
 impl Default for PerformanceConfig {
@@ -102,11 +261,47 @@ impl Default for PerformanceConfig {
             write_buffer_size: CHUNK_SIZE * 2,
             use_memory_mapping: true,
             enable_compression: true,
+            chunking: ChunkingConfig::default(),
+            compression: CompressionConfig::default(),
+            hash_algo: HashAlgo::default(),
+            read_amplification_batch: 8,
+            rate_limit: None,
+        }
+    }
+}
+
+impl PerformanceConfig {
+    /// Re-tune buffer sizes and worker thread count for `profile`, in place.
+    /// `profile` must already be resolved to [`StorageProfile::Ssd`] or
+    /// [`StorageProfile::Hdd`] - `Auto` is a no-op here since there's nothing
+    /// concrete to tune for.
+    pub fn tune_for_storage(&mut self, profile: StorageProfile) {
+        match profile {
+            StorageProfile::Hdd => {
+                // Large sequential buffers amortize the seek cost of jumping
+                // between bundle and index files; fewer worker threads so
+                // concurrent chunking doesn't turn one sequential stream
+                // into several competing ones.
+                self.read_buffer_size = CHUNK_SIZE * 8;
+                self.write_buffer_size = CHUNK_SIZE * 8;
+                self.worker_threads = get_max_parallel_threads().max(2);
+            }
+            StorageProfile::Ssd => {
+                // Random access is cheap, so smaller buffers free up memory
+                // without costing throughput; parallelism is where SSDs
+                // actually win.
+                self.read_buffer_size = CHUNK_SIZE;
+                self.write_buffer_size = CHUNK_SIZE;
+                self.worker_threads = (get_max_parallel_threads() * 2).max(8);
+            }
+            StorageProfile::Auto => {}
         }
     }
 }
 
 /// Database configuration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatabaseConfig {
     /// Connection timeout in seconds
     pub timeout: u32,
@@ -116,6 +311,26 @@ pub struct DatabaseConfig {
     pub cache_size: i32,
     /// Enable foreign key constraints
     pub enable_foreign_keys: bool,
+    /// Storage media this database's file is expected to sit on - drives
+    /// [`Self::tune_for_storage`]
+    pub storage_profile: StorageProfile,
+    /// Background/checkpoint worker threads, scaled off
+    /// [`get_max_parallel_threads`] by [`Self::tune_for_storage`]
+    pub background_threads: usize,
+    /// Maximum number of pooled SQLite connections `Database` keeps open at
+    /// once - each is configured (WAL, cache size, foreign keys, ...) only
+    /// the first time it's opened, then reused for the life of the pool
+    /// instead of reopened per call
+    pub pool_size: usize,
+    /// How many times a transactional batch method retries after a
+    /// transient `SQLITE_BUSY`/`SQLITE_LOCKED` error before giving up and
+    /// surfacing it, including the first attempt
+    pub retry_max_attempts: u32,
+    /// Delay before the first retry, in milliseconds - doubles on each
+    /// subsequent attempt up to `retry_max_delay_ms`
+    pub retry_base_delay_ms: u64,
+    /// Cap on the exponential backoff delay between retries, in milliseconds
+    pub retry_max_delay_ms: u64,
 }
 
 impl Default for DatabaseConfig {
@@ -125,10 +340,198 @@ impl Default for DatabaseConfig {
             enable_wal_mode: true,
             cache_size: 32768, // 32MB - larger cache for better performance
             enable_foreign_keys: false, // Disable for better performance
+            storage_profile: StorageProfile::Auto,
+            pool_size: get_max_parallel_threads().max(4),
+            background_threads: get_max_parallel_threads().max(1),
+            retry_max_attempts: 5,
+            retry_base_delay_ms: 5,
+            retry_max_delay_ms: 200,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// Re-tune the page cache size and background thread count for
+    /// `profile`, in place, and record it as `storage_profile`. `profile`
+    /// must already be resolved to [`StorageProfile::Ssd`] or
+    /// [`StorageProfile::Hdd`] - `Auto` is stored as-is with no tuning
+    /// applied, since there's nothing concrete to tune for.
+    pub fn tune_for_storage(&mut self, profile: StorageProfile) {
+        self.storage_profile = profile;
+
+        match profile {
+            StorageProfile::Hdd => {
+                // A bigger page cache amortizes a seek across more cached
+                // pages; fewer background threads so checkpoint I/O doesn't
+                // compete with the single sequential stream the head is
+                // already committed to.
+                self.cache_size = 131072; // 128MB
+                self.background_threads = (get_max_parallel_threads() / 4).max(1);
+            }
+            StorageProfile::Ssd => {
+                // Random access is cheap, so a smaller cache leaves more RAM
+                // for the OS page cache, and checkpointing can run alongside
+                // more concurrent readers/writers.
+                self.cache_size = 16384; // 16MB
+                self.background_threads = get_max_parallel_threads().max(2);
+            }
+            StorageProfile::Auto => {}
+        }
+    }
+}
+
+/// Which compression algorithm a chunk store uses for a given chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionAlgo {
+    /// Never compress - useful for already-compressed media (images, archives, video)
+    None,
+    /// Always compress with LZ4 - fast, modest ratio
+    Lz4,
+    /// Always compress with zstd at the configured level
+    Zstd,
+    /// Always compress with gzip (DEFLATE + gzip framing) at the configured level -
+    /// wider decoder support than zstd, at a worse ratio/speed tradeoff
+    Gzip,
+    /// Always compress with raw DEFLATE at the configured level - gzip without
+    /// the container overhead, for chunks where every byte of framing matters
+    Deflate,
+    /// Always compress with Brotli at the configured level - slower than zstd
+    /// but tends to win on text-heavy chunks (source files, docs)
+    Brotli,
+    /// Try zstd first, fall back to LZ4, then fall back to storing raw data -
+    /// the original hardcoded heuristic
+    Auto,
+}
+
+/// Compression level selection, shared across every codec in [`CompressionAlgo`]
+/// that takes a level (everything but `None` and `Lz4`). `Fixed`'s value is
+/// interpreted against whichever codec is active, and [`CompressionConfig::validate`]
+/// rejects a value outside that codec's valid range (1-22 for zstd and `Auto`,
+/// 1-9 for gzip/deflate, 0-11 for Brotli).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionLevel {
+    /// Pick a level from input size, the way the original heuristic ladder did
+    Auto,
+    /// Always use this exact level, interpreted per-codec
+    Fixed(i32),
+}
+
+/// Chunk compression policy, analogous to garage's configurable block compression
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Which algorithm to use
+    pub algo: CompressionAlgo,
+    /// Level to use when `algo` takes one, i.e. every variant but `None` and `Lz4`
+    pub level: CompressionLevel,
+    /// Chunks smaller than this are stored raw without attempting compression
+    pub min_size: usize,
+    /// Minimum fraction of size a compressed chunk must shave off to be kept
+    /// (e.g. 0.1 means compressed output must be at most 90% of the input size)
+    pub min_savings_ratio: f64,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            algo: CompressionAlgo::Auto,
+            level: CompressionLevel::Auto,
+            min_size: 128,
+            min_savings_ratio: 0.1,
         }
     }
 }
 
+impl CompressionConfig {
+    /// Validate the policy, rejecting out-of-range knobs before they reach the
+    /// compressor
+    pub fn validate(&self) -> Result<()> {
+        if let CompressionLevel::Fixed(level) = self.level {
+            let range = match self.algo {
+                CompressionAlgo::Zstd => 1..=22,
+                CompressionAlgo::Gzip | CompressionAlgo::Deflate => 1..=9,
+                CompressionAlgo::Brotli => 0..=11,
+                // `None` and `Lz4` ignore the level; `Auto` hands it to zstd.
+                // All three are checked against zstd's range.
+                CompressionAlgo::None | CompressionAlgo::Lz4 | CompressionAlgo::Auto => 1..=22,
+            };
+
+            if !range.contains(&level) {
+                return Err(BlazeError::Config(format!(
+                    "compression level must be between {} and {} for {:?}, got {}",
+                    range.start(),
+                    range.end(),
+                    self.algo,
+                    level
+                )));
+            }
+        }
+
+        if !(0.0..1.0).contains(&self.min_savings_ratio) {
+            return Err(BlazeError::Config(format!(
+                "min_savings_ratio must be between 0.0 and 1.0 (exclusive), got {}",
+                self.min_savings_ratio
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Opt-in at-rest encryption of chunk payloads, applied by
+/// [`crate::chunks::ChunkStore`] at the bundle I/O boundary - transparent to
+/// compression and delta-encoding, which operate on the plaintext either
+/// side of it. Chunk hashes are computed over plaintext before this ever
+/// runs (see `files::compute_chunk_hash_with`), so dedup is unaffected by
+/// whether encryption is on.
+///
+/// Deliberately not `Serialize`/`Deserialize` - unlike the other `*Config`
+/// types, its key material has no business round-tripping through a
+/// settings file in plain text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncryptionConfig {
+    key: Option<[u8; 32]>,
+}
+
+impl EncryptionConfig {
+    /// No encryption - chunk payloads are stored exactly as compression (or
+    /// delta-encoding) left them
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Encrypt every chunk payload with `key` (AES-256-GCM) before it's
+    /// written to a bundle
+    pub fn with_key(key: [u8; 32]) -> Self {
+        Self { key: Some(key) }
+    }
+
+    /// Derive a 256-bit key from `passphrase` for callers that would rather
+    /// type a passphrase than manage a raw key file. This is a plain
+    /// SHA-256 hash rather than a dedicated password-hashing KDF
+    /// (argon2/scrypt), so prefer [`Self::with_key`] with a properly
+    /// generated key wherever one can be stored instead of typed.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(passphrase.as_bytes());
+        Self::with_key(hasher.finalize().into())
+    }
+
+    /// The symmetric key chunk payloads are encrypted with, if any
+    pub fn key(&self) -> Option<&[u8; 32]> {
+        self.key.as_ref()
+    }
+
+    /// Whether this config actually encrypts anything
+    pub fn is_enabled(&self) -> bool {
+        self.key.is_some()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,6 +547,7 @@ mod tests {
         assert_eq!(DB_FILE, "metadata.db");
         assert_eq!(CHUNKS_DIR, "chunks");
         assert_eq!(LOCK_FILE, "repo.lock");
+        assert_eq!(DIRSTATE_FILE, "dirstate.json");
     }
 
     #[test]
@@ -152,6 +556,11 @@ mod tests {
         assert!(config.worker_threads > 0);
         assert!(config.read_buffer_size > 0);
         assert!(config.write_buffer_size > 0);
+        assert_eq!(config.chunking, ChunkingConfig::default());
+        assert_eq!(config.compression, CompressionConfig::default());
+        assert_eq!(config.hash_algo, HashAlgo::Xxh3);
+        assert!(config.read_amplification_batch > 0);
+        assert_eq!(config.rate_limit, None);
     }
 
     #[test]
@@ -160,4 +569,127 @@ mod tests {
         assert!(config.timeout > 0);
         assert!(config.cache_size > 0);
     }
+
+    #[test]
+    fn test_compression_config_default_validates() {
+        assert!(CompressionConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_compression_config_rejects_bad_zstd_level() {
+        let config = CompressionConfig {
+            algo: CompressionAlgo::Zstd,
+            level: CompressionLevel::Fixed(0),
+            ..CompressionConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_compression_config_validates_brotli_and_gzip_level_ranges() {
+        let brotli_too_high = CompressionConfig {
+            algo: CompressionAlgo::Brotli,
+            level: CompressionLevel::Fixed(12),
+            ..CompressionConfig::default()
+        };
+        assert!(brotli_too_high.validate().is_err());
+
+        let brotli_ok = CompressionConfig {
+            algo: CompressionAlgo::Brotli,
+            level: CompressionLevel::Fixed(11),
+            ..CompressionConfig::default()
+        };
+        assert!(brotli_ok.validate().is_ok());
+
+        let gzip_too_high = CompressionConfig {
+            algo: CompressionAlgo::Gzip,
+            level: CompressionLevel::Fixed(10),
+            ..CompressionConfig::default()
+        };
+        assert!(gzip_too_high.validate().is_err());
+    }
+
+    #[test]
+    fn test_compression_config_rejects_bad_savings_ratio() {
+        let config = CompressionConfig {
+            min_savings_ratio: 1.5,
+            ..CompressionConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_chunking_config_default_validates() {
+        assert!(ChunkingConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_chunking_config_rejects_out_of_order_sizes() {
+        let config = ChunkingConfig {
+            strategy: ChunkingStrategy::FastCdc,
+            min_size: 100,
+            avg_size: 50,
+            max_size: 200,
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_chunking_config_fixed_strategy_ignores_min_max_ordering() {
+        let config = ChunkingConfig {
+            strategy: ChunkingStrategy::Fixed,
+            avg_size: 4096,
+            min_size: 0,
+            max_size: 0,
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_performance_config_tunes_larger_buffers_for_hdd() {
+        let mut ssd = PerformanceConfig::default();
+        let mut hdd = PerformanceConfig::default();
+        ssd.tune_for_storage(StorageProfile::Ssd);
+        hdd.tune_for_storage(StorageProfile::Hdd);
+
+        assert!(hdd.read_buffer_size > ssd.read_buffer_size);
+        assert!(hdd.write_buffer_size > ssd.write_buffer_size);
+    }
+
+    #[test]
+    fn test_database_config_tunes_larger_cache_for_hdd() {
+        let mut ssd = DatabaseConfig::default();
+        let mut hdd = DatabaseConfig::default();
+        ssd.tune_for_storage(StorageProfile::Ssd);
+        hdd.tune_for_storage(StorageProfile::Hdd);
+
+        assert!(hdd.cache_size > ssd.cache_size);
+        assert_eq!(ssd.storage_profile, StorageProfile::Ssd);
+        assert_eq!(hdd.storage_profile, StorageProfile::Hdd);
+    }
+
+    #[test]
+    fn test_database_config_auto_profile_is_a_no_op() {
+        let mut config = DatabaseConfig::default();
+        let before = config.cache_size;
+        config.tune_for_storage(StorageProfile::Auto);
+        assert_eq!(config.cache_size, before);
+    }
+
+    #[test]
+    fn test_encryption_config_disabled_by_default() {
+        assert!(!EncryptionConfig::default().is_enabled());
+        assert!(EncryptionConfig::disabled().key().is_none());
+    }
+
+    #[test]
+    fn test_encryption_config_from_passphrase_is_deterministic() {
+        let a = EncryptionConfig::from_passphrase("correct horse battery staple");
+        let b = EncryptionConfig::from_passphrase("correct horse battery staple");
+        assert!(a.is_enabled());
+        assert_eq!(a.key(), b.key());
+
+        let c = EncryptionConfig::from_passphrase("a different passphrase");
+        assert_ne!(a.key(), c.key());
+    }
 }
@@ -4,19 +4,243 @@ use crate::config::{DatabaseConfig, DB_FILE};
 use crate::errors::{BlazeError, Result, ResultExt};
 use crate::files::FileRecord;
 use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One ordered step in [`SCHEMA_MIGRATIONS`]: the schema version it brings
+/// the database to, and the SQL that gets it there from `version - 1`
+struct Migration {
+    version: i64,
+    sql: &'static str,
+}
+
+/// The database's schema history, in order - [`Database::migrate`] applies
+/// whichever suffix of this list is newer than the open database's
+/// `PRAGMA user_version`. Append new steps here rather than editing old
+/// ones, so a repository opened partway through this history still upgrades
+/// one version at a time.
+static SCHEMA_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS chunks (
+                hash TEXT PRIMARY KEY,
+                size INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS files (
+                path TEXT PRIMARY KEY,
+                chunks TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                mtime INTEGER NOT NULL,
+                permissions INTEGER NOT NULL,
+                is_executable INTEGER NOT NULL,
+                partial_hash TEXT NOT NULL DEFAULT '',
+                full_hash TEXT,
+                kind TEXT NOT NULL DEFAULT '"Regular"',
+                xattrs TEXT NOT NULL DEFAULT '{}'
+            );
+
+            CREATE TABLE IF NOT EXISTS commits (
+                hash TEXT PRIMARY KEY,
+                parent TEXT,
+                message TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                tree_hash TEXT NOT NULL,
+                files_json TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS refs (
+                name TEXT PRIMARY KEY,
+                commit_hash TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS changes (
+                change_id TEXT PRIMARY KEY,
+                commit_hash TEXT NOT NULL,
+                parent_change_id TEXT,
+                parent_commit_hash TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chunks_created_at ON chunks(created_at);
+            CREATE INDEX IF NOT EXISTS idx_commits_timestamp ON commits(timestamp DESC);
+            CREATE INDEX IF NOT EXISTS idx_commits_parent ON commits(parent);
+            CREATE INDEX IF NOT EXISTS idx_files_mtime ON files(mtime);
+            CREATE INDEX IF NOT EXISTS idx_changes_commit_hash ON changes(commit_hash);
+        "#,
+    },
+    Migration {
+        version: 2,
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS file_chunks (
+                path TEXT NOT NULL,
+                chunk_hash TEXT NOT NULL,
+                ordinal INTEGER NOT NULL,
+                PRIMARY KEY (path, ordinal)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_file_chunks_hash ON file_chunks(chunk_hash);
+        "#,
+    },
+    Migration {
+        version: 3,
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS gc_keep (
+                hash TEXT PRIMARY KEY
+            );
+        "#,
+    },
+];
+
+/// A small fixed-capacity pool of already-configured SQLite connections,
+/// reused across calls instead of opening (and re-applying `busy_timeout`
+/// and every PRAGMA to) a fresh connection per method - the same role
+/// r2d2/r2d2_sqlite play for other backends, implemented directly here since
+/// `Database` is the only thing in the crate that needs one. Connections are
+/// configured once, the first time they're physically opened, not on every
+/// checkout.
+struct ConnectionPool {
+    db_path: PathBuf,
+    config: DatabaseConfig,
+    state: Mutex<PoolState>,
+    slot_freed: Condvar,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: Vec<Connection>,
+    /// Connections currently open, whether idle above or checked out -
+    /// bounded by `DatabaseConfig::pool_size`
+    open: usize,
+}
+
+impl ConnectionPool {
+    fn new(db_path: PathBuf, config: DatabaseConfig) -> Self {
+        ConnectionPool {
+            db_path,
+            config,
+            state: Mutex::new(PoolState::default()),
+            slot_freed: Condvar::new(),
+        }
+    }
+
+    /// Check out a connection: reuse an idle one if there is one, otherwise
+    /// open (and configure) a new one if the pool has spare capacity, or
+    /// block until one of the two becomes possible
+    fn acquire(self: &Arc<Self>) -> Result<PooledConnection> {
+        let capacity = self.config.pool_size.max(1);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let conn = loop {
+            if let Some(conn) = state.idle.pop() {
+                break conn;
+            }
+
+            if state.open < capacity {
+                state.open += 1;
+                break self.open_connection()?;
+            }
+
+            state = self
+                .slot_freed
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        };
+
+        Ok(PooledConnection {
+            pool: Arc::clone(self),
+            conn: Some(conn),
+        })
+    }
+
+    /// Return a connection to the idle list and wake one waiter, if any
+    fn release(&self, conn: Connection) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.idle.push(conn);
+        drop(state);
+        self.slot_freed.notify_one();
+    }
+
+    /// Open and fully configure a brand-new physical connection - runs once
+    /// per connection the pool ever opens, rather than on every checkout
+    fn open_connection(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.db_path)
+            .with_context(|| format!("Failed to open database: {}", self.db_path.display()))?;
+
+        conn.busy_timeout(Duration::from_secs(self.config.timeout as u64))
+            .context("Failed to set database timeout")?;
+
+        conn.execute_batch(&format!(
+            r#"
+            PRAGMA foreign_keys = {};
+            PRAGMA journal_mode = {};
+            PRAGMA cache_size = -{};
+            PRAGMA synchronous = NORMAL;
+            PRAGMA temp_store = MEMORY;
+            "#,
+            if self.config.enable_foreign_keys {
+                "ON"
+            } else {
+                "OFF"
+            },
+            if self.config.enable_wal_mode {
+                "WAL"
+            } else {
+                "DELETE"
+            },
+            self.config.cache_size
+        ))
+        .context("Failed to configure connection")?;
+
+        Ok(conn)
+    }
+}
+
+/// A connection checked out from a [`ConnectionPool`], returned to its idle
+/// list on drop rather than closed
+struct PooledConnection {
+    pool: Arc<ConnectionPool>,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection taken before drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection taken before drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.release(conn);
+        }
+    }
+}
 
 /// Database manager for Blaze VCS
 pub struct Database {
     db_path: PathBuf,
     config: DatabaseConfig,
+    pool: Arc<ConnectionPool>,
 }
 
 /// Represents a commit in the database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitRecord {
     pub hash: String,
     pub parent: Option<String>,
@@ -27,96 +251,981 @@ pub struct CommitRecord {
 }
 
 /// Represents a reference (branch/tag) in the database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefRecord {
     pub name: String,
     pub commit_hash: Option<String>,
 }
 
 /// Represents a chunk record in the database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkRecord {
     pub hash: String,
     pub size: u64,
     pub created_at: u64,
 }
 
+/// Outcome of [`Database::garbage_collect`]: the chunks it deleted and how
+/// many bytes they accounted for
+#[derive(Debug, Clone, Default)]
+pub struct ChunkGcResult {
+    pub reclaimed_hashes: Vec<String>,
+    pub reclaimed_bytes: u64,
+}
+
+/// Outcome of [`Database::gc`]
+#[derive(Debug, Clone, Default)]
+pub struct GcReport {
+    pub chunks_deleted: usize,
+    pub bytes_freed: u64,
+    pub commits_scanned: usize,
+}
+
+/// Header entry written first in every [`Database::dump`] archive, read by
+/// [`Database::restore`] before it dispatches to the [`Loader`] matching
+/// `db_version`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpMetadata {
+    pub db_version: i64,
+    pub created_at: u64,
+    pub chunk_count: usize,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// Every table [`Database::dump`]/[`Database::restore`] round-trip, decoded
+/// into the schema's current in-memory shape by whichever [`Loader`]
+/// matches the dump's `db_version`
+#[derive(Debug, Clone, Default)]
+struct LoadedTables {
+    chunks: Vec<ChunkRecord>,
+    files: Vec<FileRecord>,
+    commits: Vec<CommitRecord>,
+    refs: Vec<RefRecord>,
+    changes: Vec<ChangeRecord>,
+}
+
+/// Decodes one historical on-disk schema version's dump entries. Matched
+/// against a dump's [`DumpMetadata::db_version`] by [`load_tables`], which
+/// walks forward from there through any later versions' [`Loader::upgrade`]
+/// so a dump made by an older Blaze release is upgraded in memory rather
+/// than rejected outright.
+trait Loader {
+    /// The schema version this loader reads entries for
+    fn version(&self) -> i64;
+
+    /// Parse this version's dump entries into the current [`LoadedTables`]
+    /// shape
+    fn load(&self, entries: &HashMap<String, Vec<u8>>) -> Result<LoadedTables>;
+
+    /// Translate tables decoded by an older loader into this version's
+    /// shape. The default is a no-op, which is all any loader needs until a
+    /// migration changes a record's fields enough that already-decoded data
+    /// must be transformed rather than just reparsed.
+    fn upgrade(&self, tables: LoadedTables) -> Result<LoadedTables> {
+        Ok(tables)
+    }
+}
+
+/// Reads the current (version 3) on-disk schema's dump entries - every
+/// record type already serializes to the shape this schema version stores,
+/// so no translation is needed beyond parsing the JSON
+struct LoaderV3;
+
+impl Loader for LoaderV3 {
+    fn version(&self) -> i64 {
+        3
+    }
+
+    fn load(&self, entries: &HashMap<String, Vec<u8>>) -> Result<LoadedTables> {
+        Ok(LoadedTables {
+            chunks: read_table_entry(entries, "chunks.json")?,
+            files: read_table_entry(entries, "files.json")?,
+            commits: read_table_entry(entries, "commits.json")?,
+            refs: read_table_entry(entries, "refs.json")?,
+            changes: read_table_entry(entries, "changes.json")?,
+        })
+    }
+}
+
+/// Every [`Loader`] this build of Blaze understands, oldest first - append
+/// a new one here alongside each future schema migration rather than
+/// replacing `LoaderV3`, so dumps taken before that migration keep loading
+fn loaders() -> Vec<Box<dyn Loader>> {
+    vec![Box::new(LoaderV3)]
+}
+
+/// Parse one dump entry's JSON bytes into a table's record type, reporting
+/// a missing entry the same way a malformed one is reported rather than
+/// panicking on the `HashMap` lookup
+fn read_table_entry<T: serde::de::DeserializeOwned>(
+    entries: &HashMap<String, Vec<u8>>,
+    name: &str,
+) -> Result<Vec<T>> {
+    let bytes = entries.get(name).ok_or_else(|| {
+        BlazeError::Serialization(format!("Dump archive is missing {}", name))
+    })?;
+
+    serde_json::from_slice(bytes).with_context(|| format!("Failed to parse {} from dump archive", name))
+}
+
+/// Decode a dump's tables starting from the [`Loader`] matching its
+/// `db_version`, then chain forward through any newer loaders' `upgrade` so
+/// an old dump ends up in the current schema's shape
+fn load_tables(db_version: i64, entries: &HashMap<String, Vec<u8>>) -> Result<LoadedTables> {
+    let mut loaders = loaders();
+    loaders.sort_by_key(|loader| loader.version());
+
+    let start = loaders
+        .iter()
+        .position(|loader| loader.version() == db_version)
+        .ok_or_else(|| {
+            BlazeError::Serialization(format!(
+                "No loader available for dump schema version {}",
+                db_version
+            ))
+        })?;
+
+    let mut tables = loaders[start].load(entries)?;
+    for loader in &loaders[start + 1..] {
+        tables = loader.upgrade(tables)?;
+    }
+
+    Ok(tables)
+}
+
+/// Serialize one table to JSON and append it as a named entry in an
+/// in-progress dump archive, matching [`crate::core::Blaze::export`]'s tar
+/// archive style
+fn append_json_entry<W: Write, T: Serialize>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    value: &T,
+) -> Result<()> {
+    let bytes = serde_json::to_vec(value).with_context(|| format!("Failed to serialize {}", name))?;
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+
+    builder
+        .append_data(&mut header, name, &bytes[..])
+        .with_context(|| format!("Failed to add {} to dump archive", name))?;
+
+    Ok(())
+}
+
+/// A Jujutsu-style stable identity for a commit, kept across amends/rebases
+/// even though those operations give the commit a new content hash.
+///
+/// `parent_commit_hash` pins the commit hash this change's parent pointed to
+/// when the link was made; if the parent change's `commit_hash` later moves
+/// away from that pinned value (because the parent was itself rewritten),
+/// this change is orphaned until a rebase re-parents it onto the new hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub change_id: String,
+    pub commit_hash: String,
+    pub parent_change_id: Option<String>,
+    pub parent_commit_hash: Option<String>,
+}
+
+/// Parse one row of a query's result into `Self`, so a query's shape only
+/// has to be matched up with its target type once - at the `impl` below -
+/// instead of in a fresh `row.get::<_, i64>(n)? as u64`-style closure at
+/// every call site. See [`row_extract`] and [`Database::query_rows`].
+pub trait FromRow: Sized {
+    fn from_row(row: &Row) -> rusqlite::Result<Self>;
+}
+
+/// `query_map`-compatible function pointer that extracts a `T: FromRow`
+/// from a row - lets `query_map(params, row_extract::<T>)` stand in for a
+/// one-off closure.
+fn row_extract<T: FromRow>(row: &Row) -> rusqlite::Result<T> {
+    T::from_row(row)
+}
+
+impl FromRow for ChunkRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ChunkRecord {
+            hash: row.get(0)?,
+            size: row.get::<_, i64>(1)? as u64,
+            created_at: row.get::<_, i64>(2)? as u64,
+        })
+    }
+}
+
+impl FromRow for FileRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let chunks_json: String = row.get(1)?;
+        let chunks: Vec<String> = serde_json::from_str(&chunks_json).map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(1, "chunks".to_string(), rusqlite::types::Type::Text)
+        })?;
+
+        let kind_json: String = row.get(8)?;
+        let kind = serde_json::from_str(&kind_json).map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(8, "kind".to_string(), rusqlite::types::Type::Text)
+        })?;
+
+        let xattrs_json: String = row.get(9)?;
+        let xattrs = serde_json::from_str(&xattrs_json).map_err(|_e| {
+            rusqlite::Error::InvalidColumnType(9, "xattrs".to_string(), rusqlite::types::Type::Text)
+        })?;
+
+        Ok(FileRecord {
+            path: row.get(0)?,
+            chunks,
+            size: row.get::<_, i64>(2)? as u64,
+            mtime: row.get::<_, i64>(3)? as u64,
+            permissions: row.get::<_, i64>(4)? as u32,
+            is_executable: row.get::<_, i64>(5)? != 0,
+            partial_hash: row.get(6)?,
+            full_hash: row.get(7)?,
+            kind,
+            xattrs,
+        })
+    }
+}
+
+impl FromRow for CommitRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let files_json: String = row.get(5)?;
+        let files: HashMap<String, FileRecord> = serde_json::from_str(&files_json).map_err(|_| {
+            rusqlite::Error::InvalidColumnType(5, "files_json".to_string(), rusqlite::types::Type::Text)
+        })?;
+
+        Ok(CommitRecord {
+            hash: row.get(0)?,
+            parent: row.get(1)?,
+            message: row.get(2)?,
+            timestamp: row.get::<_, i64>(3)? as u64,
+            tree_hash: row.get(4)?,
+            files,
+        })
+    }
+}
+
+impl FromRow for RefRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(RefRecord {
+            name: row.get(0)?,
+            commit_hash: row.get(1)?,
+        })
+    }
+}
+
+impl FromRow for ChangeRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ChangeRecord {
+            change_id: row.get(0)?,
+            commit_hash: row.get(1)?,
+            parent_change_id: row.get(2)?,
+            parent_commit_hash: row.get(3)?,
+        })
+    }
+}
+
+macro_rules! impl_from_row_for_tuple {
+    ($($idx:tt => $ty:ident),+) => {
+        impl<$($ty),+> FromRow for ($($ty,)+)
+        where
+            $($ty: rusqlite::types::FromSql),+
+        {
+            fn from_row(row: &Row) -> rusqlite::Result<Self> {
+                Ok(($(row.get::<_, $ty>($idx)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_row_for_tuple!(0 => A);
+impl_from_row_for_tuple!(0 => A, 1 => B);
+impl_from_row_for_tuple!(0 => A, 1 => B, 2 => C);
+impl_from_row_for_tuple!(0 => A, 1 => B, 2 => C, 3 => D);
+impl_from_row_for_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E);
+impl_from_row_for_tuple!(0 => A, 1 => B, 2 => C, 3 => D, 4 => E, 5 => F);
+
+/// One pending write collected into a [`Proposal`] - either stores or
+/// removes a chunk/file record. Chunk puts carry only `hash`/`size` since
+/// `created_at` is stamped at commit time, matching [`Database::store_chunks`].
+#[derive(Debug, Clone)]
+pub enum WriteOp {
+    PutChunk { hash: String, size: u64 },
+    DeleteChunk(String),
+    PutFile(FileRecord),
+    DeleteFile(String),
+}
+
+/// A batch of [`WriteOp`]s layered over the committed state. Reads through
+/// [`Self::get_chunk`]/[`Self::get_file`] consult the batch's own pending
+/// writes first, falling back to the underlying [`Database`] on a miss, so
+/// callers can assemble a change, verify it against its own writes, and
+/// only then make it durable with [`Self::commit`] - rather than writing
+/// records one at a time and hoping nothing reads a half-applied state in
+/// between. Nothing touches the database until `commit` is called.
+pub struct Proposal<'db> {
+    db: &'db Database,
+    ops: Vec<WriteOp>,
+    chunks: HashMap<String, Option<u64>>,
+    files: HashMap<String, Option<FileRecord>>,
+}
+
+impl<'db> Proposal<'db> {
+    fn new(db: &'db Database, ops: Vec<WriteOp>) -> Self {
+        let mut chunks = HashMap::new();
+        let mut files = HashMap::new();
+
+        for op in &ops {
+            match op {
+                WriteOp::PutChunk { hash, size } => {
+                    chunks.insert(hash.clone(), Some(*size));
+                }
+                WriteOp::DeleteChunk(hash) => {
+                    chunks.insert(hash.clone(), None);
+                }
+                WriteOp::PutFile(record) => {
+                    files.insert(record.path.clone(), Some(record.clone()));
+                }
+                WriteOp::DeleteFile(path) => {
+                    files.insert(path.clone(), None);
+                }
+            }
+        }
+
+        Self { db, ops, chunks, files }
+    }
+
+    /// Look up a chunk as it would read after this proposal commits: the
+    /// overlay's own pending write if one touched `hash`, the database's
+    /// currently-committed record otherwise. `None` means deleted in the
+    /// overlay or absent from both.
+    pub fn get_chunk(&self, hash: &str) -> Result<Option<ChunkRecord>> {
+        match self.chunks.get(hash) {
+            Some(Some(size)) => Ok(Some(ChunkRecord {
+                hash: hash.to_string(),
+                size: *size,
+                created_at: current_timestamp(),
+            })),
+            Some(None) => Ok(None),
+            None => self.db.get_chunk(hash),
+        }
+    }
+
+    /// Look up a file the same way as [`Self::get_chunk`]: overlay first,
+    /// database on a miss.
+    pub fn get_file(&self, path: &str) -> Result<Option<FileRecord>> {
+        match self.files.get(path) {
+            Some(Some(record)) => Ok(Some(record.clone())),
+            Some(None) => Ok(None),
+            None => self.db.get_file(path),
+        }
+    }
+
+    /// Apply every pending write in one transaction - either all of it
+    /// lands, or none of it does.
+    pub fn commit(self) -> Result<()> {
+        self.db.with_retry(|| {
+            let mut conn = self.db.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin proposal transaction")?;
+
+            let timestamp = current_timestamp();
+
+            for op in &self.ops {
+                match op {
+                    WriteOp::PutChunk { hash, size } => {
+                        tx.execute(
+                            "INSERT OR IGNORE INTO chunks (hash, size, created_at) VALUES (?, ?, ?)",
+                            params![hash, *size as i64, timestamp as i64],
+                        )
+                        .context("Failed to insert chunk record")?;
+                    }
+                    WriteOp::DeleteChunk(hash) => {
+                        tx.execute("DELETE FROM chunks WHERE hash = ?", params![hash])
+                            .context("Failed to delete chunk record")?;
+                    }
+                    WriteOp::PutFile(record) => {
+                        let chunks_json = serde_json::to_string(&record.chunks)
+                            .context("Failed to serialize file chunks")?;
+                        let kind_json = serde_json::to_string(&record.kind)
+                            .context("Failed to serialize file kind")?;
+                        let xattrs_json = serde_json::to_string(&record.xattrs)
+                            .context("Failed to serialize file xattrs")?;
+
+                        tx.execute(
+                            "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                            params![
+                                record.path,
+                                chunks_json,
+                                record.size as i64,
+                                record.mtime as i64,
+                                record.permissions as i64,
+                                if record.is_executable { 1 } else { 0 },
+                                record.partial_hash,
+                                record.full_hash,
+                                kind_json,
+                                xattrs_json,
+                            ],
+                        )
+                        .context("Failed to store file record")?;
+
+                        Database::replace_file_chunks(&tx, &record.path, &record.chunks)?;
+                    }
+                    WriteOp::DeleteFile(path) => {
+                        tx.execute("DELETE FROM files WHERE path = ?", params![path])
+                            .context("Failed to delete file record")?;
+                        tx.execute("DELETE FROM file_chunks WHERE path = ?", params![path])
+                            .context("Failed to delete file_chunks rows")?;
+                    }
+                }
+            }
+
+            tx.commit().context("Failed to commit proposal transaction")?;
+            Ok(())
+        })
+    }
+}
+
+/// Read-only view of the repository as it existed at a specific commit's
+/// tree, for time-travel queries without materializing a full checkout.
+/// File lookups are pinned to the historical snapshot embedded in that
+/// commit's record; chunk content is immutable once stored, so chunk
+/// lookups just fall through to the live database.
+pub struct Revision<'db> {
+    db: &'db Database,
+    commit: CommitRecord,
+}
+
+impl<'db> Revision<'db> {
+    /// The commit this revision is pinned to
+    pub fn commit(&self) -> &CommitRecord {
+        &self.commit
+    }
+
+    /// File record as it existed at this revision's tree, or `None` if the
+    /// path didn't exist at that point in history
+    pub fn get_file(&self, path: &str) -> Option<&FileRecord> {
+        self.commit.files.get(path)
+    }
+
+    /// Chunk content is immutable once stored, so this is just an ordinary
+    /// (unpinned) lookup against the live database
+    pub fn get_chunk(&self, hash: &str) -> Result<Option<ChunkRecord>> {
+        self.db.get_chunk(hash)
+    }
+}
+
+/// One pending write collected into a [`WriteBatch`] - covers every table
+/// [`Database`] stores, unlike [`WriteOp`] (chunks/files only). There is no
+/// `DeleteCommit`: nothing in this crate ever deletes a commit once made, so
+/// a variant for it would have nowhere to be applied.
+#[derive(Debug, Clone)]
+pub enum BatchOp {
+    PutChunk { hash: String, size: u64 },
+    DeleteChunk(String),
+    PutFile(FileRecord),
+    DeleteFile(String),
+    PutCommit(CommitRecord),
+    PutRef { name: String, commit_hash: Option<String> },
+    DeleteRef(String),
+    PutChange(ChangeRecord),
+    DeleteChange(String),
+}
+
+/// An accumulating batch of [`BatchOp`]s applied atomically by
+/// [`Database::write`] - either every op in the batch lands, or none does, so
+/// a crash (or a concurrent reader) never observes a half-applied sequence of
+/// `store_chunk`/`store_file`/`store_ref` calls. Unlike [`Proposal`], a
+/// `WriteBatch` has no overlay for reading back its own pending writes - it
+/// exists purely to make a multi-table change crash-safe, not to stage and
+/// inspect one before committing.
+#[derive(Debug, Clone, Default)]
+pub struct WriteBatch {
+    ops: Vec<BatchOp>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put_chunk(&mut self, hash: impl Into<String>, size: u64) {
+        self.ops.push(BatchOp::PutChunk {
+            hash: hash.into(),
+            size,
+        });
+    }
+
+    pub fn delete_chunk(&mut self, hash: impl Into<String>) {
+        self.ops.push(BatchOp::DeleteChunk(hash.into()));
+    }
+
+    pub fn put_file(&mut self, record: FileRecord) {
+        self.ops.push(BatchOp::PutFile(record));
+    }
+
+    pub fn delete_file(&mut self, path: impl Into<String>) {
+        self.ops.push(BatchOp::DeleteFile(path.into()));
+    }
+
+    pub fn put_commit(&mut self, record: CommitRecord) {
+        self.ops.push(BatchOp::PutCommit(record));
+    }
+
+    pub fn put_ref(&mut self, name: impl Into<String>, commit_hash: Option<String>) {
+        self.ops.push(BatchOp::PutRef {
+            name: name.into(),
+            commit_hash,
+        });
+    }
+
+    pub fn delete_ref(&mut self, name: impl Into<String>) {
+        self.ops.push(BatchOp::DeleteRef(name.into()));
+    }
+
+    pub fn put_change(&mut self, record: ChangeRecord) {
+        self.ops.push(BatchOp::PutChange(record));
+    }
+
+    pub fn delete_change(&mut self, change_id: impl Into<String>) {
+        self.ops.push(BatchOp::DeleteChange(change_id.into()));
+    }
+
+    /// Whether any op has been accumulated yet - [`Database::write`] on an
+    /// empty batch is a harmless no-op, but callers can use this to skip
+    /// opening a transaction for one at all
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// A consistent read view pinned to the moment it was created: every
+/// `get_*` call issued through a `Snapshot` sees the database exactly as it
+/// stood then, regardless of writes [`Database::write`] or any other method
+/// commits afterward. Backed by a dedicated [`PooledConnection`] holding a
+/// `BEGIN DEFERRED` transaction open for the snapshot's lifetime - SQLite
+/// itself guarantees the isolation, so reads here are ordinary queries
+/// against that transaction rather than a bespoke copy-on-write overlay like
+/// [`Proposal`]'s. Cheap to create: no rows are copied, only a read
+/// transaction opened. Dropping a `Snapshot` ends its transaction.
+pub struct Snapshot {
+    conn: PooledConnection,
+}
+
+impl Snapshot {
+    /// Chunk record as of snapshot creation
+    pub fn get_chunk(&self, hash: &str) -> Result<Option<ChunkRecord>> {
+        self.conn
+            .query_row(
+                "SELECT hash, size, created_at FROM chunks WHERE hash = ?",
+                params![hash],
+                row_extract::<ChunkRecord>,
+            )
+            .optional()
+            .context("Failed to get chunk record from snapshot")
+    }
+
+    /// File record as of snapshot creation
+    pub fn get_file(&self, path: &str) -> Result<Option<FileRecord>> {
+        self.conn
+            .query_row(
+                "SELECT path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs FROM files WHERE path = ?",
+                params![path],
+                row_extract::<FileRecord>,
+            )
+            .optional()
+            .context("Failed to get file record from snapshot")
+    }
+
+    /// Commit record by hash (supports partial hashes), as of snapshot
+    /// creation
+    pub fn get_commit(&self, hash_prefix: &str) -> Result<Option<CommitRecord>> {
+        let search_pattern = format!("{}%", hash_prefix);
+
+        self.conn
+            .query_row(
+                "SELECT hash, parent, message, timestamp, tree_hash, files_json FROM commits WHERE hash LIKE ? ORDER BY timestamp DESC LIMIT 1",
+                params![search_pattern],
+                row_extract::<CommitRecord>,
+            )
+            .optional()
+            .context("Failed to get commit record from snapshot")
+    }
+
+    /// Commits as of snapshot creation, with the same `limit`/`since`
+    /// iteration [`Database::get_commits`] supports
+    pub fn get_commits(
+        &self,
+        limit: Option<usize>,
+        since: Option<&str>,
+    ) -> Result<Vec<CommitRecord>> {
+        let (query, params): (String, Vec<rusqlite::types::Value>) = if let Some(since_hash) = since
+        {
+            (
+                format!(
+                    "SELECT hash, parent, message, timestamp, tree_hash, files_json FROM commits
+                     WHERE timestamp >= (SELECT timestamp FROM commits WHERE hash LIKE ?)
+                     ORDER BY timestamp DESC {}",
+                    limit.map(|l| format!("LIMIT {}", l)).unwrap_or_default()
+                ),
+                vec![format!("{}%", since_hash).into()],
+            )
+        } else {
+            (
+                format!(
+                    "SELECT hash, parent, message, timestamp, tree_hash, files_json FROM commits
+                     ORDER BY timestamp DESC {}",
+                    limit.map(|l| format!("LIMIT {}", l)).unwrap_or_default()
+                ),
+                vec![],
+            )
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .context("Failed to prepare commit query")?;
+
+        let commits: Result<Vec<_>> = stmt
+            .query_map(rusqlite::params_from_iter(params), row_extract::<CommitRecord>)?
+            .map(|row| row.map_err(BlazeError::from))
+            .collect();
+
+        commits.context("Failed to collect commit records from snapshot")
+    }
+
+    /// Reference by name, as of snapshot creation
+    pub fn get_ref(&self, name: &str) -> Result<Option<RefRecord>> {
+        self.conn
+            .query_row(
+                "SELECT name, commit_hash FROM refs WHERE name = ?",
+                params![name],
+                row_extract::<RefRecord>,
+            )
+            .optional()
+            .context("Failed to get reference from snapshot")
+    }
+
+    /// Every reference as of snapshot creation
+    pub fn get_all_refs(&self) -> Result<HashMap<String, RefRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, commit_hash FROM refs")
+            .context("Failed to prepare refs query")?;
+
+        let refs: Result<HashMap<_, _>> = stmt
+            .query_map([], row_extract::<RefRecord>)?
+            .map(|result| {
+                let record = result?;
+                Ok((record.name.clone(), record))
+            })
+            .collect();
+
+        refs.context("Failed to collect reference records from snapshot")
+    }
+
+    /// Database statistics as of snapshot creation
+    pub fn get_stats(&self) -> Result<DatabaseStats> {
+        let chunk_count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))
+            .context("Failed to get chunk count from snapshot")?;
+
+        let file_count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .context("Failed to get file count from snapshot")?;
+
+        let commit_count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM commits", [], |row| row.get(0))
+            .context("Failed to get commit count from snapshot")?;
+
+        let ref_count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM refs", [], |row| row.get(0))
+            .context("Failed to get ref count from snapshot")?;
+
+        let total_chunk_size: Option<i64> = self
+            .conn
+            .query_row("SELECT SUM(size) FROM chunks", [], |row| row.get(0))
+            .context("Failed to get total chunk size from snapshot")?;
+
+        let total_file_size: Option<i64> = self
+            .conn
+            .query_row("SELECT SUM(size) FROM files", [], |row| row.get(0))
+            .context("Failed to get total file size from snapshot")?;
+
+        Ok(DatabaseStats {
+            chunk_count: chunk_count as usize,
+            file_count: file_count as usize,
+            commit_count: commit_count as usize,
+            ref_count: ref_count as usize,
+            total_chunk_size: total_chunk_size.unwrap_or(0) as u64,
+            total_file_size: total_file_size.unwrap_or(0) as u64,
+        })
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        // Best-effort: if the connection is already in a bad state there's
+        // nothing left to recover, and a read-only transaction failing to
+        // close cleanly isn't worth propagating from a `Drop`.
+        let _ = self.conn.execute_batch("COMMIT");
+    }
+}
+
 impl Database {
     /// Create a new database instance
     pub fn new<P: AsRef<Path>>(blaze_dir: P) -> Result<Self> {
-        let db_path = blaze_dir.as_ref().join(DB_FILE);
-        let config = DatabaseConfig::default();
-
-        Ok(Database { db_path, config })
+        Self::with_config(blaze_dir, DatabaseConfig::default())
     }
 
     /// Create a new database with custom configuration
     pub fn with_config<P: AsRef<Path>>(blaze_dir: P, config: DatabaseConfig) -> Result<Self> {
         let db_path = blaze_dir.as_ref().join(DB_FILE);
+        let pool = Arc::new(ConnectionPool::new(db_path.clone(), config));
 
-        Ok(Database { db_path, config })
+        Ok(Database {
+            db_path,
+            config,
+            pool,
+        })
+    }
+
+    /// The configuration this database was opened with
+    pub fn config(&self) -> DatabaseConfig {
+        self.config
     }
 
-    /// Initialize the database schema
+    /// Initialize the database schema, bringing it up to the latest
+    /// version via [`Database::migrate`]
     pub fn init(&self) -> Result<()> {
+        self.migrate()
+    }
+
+    /// Bring the schema up to [`SCHEMA_MIGRATIONS`]' latest version,
+    /// tracked via SQLite's `PRAGMA user_version`. Every pending step runs
+    /// in its own transaction that only bumps `user_version` once its SQL
+    /// has applied cleanly, so a crash mid-migration leaves the database at
+    /// the last fully-applied version rather than partially upgraded.
+    /// Refuses to open a database stamped with a version newer than this
+    /// build knows about, rather than risk misreading its schema.
+    pub fn migrate(&self) -> Result<()> {
+        // Connection-level PRAGMAs (foreign keys, WAL mode, cache size, ...)
+        // are applied once per physical connection by the pool itself, so
+        // there's no need to repeat them here.
+        let mut conn = self.open_connection()?;
+
+        let current_version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .context("Failed to read schema version")?;
+
+        let latest_version = SCHEMA_MIGRATIONS
+            .last()
+            .map(|migration| migration.version)
+            .unwrap_or(0);
+
+        if current_version > latest_version {
+            return Err(crate::config_error!(
+                "Database schema version {} is newer than this build of blaze supports (up to {}) - upgrade blaze before opening this repository",
+                current_version,
+                latest_version
+            ));
+        }
+
+        for migration in SCHEMA_MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > current_version)
+        {
+            let tx = conn
+                .transaction()
+                .context("Failed to begin schema migration transaction")?;
+
+            tx.execute_batch(migration.sql).with_context(|| {
+                format!("Failed to apply schema migration {}", migration.version)
+            })?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))
+                .with_context(|| {
+                    format!("Failed to record schema version {}", migration.version)
+                })?;
+
+            tx.commit().with_context(|| {
+                format!("Failed to commit schema migration {}", migration.version)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Stage `ops` as a [`Proposal`]: reads against it see the batch's own
+    /// pending writes layered over the currently-committed state, and
+    /// nothing is persisted until the caller calls [`Proposal::commit`].
+    pub fn propose(&self, ops: Vec<WriteOp>) -> Proposal<'_> {
+        Proposal::new(self, ops)
+    }
+
+    /// Look up the (most recent, in case of a tree-hash collision) commit
+    /// whose tree matches `tree_hash` and return a read-only [`Revision`]
+    /// pinned to it, for querying files/chunks as they existed at that
+    /// point in history rather than at the current HEAD.
+    pub fn revision(&self, tree_hash: &str) -> Result<Option<Revision<'_>>> {
         let conn = self.open_connection()?;
 
-        conn.execute_batch(&format!(
-            r#"
-            PRAGMA foreign_keys = {};
-            PRAGMA journal_mode = {};
-            PRAGMA cache_size = -{};
-            PRAGMA synchronous = NORMAL;
-            PRAGMA temp_store = MEMORY;
+        let commit: Option<CommitRecord> = conn
+            .query_row(
+                "SELECT hash, parent, message, timestamp, tree_hash, files_json FROM commits WHERE tree_hash = ? ORDER BY timestamp DESC LIMIT 1",
+                params![tree_hash],
+                row_extract::<CommitRecord>,
+            )
+            .optional()
+            .context("Failed to look up commit by tree hash")?;
 
-            CREATE TABLE IF NOT EXISTS chunks (
-                hash TEXT PRIMARY KEY,
-                size INTEGER NOT NULL,
-                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
-            );
+        Ok(commit.map(|commit| Revision { db: self, commit }))
+    }
 
-            CREATE TABLE IF NOT EXISTS files (
-                path TEXT PRIMARY KEY,
-                chunks TEXT NOT NULL,
-                size INTEGER NOT NULL,
-                mtime INTEGER NOT NULL,
-                permissions INTEGER NOT NULL,
-                is_executable INTEGER NOT NULL
-            );
+    /// Apply every op in `batch` in a single transaction - either all of it
+    /// lands, or (on any failure, or a crash partway through) none of it
+    /// does, unlike calling `store_chunk`/`store_file`/`store_ref` etc. one
+    /// at a time.
+    pub fn write(&self, batch: WriteBatch) -> Result<()> {
+        if batch.ops.is_empty() {
+            return Ok(());
+        }
+
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin write batch transaction")?;
+
+            let timestamp = current_timestamp();
+
+            for op in &batch.ops {
+                match op {
+                    BatchOp::PutChunk { hash, size } => {
+                        tx.execute(
+                            "INSERT OR IGNORE INTO chunks (hash, size, created_at) VALUES (?, ?, ?)",
+                            params![hash, *size as i64, timestamp as i64],
+                        )
+                        .context("Failed to insert chunk record")?;
+                    }
+                    BatchOp::DeleteChunk(hash) => {
+                        tx.execute("DELETE FROM chunks WHERE hash = ?", params![hash])
+                            .context("Failed to delete chunk record")?;
+                    }
+                    BatchOp::PutFile(record) => {
+                        let chunks_json = serde_json::to_string(&record.chunks)
+                            .context("Failed to serialize file chunks")?;
+                        let kind_json = serde_json::to_string(&record.kind)
+                            .context("Failed to serialize file kind")?;
+                        let xattrs_json = serde_json::to_string(&record.xattrs)
+                            .context("Failed to serialize file xattrs")?;
+
+                        tx.execute(
+                            "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                            params![
+                                record.path,
+                                chunks_json,
+                                record.size as i64,
+                                record.mtime as i64,
+                                record.permissions as i64,
+                                if record.is_executable { 1 } else { 0 },
+                                record.partial_hash,
+                                record.full_hash,
+                                kind_json,
+                                xattrs_json,
+                            ],
+                        )
+                        .context("Failed to store file record")?;
+
+                        Database::replace_file_chunks(&tx, &record.path, &record.chunks)?;
+                    }
+                    BatchOp::DeleteFile(path) => {
+                        tx.execute("DELETE FROM files WHERE path = ?", params![path])
+                            .context("Failed to delete file record")?;
+                        tx.execute("DELETE FROM file_chunks WHERE path = ?", params![path])
+                            .context("Failed to delete file_chunks rows")?;
+                    }
+                    BatchOp::PutCommit(record) => {
+                        let files_json = serde_json::to_string(&record.files)
+                            .context("Failed to serialize commit files")?;
+
+                        tx.execute(
+                            "INSERT INTO commits (hash, parent, message, timestamp, tree_hash, files_json) VALUES (?, ?, ?, ?, ?, ?)",
+                            params![
+                                record.hash,
+                                record.parent,
+                                record.message,
+                                record.timestamp as i64,
+                                record.tree_hash,
+                                files_json
+                            ],
+                        )
+                        .context("Failed to store commit record")?;
+                    }
+                    BatchOp::PutRef { name, commit_hash } => {
+                        tx.execute(
+                            "INSERT OR REPLACE INTO refs (name, commit_hash) VALUES (?, ?)",
+                            params![name, commit_hash],
+                        )
+                        .context("Failed to store reference")?;
+                    }
+                    BatchOp::DeleteRef(name) => {
+                        tx.execute("DELETE FROM refs WHERE name = ?", params![name])
+                            .context("Failed to delete reference")?;
+                    }
+                    BatchOp::PutChange(record) => {
+                        tx.execute(
+                            "INSERT OR REPLACE INTO changes (change_id, commit_hash, parent_change_id, parent_commit_hash) VALUES (?, ?, ?, ?)",
+                            params![
+                                record.change_id,
+                                record.commit_hash,
+                                record.parent_change_id,
+                                record.parent_commit_hash,
+                            ],
+                        )
+                        .context("Failed to store change record")?;
+                    }
+                    BatchOp::DeleteChange(change_id) => {
+                        tx.execute(
+                            "DELETE FROM changes WHERE change_id = ?",
+                            params![change_id],
+                        )
+                        .context("Failed to delete change record")?;
+                    }
+                }
+            }
 
-            CREATE TABLE IF NOT EXISTS commits (
-                hash TEXT PRIMARY KEY,
-                parent TEXT,
-                message TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                tree_hash TEXT NOT NULL,
-                files_json TEXT NOT NULL
-            );
+            tx.commit()
+                .context("Failed to commit write batch transaction")?;
+            Ok(())
+        })
+    }
 
-            CREATE TABLE IF NOT EXISTS refs (
-                name TEXT PRIMARY KEY,
-                commit_hash TEXT
-            );
+    /// Open a [`Snapshot`] pinned to the database as it stands right now -
+    /// cheap (no rows are copied, just a read transaction opened), so it's
+    /// safe to take one per backup/integrity scan even while writes proceed
+    /// concurrently.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        let conn = self.open_connection()?;
+        conn.execute_batch("BEGIN DEFERRED")
+            .context("Failed to begin snapshot transaction")?;
+        // A deferred transaction only takes its read snapshot at its first
+        // read, so read once now to pin it to this moment
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+            .context("Failed to begin snapshot transaction")?;
+        Ok(Snapshot { conn })
+    }
 
-            CREATE INDEX IF NOT EXISTS idx_chunks_created_at ON chunks(created_at);
-            CREATE INDEX IF NOT EXISTS idx_commits_timestamp ON commits(timestamp DESC);
-            CREATE INDEX IF NOT EXISTS idx_commits_parent ON commits(parent);
-            CREATE INDEX IF NOT EXISTS idx_files_mtime ON files(mtime);
-            "#,
-            if self.config.enable_foreign_keys {
-                "ON"
-            } else {
-                "OFF"
-            },
-            if self.config.enable_wal_mode {
-                "WAL"
-            } else {
-                "DELETE"
-            },
-            self.config.cache_size
-        ))?;
+    /// The schema version this database is currently stamped with
+    pub fn schema_version(&self) -> Result<i64> {
+        let conn = self.open_connection()?;
 
-        Ok(())
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .context("Failed to read schema version")
     }
 
     /// Store a chunk record
@@ -135,26 +1244,30 @@ impl Database {
 
     /// Store multiple chunks in a transaction
     pub fn store_chunks(&self, chunks: &[(String, u64)]) -> Result<()> {
-        let mut conn = self.open_connection()?;
-        let tx = conn
-            .transaction()
-            .context("Failed to begin chunk storage transaction")?;
-
-        let timestamp = current_timestamp();
-
-        {
-            let mut stmt = tx
-                .prepare("INSERT OR IGNORE INTO chunks (hash, size, created_at) VALUES (?, ?, ?)")
-                .context("Failed to prepare chunk insert statement")?;
-
-            for (hash, size) in chunks {
-                stmt.execute(params![hash, *size as i64, timestamp as i64])
-                    .context("Failed to insert chunk record")?;
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin chunk storage transaction")?;
+
+            let timestamp = current_timestamp();
+
+            {
+                let mut stmt = tx
+                    .prepare(
+                        "INSERT OR IGNORE INTO chunks (hash, size, created_at) VALUES (?, ?, ?)",
+                    )
+                    .context("Failed to prepare chunk insert statement")?;
+
+                for (hash, size) in chunks {
+                    stmt.execute(params![hash, *size as i64, timestamp as i64])
+                        .context("Failed to insert chunk record")?;
+                }
             }
-        }
 
-        tx.commit().context("Failed to commit chunk transaction")?;
-        Ok(())
+            tx.commit().context("Failed to commit chunk transaction")?;
+            Ok(())
+        })
     }
 
     /// Check if a chunk exists
@@ -173,6 +1286,13 @@ impl Database {
         Ok(exists.is_some())
     }
 
+    /// Alias for [`Self::chunk_exists`], named to match [`Self::has_file`]/
+    /// [`Self::has_commit`] for callers (e.g. chunk-ingestion dedup) that
+    /// want a uniform `has_*` existence-probe family across object types
+    pub fn has_chunk(&self, hash: &str) -> Result<bool> {
+        self.chunk_exists(hash)
+    }
+
     /// Get chunk record by hash
     pub fn get_chunk(&self, hash: &str) -> Result<Option<ChunkRecord>> {
         let conn = self.open_connection()?;
@@ -181,13 +1301,7 @@ impl Database {
             .query_row(
                 "SELECT hash, size, created_at FROM chunks WHERE hash = ?",
                 params![hash],
-                |row| {
-                    Ok(ChunkRecord {
-                        hash: row.get(0)?,
-                        size: row.get::<_, i64>(1)? as u64,
-                        created_at: row.get::<_, i64>(2)? as u64,
-                    })
-                },
+                row_extract::<ChunkRecord>,
             )
             .optional()
             .context("Failed to get chunk record")?;
@@ -211,86 +1325,321 @@ impl Database {
         hashes.context("Failed to collect chunk hashes")
     }
 
-    /// Delete chunks by hash
+    /// Get every chunk record, for callers that need sizes alongside hashes
+    /// rather than just `get_all_chunk_hashes`' bare list
+    pub fn get_all_chunks(&self) -> Result<Vec<ChunkRecord>> {
+        let conn = self.open_connection()?;
+
+        let mut stmt = conn
+            .prepare("SELECT hash, size, created_at FROM chunks")
+            .context("Failed to prepare chunk query")?;
+
+        let records: Result<Vec<_>> = stmt
+            .query_map([], row_extract::<ChunkRecord>)?
+            .map(|row| row.map_err(BlazeError::from))
+            .collect();
+
+        records.context("Failed to collect chunk records")
+    }
+
+    /// Delete chunks by hash, refusing to delete any hash still referenced by
+    /// a live file record or a commit reachable from some ref - use
+    /// [`Database::garbage_collect`] to find reclaimable hashes in the first
+    /// place rather than guessing which ones are safe to pass here
     pub fn delete_chunks(&self, hashes: &[String]) -> Result<usize> {
-        let mut conn = self.open_connection()?;
-        let tx = conn
-            .transaction()
-            .context("Failed to begin chunk deletion transaction")?;
+        let (live, _) = self.live_chunk_hashes()?;
+        let reclaimable: Vec<String> = hashes
+            .iter()
+            .filter(|hash| !live.contains(*hash))
+            .cloned()
+            .collect();
 
-        let mut deleted_count = 0;
+        self.delete_chunk_rows(&reclaimable)
+    }
 
-        {
-            let mut stmt = tx
-                .prepare("DELETE FROM chunks WHERE hash = ?")
-                .context("Failed to prepare chunk deletion statement")?;
-
-            for hash in hashes {
-                let changes = stmt
-                    .execute(params![hash])
-                    .context("Failed to delete chunk")?;
-                deleted_count += changes;
+    /// Delete chunk bookkeeping rows unconditionally, bypassing the
+    /// still-referenced check [`Database::delete_chunks`] applies. Only
+    /// meant for repairing known corruption (e.g. `blaze verify --fix`
+    /// dropping a `chunks` row whose content is already gone from storage),
+    /// where forcing the delete is the point - everyday callers should use
+    /// `delete_chunks` or [`Database::garbage_collect`] instead.
+    pub(crate) fn delete_chunk_rows(&self, hashes: &[String]) -> Result<usize> {
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin chunk deletion transaction")?;
+
+            let mut deleted_count = 0;
+
+            {
+                let mut stmt = tx
+                    .prepare("DELETE FROM chunks WHERE hash = ?")
+                    .context("Failed to prepare chunk deletion statement")?;
+
+                for hash in hashes {
+                    let changes = stmt
+                        .execute(params![hash])
+                        .context("Failed to delete chunk")?;
+                    deleted_count += changes;
+                }
             }
-        }
 
-        tx.commit()
-            .context("Failed to commit chunk deletion transaction")?;
+            tx.commit()
+                .context("Failed to commit chunk deletion transaction")?;
 
-        Ok(deleted_count)
+            Ok(deleted_count)
+        })
     }
 
-    /// Store or update a file record
-    pub fn store_file(&self, record: &FileRecord) -> Result<()> {
+    /// Delete every chunk in the `chunks` table unreferenced by any live file
+    /// record or by a commit reachable from some ref, following Obnam's
+    /// generation model where a GC walks forward from live roots rather than
+    /// trusting a reference count that could drift out of sync
+    pub fn garbage_collect(&self) -> Result<ChunkGcResult> {
+        let (live, _) = self.live_chunk_hashes()?;
+
+        let dead: Vec<ChunkRecord> = self
+            .get_all_chunks()?
+            .into_iter()
+            .filter(|chunk| !live.contains(&chunk.hash))
+            .collect();
+
+        let reclaimed_bytes = dead.iter().map(|chunk| chunk.size).sum();
+        let reclaimed_hashes: Vec<String> = dead.into_iter().map(|chunk| chunk.hash).collect();
+
+        self.delete_chunk_rows(&reclaimed_hashes)?;
+
+        Ok(ChunkGcResult {
+            reclaimed_hashes,
+            reclaimed_bytes,
+        })
+    }
+
+    /// Pin `hash` so [`Database::gc`] never collects it, even if nothing
+    /// else references it yet - for objects written ahead of the commit
+    /// that will root them, e.g. a [`Proposal`] assembling a batch or a
+    /// commit a caller wants to keep around outside of any ref.
+    pub fn add_gc_keep(&self, hash: &str) -> Result<()> {
         let conn = self.open_connection()?;
 
-        let chunks_json =
-            serde_json::to_string(&record.chunks).context("Failed to serialize file chunks")?;
+        conn.execute("INSERT OR IGNORE INTO gc_keep (hash) VALUES (?)", params![hash])
+            .context("Failed to add gc keep entry")?;
 
-        conn.execute(
-            "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable) VALUES (?, ?, ?, ?, ?, ?)",
-            params![
-                record.path,
-                chunks_json,
-                record.size as i64,
-                record.mtime as i64,
-                record.permissions as i64,
-                if record.is_executable { 1 } else { 0 }
-            ],
-        ).context("Failed to store file record")?;
+        Ok(())
+    }
+
+    /// Unpin `hash`, making it collectible again by [`Database::gc`] once
+    /// nothing else references it - the counterpart to
+    /// [`Database::add_gc_keep`].
+    pub fn remove_gc_keep(&self, hash: &str) -> Result<()> {
+        let conn = self.open_connection()?;
+
+        conn.execute("DELETE FROM gc_keep WHERE hash = ?", params![hash])
+            .context("Failed to remove gc keep entry")?;
 
         Ok(())
     }
 
-    /// Store multiple file records in a transaction
-    pub fn store_files(&self, records: &[FileRecord]) -> Result<()> {
-        let mut conn = self.open_connection()?;
-        let tx = conn
-            .transaction()
-            .context("Failed to begin file storage transaction")?;
+    /// Mark-and-sweep garbage collection of the `chunks` table: mark every
+    /// chunk reachable from a live root (same walk as
+    /// [`Database::garbage_collect`]) or pinned via [`Database::add_gc_keep`],
+    /// then sweep every unmarked chunk in one transaction. Pass
+    /// `dry_run: true` to compute the report without deleting anything, to
+    /// preview a sweep before committing to it.
+    pub fn gc(&self, dry_run: bool) -> Result<GcReport> {
+        let (mut reachable, commits_scanned) = self.live_chunk_hashes()?;
 
         {
-            let mut stmt = tx.prepare(
-                "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable) VALUES (?, ?, ?, ?, ?, ?)"
-            ).context("Failed to prepare file insert statement")?;
+            let conn = self.open_connection()?;
+            let mut stmt = conn
+                .prepare("SELECT hash FROM gc_keep")
+                .context("Failed to prepare gc_keep query")?;
+            for hash in stmt.query_map([], |row| row.get::<_, String>(0))? {
+                reachable.insert(hash.context("Failed to read gc_keep row")?);
+            }
+        }
 
-            for record in records {
-                let chunks_json = serde_json::to_string(&record.chunks)
-                    .context("Failed to serialize file chunks")?;
+        let dead: Vec<ChunkRecord> = self
+            .get_all_chunks()?
+            .into_iter()
+            .filter(|chunk| !reachable.contains(&chunk.hash))
+            .collect();
+
+        let bytes_freed = dead.iter().map(|chunk| chunk.size).sum();
+        let dead_hashes: Vec<String> = dead.into_iter().map(|chunk| chunk.hash).collect();
+
+        if !dry_run {
+            self.delete_chunk_rows(&dead_hashes)?;
+        }
+
+        Ok(GcReport {
+            chunks_deleted: dead_hashes.len(),
+            bytes_freed,
+            commits_scanned,
+        })
+    }
+
+    /// Every chunk hash still reachable from a live root: either a current
+    /// `file_chunks` row, or a file within a commit reachable by following
+    /// `parent` links from some ref. A commit that no ref (directly or
+    /// transitively) points at contributes nothing here, so its chunks
+    /// become collectible once it falls out of reach. Also reports how many
+    /// commits the walk actually resolved and visited, for [`Database::gc`]'s
+    /// `commits_scanned` figure.
+    fn live_chunk_hashes(&self) -> Result<(HashSet<String>, usize)> {
+        let conn = self.open_connection()?;
+
+        let mut live: HashSet<String> = HashSet::new();
+        {
+            let mut stmt = conn
+                .prepare("SELECT DISTINCT chunk_hash FROM file_chunks")
+                .context("Failed to prepare file_chunks query")?;
+            for hash in stmt.query_map([], |row| row.get::<_, String>(0))? {
+                live.insert(hash.context("Failed to read file_chunks row")?);
+            }
+        }
+
+        let refs = self.get_all_refs()?;
+        let commits = self.get_commits(None, None)?;
+        let commits_by_hash: HashMap<&str, &CommitRecord> =
+            commits.iter().map(|commit| (commit.hash.as_str(), commit)).collect();
+
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut commits_scanned = 0;
+        let mut pending: Vec<&str> = refs
+            .values()
+            .filter_map(|r| r.commit_hash.as_deref())
+            .collect();
+
+        while let Some(hash) = pending.pop() {
+            if !visited.insert(hash) {
+                continue;
+            }
+
+            let Some(commit) = commits_by_hash.get(hash) else {
+                continue;
+            };
+
+            commits_scanned += 1;
+
+            for file in commit.files.values() {
+                live.extend(file.chunks.iter().cloned());
+            }
+
+            if let Some(parent) = commit.parent.as_deref() {
+                pending.push(parent);
+            }
+        }
+
+        Ok((live, commits_scanned))
+    }
+
+    /// Replace `path`'s rows in the `file_chunks` join table with one row per
+    /// entry in `chunks`, in order - called wherever a `files.chunks` JSON
+    /// array is written, so the normalized table never drifts out of sync
+    fn replace_file_chunks(
+        conn: &Connection,
+        path: &str,
+        chunks: &[String],
+    ) -> Result<()> {
+        conn.execute("DELETE FROM file_chunks WHERE path = ?", params![path])
+            .context("Failed to clear old file_chunks rows")?;
+
+        let mut stmt = conn
+            .prepare("INSERT INTO file_chunks (path, chunk_hash, ordinal) VALUES (?, ?, ?)")
+            .context("Failed to prepare file_chunks insert statement")?;
+
+        for (ordinal, chunk_hash) in chunks.iter().enumerate() {
+            stmt.execute(params![path, chunk_hash, ordinal as i64])
+                .context("Failed to insert file_chunks row")?;
+        }
 
-                stmt.execute(params![
+        Ok(())
+    }
+
+    /// Store or update a file record
+    pub fn store_file(&self, record: &FileRecord) -> Result<()> {
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin file storage transaction")?;
+
+            let chunks_json = serde_json::to_string(&record.chunks)
+                .context("Failed to serialize file chunks")?;
+            let kind_json =
+                serde_json::to_string(&record.kind).context("Failed to serialize file kind")?;
+            let xattrs_json = serde_json::to_string(&record.xattrs)
+                .context("Failed to serialize file xattrs")?;
+
+            tx.execute(
+                "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
                     record.path,
                     chunks_json,
                     record.size as i64,
                     record.mtime as i64,
                     record.permissions as i64,
-                    if record.is_executable { 1 } else { 0 }
-                ])
-                .context("Failed to insert file record")?;
+                    if record.is_executable { 1 } else { 0 },
+                    record.partial_hash,
+                    record.full_hash,
+                    kind_json,
+                    xattrs_json,
+                ],
+            ).context("Failed to store file record")?;
+
+            Self::replace_file_chunks(&tx, &record.path, &record.chunks)?;
+
+            tx.commit().context("Failed to commit file storage transaction")?;
+            Ok(())
+        })
+    }
+
+    /// Store multiple file records in a transaction
+    pub fn store_files(&self, records: &[FileRecord]) -> Result<()> {
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin file storage transaction")?;
+
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR REPLACE INTO files (path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                ).context("Failed to prepare file insert statement")?;
+
+                for record in records {
+                    let chunks_json = serde_json::to_string(&record.chunks)
+                        .context("Failed to serialize file chunks")?;
+                    let kind_json = serde_json::to_string(&record.kind)
+                        .context("Failed to serialize file kind")?;
+                    let xattrs_json = serde_json::to_string(&record.xattrs)
+                        .context("Failed to serialize file xattrs")?;
+
+                    stmt.execute(params![
+                        record.path,
+                        chunks_json,
+                        record.size as i64,
+                        record.mtime as i64,
+                        record.permissions as i64,
+                        if record.is_executable { 1 } else { 0 },
+                        record.partial_hash,
+                        record.full_hash,
+                        kind_json,
+                        xattrs_json,
+                    ])
+                    .context("Failed to insert file record")?;
+                }
             }
-        }
 
-        tx.commit().context("Failed to commit file transaction")?;
-        Ok(())
+            for record in records {
+                Self::replace_file_chunks(&tx, &record.path, &record.chunks)?;
+            }
+
+            tx.commit().context("Failed to commit file transaction")?;
+            Ok(())
+        })
     }
 
     /// Get a file record by path
@@ -299,9 +1648,9 @@ impl Database {
 
         let record = conn
             .query_row(
-                "SELECT path, chunks, size, mtime, permissions, is_executable FROM files WHERE path = ?",
+                "SELECT path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs FROM files WHERE path = ?",
                 params![path],
-                parse_file_record,
+                row_extract::<FileRecord>,
             )
             .optional()
             .context("Failed to get file record")?;
@@ -309,16 +1658,34 @@ impl Database {
         Ok(record)
     }
 
+    /// Whether a file record exists for `path`, without reading or
+    /// deserializing the row - an index-only existence probe, like
+    /// [`Self::has_chunk`]/[`Self::has_commit`]
+    pub fn has_file(&self, path: &str) -> Result<bool> {
+        let conn = self.open_connection()?;
+
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM files WHERE path = ? LIMIT 1",
+                params![path],
+                |_| Ok(()),
+            )
+            .optional()
+            .context("Failed to check file existence")?;
+
+        Ok(exists.is_some())
+    }
+
     /// Get all file records
     pub fn get_all_files(&self) -> Result<HashMap<String, FileRecord>> {
         let conn = self.open_connection()?;
 
         let mut stmt = conn
-            .prepare("SELECT path, chunks, size, mtime, permissions, is_executable FROM files")
+            .prepare("SELECT path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs FROM files")
             .context("Failed to prepare file query")?;
 
         let files: Result<HashMap<_, _>> = stmt
-            .query_map([], parse_file_record)?
+            .query_map([], row_extract::<FileRecord>)?
             .map(|result| {
                 let record = result?;
                 Ok((record.path.clone(), record))
@@ -330,24 +1697,44 @@ impl Database {
 
     /// Delete a file record
     pub fn delete_file(&self, path: &str) -> Result<bool> {
-        let conn = self.open_connection()?;
-
-        let changes = conn
-            .execute("DELETE FROM files WHERE path = ?", params![path])
-            .context("Failed to delete file record")?;
-
-        Ok(changes > 0)
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin file deletion transaction")?;
+
+            let changes = tx
+                .execute("DELETE FROM files WHERE path = ?", params![path])
+                .context("Failed to delete file record")?;
+            tx.execute("DELETE FROM file_chunks WHERE path = ?", params![path])
+                .context("Failed to delete file_chunks rows")?;
+
+            tx.commit()
+                .context("Failed to commit file deletion transaction")?;
+
+            Ok(changes > 0)
+        })
     }
 
     /// Clear all file records
     pub fn clear_files(&self) -> Result<usize> {
-        let conn = self.open_connection()?;
-
-        let changes = conn
-            .execute("DELETE FROM files", [])
-            .context("Failed to clear file records")?;
-
-        Ok(changes)
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin file clearing transaction")?;
+
+            let changes = tx
+                .execute("DELETE FROM files", [])
+                .context("Failed to clear file records")?;
+            tx.execute("DELETE FROM file_chunks", [])
+                .context("Failed to clear file_chunks rows")?;
+
+            tx.commit()
+                .context("Failed to commit file clearing transaction")?;
+
+            Ok(changes)
+        })
     }
 
     /// Store a commit record
@@ -382,7 +1769,7 @@ impl Database {
             .query_row(
                 "SELECT hash, parent, message, timestamp, tree_hash, files_json FROM commits WHERE hash LIKE ? ORDER BY timestamp DESC LIMIT 1",
                 params![search_pattern],
-                parse_commit_record,
+                row_extract::<CommitRecord>,
             )
             .optional()
             .context("Failed to get commit record")?;
@@ -390,6 +1777,26 @@ impl Database {
         Ok(record)
     }
 
+    /// Whether a commit exists matching `hash_prefix` (supports partial
+    /// hashes, same as [`Self::get_commit`]), without reading or
+    /// deserializing the matching row
+    pub fn has_commit(&self, hash_prefix: &str) -> Result<bool> {
+        let conn = self.open_connection()?;
+
+        let search_pattern = format!("{}%", hash_prefix);
+
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM commits WHERE hash LIKE ? LIMIT 1",
+                params![search_pattern],
+                |_| Ok(()),
+            )
+            .optional()
+            .context("Failed to check commit existence")?;
+
+        Ok(exists.is_some())
+    }
+
     /// Get commits with optional limit and parent filtering
     pub fn get_commits(
         &self,
@@ -425,13 +1832,30 @@ impl Database {
             .context("Failed to prepare commit query")?;
 
         let commits: Result<Vec<_>> = stmt
-            .query_map(rusqlite::params_from_iter(params), parse_commit_record)?
+            .query_map(rusqlite::params_from_iter(params), row_extract::<CommitRecord>)?
             .map(|row| row.map_err(BlazeError::from))
             .collect();
 
         commits.context("Failed to collect commit records")
     }
 
+    /// Get every commit hash, sorted ascending so callers can binary-search
+    /// them for abbreviated-prefix resolution instead of scanning linearly
+    pub fn get_all_commit_hashes(&self) -> Result<Vec<String>> {
+        let conn = self.open_connection()?;
+
+        let mut stmt = conn
+            .prepare("SELECT hash FROM commits ORDER BY hash ASC")
+            .context("Failed to prepare commit hash query")?;
+
+        let hashes: Result<Vec<String>> = stmt
+            .query_map([], |row| row.get(0))?
+            .map(|row| row.map_err(BlazeError::from))
+            .collect();
+
+        hashes.context("Failed to collect commit hashes")
+    }
+
     /// Get commit count
     pub fn get_commit_count(&self) -> Result<usize> {
         let conn = self.open_connection()?;
@@ -464,12 +1888,7 @@ impl Database {
             .query_row(
                 "SELECT name, commit_hash FROM refs WHERE name = ?",
                 params![name],
-                |row| {
-                    Ok(RefRecord {
-                        name: row.get(0)?,
-                        commit_hash: row.get(1)?,
-                    })
-                },
+                row_extract::<RefRecord>,
             )
             .optional()
             .context("Failed to get reference")?;
@@ -486,12 +1905,7 @@ impl Database {
             .context("Failed to prepare refs query")?;
 
         let refs: Result<HashMap<_, _>> = stmt
-            .query_map([], |row| {
-                Ok(RefRecord {
-                    name: row.get(0)?,
-                    commit_hash: row.get(1)?,
-                })
-            })?
+            .query_map([], row_extract::<RefRecord>)?
             .map(|result| {
                 let record = result?;
                 Ok((record.name.clone(), record))
@@ -512,6 +1926,67 @@ impl Database {
         Ok(changes > 0)
     }
 
+    /// Store or update a change record (same `change_id` overwrites in place,
+    /// which is how an amend/rebase updates a change's `commit_hash` while
+    /// keeping its identity stable)
+    pub fn store_change(&self, record: &ChangeRecord) -> Result<()> {
+        let conn = self.open_connection()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO changes (change_id, commit_hash, parent_change_id, parent_commit_hash) VALUES (?, ?, ?, ?)",
+            params![
+                record.change_id,
+                record.commit_hash,
+                record.parent_change_id,
+                record.parent_commit_hash,
+            ],
+        ).context("Failed to store change record")?;
+
+        Ok(())
+    }
+
+    /// Get a change record by its stable change ID
+    pub fn get_change(&self, change_id: &str) -> Result<Option<ChangeRecord>> {
+        let conn = self.open_connection()?;
+
+        conn.query_row(
+            "SELECT change_id, commit_hash, parent_change_id, parent_commit_hash FROM changes WHERE change_id = ?",
+            params![change_id],
+            row_extract::<ChangeRecord>,
+        )
+        .optional()
+        .context("Failed to get change record")
+    }
+
+    /// Get the change record currently pointing at a given commit hash
+    pub fn get_change_by_commit(&self, commit_hash: &str) -> Result<Option<ChangeRecord>> {
+        let conn = self.open_connection()?;
+
+        conn.query_row(
+            "SELECT change_id, commit_hash, parent_change_id, parent_commit_hash FROM changes WHERE commit_hash = ?",
+            params![commit_hash],
+            row_extract::<ChangeRecord>,
+        )
+        .optional()
+        .context("Failed to get change record by commit hash")
+    }
+
+    /// Get every change record, e.g. to scan for orphans
+    pub fn get_all_changes(&self) -> Result<Vec<ChangeRecord>> {
+        let conn = self.open_connection()?;
+
+        let mut stmt = conn
+            .prepare("SELECT change_id, commit_hash, parent_change_id, parent_commit_hash FROM changes")
+            .context("Failed to prepare changes query")?;
+
+        let changes: Result<Vec<_>> = stmt
+            .query_map([], row_extract::<ChangeRecord>)?
+            .map(|row| row.map_err(BlazeError::from))
+            .collect();
+
+        changes.context("Failed to collect change records")
+    }
+
     /// Get database statistics
     pub fn get_stats(&self) -> Result<DatabaseStats> {
         let conn = self.open_connection()?;
@@ -550,6 +2025,40 @@ impl Database {
         })
     }
 
+
+    /// Walk every stored commit's full tree snapshot and tally, per chunk
+    /// hash, how many file references and how many distinct commits point at
+    /// it - the basis for reporting deduplication effectiveness (references
+    /// per unique chunk) and how much content survives unchanged across
+    /// history (chunks shared across more than one commit)
+    pub fn chunk_sharing_distribution(&self) -> Result<ChunkSharingDistribution> {
+        let commits = self.get_commits(None, None)?;
+
+        let mut commits_per_chunk: HashMap<String, (usize, HashSet<String>)> = HashMap::new();
+        for commit in &commits {
+            for file in commit.files.values() {
+                for chunk_hash in &file.chunks {
+                    let entry = commits_per_chunk.entry(chunk_hash.clone()).or_default();
+                    entry.0 += 1;
+                    entry.1.insert(commit.hash.clone());
+                }
+            }
+        }
+
+        let unique_chunks_referenced = commits_per_chunk.len();
+        let total_chunk_references: usize = commits_per_chunk.values().map(|(refs, _)| refs).sum();
+        let chunks_shared_across_multiple_commits = commits_per_chunk
+            .values()
+            .filter(|(_, commit_hashes)| commit_hashes.len() > 1)
+            .count();
+
+        Ok(ChunkSharingDistribution {
+            unique_chunks_referenced,
+            total_chunk_references,
+            chunks_shared_across_multiple_commits,
+        })
+    }
+
     /// Vacuum the database to reclaim space
     pub fn vacuum(&self) -> Result<()> {
         let conn = self.open_connection()?;
@@ -573,23 +2082,266 @@ impl Database {
 
         let issues = issues.context("Failed to collect integrity check results")?;
 
-        // Filter out "ok" results
-        Ok(issues
-            .into_iter()
-            .filter(|issue: &String| issue.to_lowercase() != "ok")
-            .collect())
+        // Filter out "ok" results
+        Ok(issues
+            .into_iter()
+            .filter(|issue: &String| issue.to_lowercase() != "ok")
+            .collect())
+    }
+
+    /// Run an arbitrary read-only query and collect its rows into `T`,
+    /// for ad-hoc reporting/stats callers that would otherwise need to
+    /// write a new `query_map` closure for every query shape - see
+    /// [`FromRow`]. `T` can be one of the record types above, or a bare
+    /// tuple of up to six columns for a quick one-off projection.
+    pub fn query_rows<T: FromRow>(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<T>> {
+        let conn = self.open_connection()?;
+
+        let mut stmt = conn.prepare(sql).context("Failed to prepare ad-hoc query")?;
+
+        let rows: Result<Vec<T>> = stmt
+            .query_map(params, row_extract::<T>)?
+            .map(|row| row.map_err(BlazeError::from))
+            .collect();
+
+        rows.context("Failed to collect query rows")
+    }
+
+    /// Serialize every table into a tar.gz archive: a `metadata.json`
+    /// header (see [`DumpMetadata`]) followed by one JSON entry per table,
+    /// so a repo can be backed up or moved to another machine without
+    /// depending on SQLite's own file format staying compatible across
+    /// versions. Pair with [`Database::restore`].
+    pub fn dump<W: Write>(&self, writer: W) -> Result<()> {
+        let stats = self.get_stats()?;
+        let metadata = DumpMetadata {
+            db_version: self.schema_version()?,
+            created_at: current_timestamp(),
+            chunk_count: stats.chunk_count,
+            file_count: stats.file_count,
+            total_size: stats.total_chunk_size,
+        };
+
+        let chunks = self.get_all_chunks()?;
+        let files: Vec<FileRecord> = self.get_all_files()?.into_values().collect();
+        let commits = self.get_commits(None, None)?;
+        let refs: Vec<RefRecord> = self.get_all_refs()?.into_values().collect();
+        let changes = self.get_all_changes()?;
+
+        let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        append_json_entry(&mut builder, "metadata.json", &metadata)?;
+        append_json_entry(&mut builder, "chunks.json", &chunks)?;
+        append_json_entry(&mut builder, "files.json", &files)?;
+        append_json_entry(&mut builder, "commits.json", &commits)?;
+        append_json_entry(&mut builder, "refs.json", &refs)?;
+        append_json_entry(&mut builder, "changes.json", &changes)?;
+
+        builder
+            .into_inner()
+            .context("Failed to finish dump tar stream")?
+            .finish()
+            .context("Failed to finish dump gzip stream")?;
+
+        Ok(())
+    }
+
+    /// Read a [`Database::dump`] archive and rebuild every table from it in
+    /// one transaction. Reads `metadata.json` first to find the dump's
+    /// schema version, then resolves it through [`load_tables`] so a dump
+    /// made by an older Blaze release is upgraded in memory by chaining
+    /// [`Loader`]s rather than being rejected.
+    pub fn restore<R: Read>(&self, reader: R) -> Result<()> {
+        let decoder = flate2::read::GzDecoder::new(reader);
+        let mut archive = tar::Archive::new(decoder);
+
+        let mut entries: HashMap<String, Vec<u8>> = HashMap::new();
+        for entry in archive.entries().context("Failed to read dump archive")? {
+            let mut entry = entry.context("Failed to read dump archive entry")?;
+            let path = entry
+                .path()
+                .context("Failed to read dump entry path")?
+                .to_string_lossy()
+                .into_owned();
+            let mut bytes = Vec::new();
+            entry
+                .read_to_end(&mut bytes)
+                .context("Failed to read dump entry contents")?;
+            entries.insert(path, bytes);
+        }
+
+        let metadata_bytes = entries.get("metadata.json").ok_or_else(|| {
+            BlazeError::Serialization("Dump archive is missing metadata.json".to_string())
+        })?;
+        let metadata: DumpMetadata =
+            serde_json::from_slice(metadata_bytes).context("Failed to parse dump metadata")?;
+
+        let tables = load_tables(metadata.db_version, &entries)?;
+
+        self.with_retry(|| {
+            let mut conn = self.open_connection()?;
+            let tx = conn
+                .transaction()
+                .context("Failed to begin restore transaction")?;
+
+            tx.execute("DELETE FROM file_chunks", [])
+                .context("Failed to clear file_chunks table")?;
+            tx.execute("DELETE FROM files", [])
+                .context("Failed to clear files table")?;
+            tx.execute("DELETE FROM chunks", [])
+                .context("Failed to clear chunks table")?;
+            tx.execute("DELETE FROM commits", [])
+                .context("Failed to clear commits table")?;
+            tx.execute("DELETE FROM refs", [])
+                .context("Failed to clear refs table")?;
+            tx.execute("DELETE FROM changes", [])
+                .context("Failed to clear changes table")?;
+
+            {
+                let mut stmt = tx
+                    .prepare("INSERT INTO chunks (hash, size, created_at) VALUES (?, ?, ?)")
+                    .context("Failed to prepare chunk insert statement")?;
+                for chunk in &tables.chunks {
+                    stmt.execute(params![chunk.hash, chunk.size as i64, chunk.created_at as i64])
+                        .context("Failed to restore chunk record")?;
+                }
+            }
+
+            {
+                let mut stmt = tx
+                    .prepare(
+                        "INSERT INTO files (path, chunks, size, mtime, permissions, is_executable, partial_hash, full_hash, kind, xattrs) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    )
+                    .context("Failed to prepare file insert statement")?;
+                for record in &tables.files {
+                    let chunks_json = serde_json::to_string(&record.chunks)
+                        .context("Failed to serialize file chunks")?;
+                    let kind_json = serde_json::to_string(&record.kind)
+                        .context("Failed to serialize file kind")?;
+                    let xattrs_json = serde_json::to_string(&record.xattrs)
+                        .context("Failed to serialize file xattrs")?;
+
+                    stmt.execute(params![
+                        record.path,
+                        chunks_json,
+                        record.size as i64,
+                        record.mtime as i64,
+                        record.permissions as i64,
+                        if record.is_executable { 1 } else { 0 },
+                        record.partial_hash,
+                        record.full_hash,
+                        kind_json,
+                        xattrs_json,
+                    ])
+                    .context("Failed to restore file record")?;
+                }
+            }
+
+            for record in &tables.files {
+                Self::replace_file_chunks(&tx, &record.path, &record.chunks)?;
+            }
+
+            {
+                let mut stmt = tx
+                    .prepare(
+                        "INSERT INTO commits (hash, parent, message, timestamp, tree_hash, files_json) VALUES (?, ?, ?, ?, ?, ?)"
+                    )
+                    .context("Failed to prepare commit insert statement")?;
+                for commit in &tables.commits {
+                    let files_json = serde_json::to_string(&commit.files)
+                        .context("Failed to serialize commit files")?;
+                    stmt.execute(params![
+                        commit.hash,
+                        commit.parent,
+                        commit.message,
+                        commit.timestamp as i64,
+                        commit.tree_hash,
+                        files_json,
+                    ])
+                    .context("Failed to restore commit record")?;
+                }
+            }
+
+            {
+                let mut stmt = tx
+                    .prepare("INSERT OR REPLACE INTO refs (name, commit_hash) VALUES (?, ?)")
+                    .context("Failed to prepare ref insert statement")?;
+                for r in &tables.refs {
+                    stmt.execute(params![r.name, r.commit_hash])
+                        .context("Failed to restore reference")?;
+                }
+            }
+
+            {
+                let mut stmt = tx
+                    .prepare(
+                        "INSERT OR REPLACE INTO changes (change_id, commit_hash, parent_change_id, parent_commit_hash) VALUES (?, ?, ?, ?)"
+                    )
+                    .context("Failed to prepare change insert statement")?;
+                for change in &tables.changes {
+                    stmt.execute(params![
+                        change.change_id,
+                        change.commit_hash,
+                        change.parent_change_id,
+                        change.parent_commit_hash,
+                    ])
+                    .context("Failed to restore change record")?;
+                }
+            }
+
+            tx.commit().context("Failed to commit restore transaction")?;
+            Ok(())
+        })
     }
 
     // Private helper methods
 
-    fn open_connection(&self) -> Result<Connection> {
-        let conn = Connection::open(&self.db_path)
-            .with_context(|| format!("Failed to open database: {}", self.db_path.display()))?;
+    /// Check out a pooled, already-configured connection rather than
+    /// opening (and reconfiguring) a new one - see [`ConnectionPool`]
+    fn open_connection(&self) -> Result<PooledConnection> {
+        self.pool.acquire()
+    }
 
-        conn.busy_timeout(std::time::Duration::from_secs(self.config.timeout as u64))
-            .context("Failed to set database timeout")?;
+    /// Run `op`, retrying with exponential backoff while it fails with a
+    /// transient `SQLITE_BUSY`/`SQLITE_LOCKED` error - e.g. a write racing a
+    /// concurrent `vacuum`/`garbage_collect` - instead of aborting the whole
+    /// operation on the first contention hiccup. Any other error, or the
+    /// last attempt's transient error once `retry_max_attempts` is
+    /// exhausted, is returned as-is.
+    fn with_retry<T>(&self, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+        let max_attempts = self.config.retry_max_attempts.max(1);
+        let mut delay = Duration::from_millis(self.config.retry_base_delay_ms.max(1));
+        let mut attempt = 1;
+
+        loop {
+            match op() {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < max_attempts && is_transient_sqlite_error(&error) => {
+                    std::thread::sleep(delay);
+                    let max_delay = Duration::from_millis(self.config.retry_max_delay_ms.max(1));
+                    delay = (delay * 2).min(max_delay);
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
 
-        Ok(conn)
+/// Whether `error` is a transient `SQLITE_BUSY`/`SQLITE_LOCKED` failure worth
+/// retrying rather than a real error, unwrapping through any `Context`/
+/// `Traced` layers [`ResultExt::context`] or [`BlazeError::with_backtrace`]
+/// added on top of the underlying [`rusqlite::Error`]
+fn is_transient_sqlite_error(error: &BlazeError) -> bool {
+    match error {
+        BlazeError::Database(rusqlite::Error::SqliteFailure(sqlite_error, _)) => matches!(
+            sqlite_error.code,
+            rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+        ),
+        BlazeError::Context { source, .. } => is_transient_sqlite_error(source),
+        BlazeError::Traced { inner, .. } => is_transient_sqlite_error(inner),
+        _ => false,
     }
 }
 
@@ -604,6 +2356,92 @@ pub struct DatabaseStats {
     pub total_file_size: u64,
 }
 
+/// Distribution of chunk sizes currently in storage, used to report how well
+/// content-defined chunking is normalizing around its target average
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkSizeDistribution {
+    pub count: usize,
+    pub min: u64,
+    pub max: u64,
+    pub avg: u64,
+    pub median: u64,
+}
+
+impl ChunkSizeDistribution {
+    /// Build a distribution summary from a list of chunk sizes; the slice is
+    /// assumed to already be sorted ascending (as
+    /// [`crate::chunks::ChunkStore::stored_chunk_sizes`] returns it)
+    pub(crate) fn from_sizes(sorted_sizes: &[u64]) -> Self {
+        if sorted_sizes.is_empty() {
+            return Self::default();
+        }
+
+        let count = sorted_sizes.len();
+        let sum: u64 = sorted_sizes.iter().sum();
+
+        ChunkSizeDistribution {
+            count,
+            min: sorted_sizes[0],
+            max: sorted_sizes[count - 1],
+            avg: sum / count as u64,
+            median: sorted_sizes[count / 2],
+        }
+    }
+
+    /// Get a formatted summary of the distribution
+    pub fn summary(&self) -> String {
+        if self.count == 0 {
+            return "No chunks stored".to_string();
+        }
+
+        format!(
+            "{} chunks, size min/avg/median/max: {} / {} / {} / {}",
+            self.count,
+            crate::utils::format_size(self.min),
+            crate::utils::format_size(self.avg),
+            crate::utils::format_size(self.median),
+            crate::utils::format_size(self.max),
+        )
+    }
+}
+
+/// How much stored chunk content is actually being reused across the
+/// repository's history, computed by walking every commit's full tree
+/// snapshot in [`Database::chunk_sharing_distribution`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkSharingDistribution {
+    pub unique_chunks_referenced: usize,
+    pub total_chunk_references: usize,
+    pub chunks_shared_across_multiple_commits: usize,
+}
+
+impl ChunkSharingDistribution {
+    /// References per unique chunk - 1.0 means every chunk is referenced
+    /// exactly once (no reuse at all), higher means dedup is paying off
+    pub fn dedup_ratio(&self) -> f64 {
+        if self.unique_chunks_referenced == 0 {
+            return 0.0;
+        }
+
+        self.total_chunk_references as f64 / self.unique_chunks_referenced as f64
+    }
+
+    /// Get a formatted summary of the distribution
+    pub fn summary(&self) -> String {
+        if self.unique_chunks_referenced == 0 {
+            return "No chunks referenced".to_string();
+        }
+
+        format!(
+            "{} unique chunks, {} references ({:.2}x dedup ratio), {} shared across multiple commits",
+            self.unique_chunks_referenced,
+            self.total_chunk_references,
+            self.dedup_ratio(),
+            self.chunks_shared_across_multiple_commits,
+        )
+    }
+}
+
 impl DatabaseStats {
     /// Get a formatted summary of the statistics
     pub fn summary(&self) -> String {
@@ -619,40 +2457,6 @@ impl DatabaseStats {
     }
 }
 
-// Helper functions for parsing database rows
-
-fn parse_file_record(row: &Row) -> rusqlite::Result<FileRecord> {
-    let chunks_json: String = row.get(1)?;
-    let chunks: Vec<String> = serde_json::from_str(&chunks_json).map_err(|_e| {
-        rusqlite::Error::InvalidColumnType(1, "chunks".to_string(), rusqlite::types::Type::Text)
-    })?;
-
-    Ok(FileRecord {
-        path: row.get(0)?,
-        chunks,
-        size: row.get::<_, i64>(2)? as u64,
-        mtime: row.get::<_, i64>(3)? as u64,
-        permissions: row.get::<_, i64>(4)? as u32,
-        is_executable: row.get::<_, i64>(5)? != 0,
-    })
-}
-
-fn parse_commit_record(row: &Row) -> rusqlite::Result<CommitRecord> {
-    let files_json: String = row.get(5)?;
-    let files: HashMap<String, FileRecord> = serde_json::from_str(&files_json).map_err(|_| {
-        rusqlite::Error::InvalidColumnType(5, "files_json".to_string(), rusqlite::types::Type::Text)
-    })?;
-
-    Ok(CommitRecord {
-        hash: row.get(0)?,
-        parent: row.get(1)?,
-        message: row.get(2)?,
-        timestamp: row.get::<_, i64>(3)? as u64,
-        tree_hash: row.get(4)?,
-        files,
-    })
-}
-
 fn current_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -687,6 +2491,34 @@ mod tests {
         assert_eq!(stats.commit_count, 0);
     }
 
+    #[test]
+    fn test_init_migrates_to_latest_schema_version_and_is_idempotent() {
+        let (_temp_dir, db) = create_test_db();
+
+        let latest = SCHEMA_MIGRATIONS.last().unwrap().version;
+        assert_eq!(db.schema_version().unwrap(), latest);
+
+        // Re-running init on an up-to-date database applies no migrations
+        // and leaves it usable.
+        db.init().unwrap();
+        assert_eq!(db.schema_version().unwrap(), latest);
+        db.store_chunk("abc", 1).unwrap();
+        assert!(db.chunk_exists("abc").unwrap());
+    }
+
+    #[test]
+    fn test_migrate_refuses_a_schema_newer_than_this_build_supports() {
+        let (_temp_dir, db) = create_test_db();
+
+        let conn = db.open_connection().unwrap();
+        let latest = SCHEMA_MIGRATIONS.last().unwrap().version;
+        conn.execute_batch(&format!("PRAGMA user_version = {}", latest + 1))
+            .unwrap();
+        drop(conn);
+
+        assert!(db.migrate().is_err());
+    }
+
     #[test]
     fn test_chunk_operations() {
         let (_temp_dir, db) = create_test_db();
@@ -700,6 +2532,8 @@ mod tests {
         // Check existence
         assert!(db.chunk_exists(hash).unwrap());
         assert!(!db.chunk_exists("nonexistent").unwrap());
+        assert!(db.has_chunk(hash).unwrap());
+        assert!(!db.has_chunk("nonexistent").unwrap());
 
         // Get chunk
         let chunk = db.get_chunk(hash).unwrap().unwrap();
@@ -718,6 +2552,10 @@ mod tests {
             mtime: 1234567890,
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
         };
 
         // Store file
@@ -729,9 +2567,13 @@ mod tests {
         assert_eq!(retrieved.chunks, record.chunks);
         assert_eq!(retrieved.size, record.size);
 
+        assert!(db.has_file("test.txt").unwrap());
+        assert!(!db.has_file("nonexistent.txt").unwrap());
+
         // Delete file
         assert!(db.delete_file("test.txt").unwrap());
         assert!(!db.delete_file("test.txt").unwrap());
+        assert!(!db.has_file("test.txt").unwrap());
     }
 
     #[test]
@@ -748,6 +2590,10 @@ mod tests {
                 mtime: 1234567890,
                 permissions: 0o644,
                 is_executable: false,
+                partial_hash: "deadbeef".to_string(),
+                full_hash: None,
+                kind: crate::files::FileKind::Regular,
+                xattrs: std::collections::BTreeMap::new(),
             },
         );
 
@@ -772,6 +2618,11 @@ mod tests {
         let retrieved = db.get_commit("commit").unwrap().unwrap();
         assert_eq!(retrieved.hash, commit.hash);
 
+        // Existence probes honor the same partial-hash prefix matching
+        assert!(db.has_commit("commit123").unwrap());
+        assert!(db.has_commit("commit").unwrap());
+        assert!(!db.has_commit("nonexistent").unwrap());
+
         // Get commits with limit
         let commits = db.get_commits(Some(1), None).unwrap();
         assert_eq!(commits.len(), 1);
@@ -814,6 +2665,10 @@ mod tests {
             mtime: 1234567890,
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
         };
         db.store_file(&file_record).unwrap();
 
@@ -830,4 +2685,481 @@ mod tests {
         let issues = db.check_integrity().unwrap();
         assert!(issues.is_empty()); // Should be no issues in a fresh database
     }
+
+    #[test]
+    fn test_chunk_sharing_distribution_counts_cross_commit_reuse() {
+        let (_temp_dir, db) = create_test_db();
+
+        let mut make_files = |chunks: Vec<&str>| {
+            let mut files = HashMap::new();
+            files.insert(
+                "test.txt".to_string(),
+                FileRecord {
+                    path: "test.txt".to_string(),
+                    chunks: chunks.into_iter().map(String::from).collect(),
+                    size: 100,
+                    mtime: 1234567890,
+                    permissions: 0o644,
+                    is_executable: false,
+                    partial_hash: "deadbeef".to_string(),
+                    full_hash: None,
+                    kind: crate::files::FileKind::Regular,
+                    xattrs: std::collections::BTreeMap::new(),
+                },
+            );
+            files
+        };
+
+        // "shared" survives unchanged into the second commit; "only_first"
+        // and "only_second" are each referenced by a single commit.
+        db.store_commit(&CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "first".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files: make_files(vec!["shared", "only_first"]),
+        })
+        .unwrap();
+
+        db.store_commit(&CommitRecord {
+            hash: "commit2".to_string(),
+            parent: Some("commit1".to_string()),
+            message: "second".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree2".to_string(),
+            files: make_files(vec!["shared", "only_second"]),
+        })
+        .unwrap();
+
+        let distribution = db.chunk_sharing_distribution().unwrap();
+        assert_eq!(distribution.unique_chunks_referenced, 3);
+        assert_eq!(distribution.total_chunk_references, 4);
+        assert_eq!(distribution.chunks_shared_across_multiple_commits, 1);
+        assert!((distribution.dedup_ratio() - (4.0 / 3.0)).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_garbage_collect_reclaims_chunks_unreferenced_by_files_or_commits() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("from_file", 10).unwrap();
+        db.store_chunk("from_commit", 20).unwrap();
+        db.store_chunk("orphaned", 30).unwrap();
+
+        db.store_file(&FileRecord {
+            path: "test.txt".to_string(),
+            chunks: vec!["from_file".to_string()],
+            size: 10,
+            mtime: 1234567890,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
+        })
+        .unwrap();
+
+        let mut commit_files = HashMap::new();
+        commit_files.insert(
+            "committed.txt".to_string(),
+            FileRecord {
+                path: "committed.txt".to_string(),
+                chunks: vec!["from_commit".to_string()],
+                size: 20,
+                mtime: 1234567890,
+                permissions: 0o644,
+                is_executable: false,
+                partial_hash: "deadbeef".to_string(),
+                full_hash: None,
+                kind: crate::files::FileKind::Regular,
+                xattrs: std::collections::BTreeMap::new(),
+            },
+        );
+        db.store_commit(&CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "first".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files: commit_files,
+        })
+        .unwrap();
+        db.store_ref("HEAD", Some("commit1")).unwrap();
+
+        let result = db.garbage_collect().unwrap();
+        assert_eq!(result.reclaimed_hashes, vec!["orphaned".to_string()]);
+        assert_eq!(result.reclaimed_bytes, 30);
+
+        assert!(db.chunk_exists("from_file").unwrap());
+        assert!(db.chunk_exists("from_commit").unwrap());
+        assert!(!db.chunk_exists("orphaned").unwrap());
+    }
+
+    #[test]
+    fn test_garbage_collect_reclaims_chunks_from_unreachable_commits() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("abandoned", 5).unwrap();
+
+        let mut files = HashMap::new();
+        files.insert(
+            "dropped.txt".to_string(),
+            FileRecord {
+                path: "dropped.txt".to_string(),
+                chunks: vec!["abandoned".to_string()],
+                size: 5,
+                mtime: 1234567890,
+                permissions: 0o644,
+                is_executable: false,
+                partial_hash: "deadbeef".to_string(),
+                full_hash: None,
+                kind: crate::files::FileKind::Regular,
+                xattrs: std::collections::BTreeMap::new(),
+            },
+        );
+        // No ref ever points at this commit, so it is unreachable and its
+        // chunks are collectible even though the commit row still exists.
+        db.store_commit(&CommitRecord {
+            hash: "orphan_commit".to_string(),
+            parent: None,
+            message: "abandoned".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files,
+        })
+        .unwrap();
+
+        let result = db.garbage_collect().unwrap();
+        assert_eq!(result.reclaimed_hashes, vec!["abandoned".to_string()]);
+    }
+
+    #[test]
+    fn test_gc_dry_run_reports_without_deleting() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("orphaned", 30).unwrap();
+
+        let report = db.gc(true).unwrap();
+        assert_eq!(report.chunks_deleted, 1);
+        assert_eq!(report.bytes_freed, 30);
+        assert!(db.chunk_exists("orphaned").unwrap());
+
+        let report = db.gc(false).unwrap();
+        assert_eq!(report.chunks_deleted, 1);
+        assert!(!db.chunk_exists("orphaned").unwrap());
+    }
+
+    #[test]
+    fn test_gc_keep_protects_unreferenced_chunks() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("pinned", 10).unwrap();
+        db.add_gc_keep("pinned").unwrap();
+
+        let report = db.gc(false).unwrap();
+        assert_eq!(report.chunks_deleted, 0);
+        assert!(db.chunk_exists("pinned").unwrap());
+
+        db.remove_gc_keep("pinned").unwrap();
+        let report = db.gc(false).unwrap();
+        assert_eq!(report.chunks_deleted, 1);
+        assert!(!db.chunk_exists("pinned").unwrap());
+    }
+
+    #[test]
+    fn test_gc_reports_commits_scanned() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("c1", 1).unwrap();
+        let mut files = HashMap::new();
+        files.insert(
+            "a.txt".to_string(),
+            FileRecord {
+                path: "a.txt".to_string(),
+                chunks: vec!["c1".to_string()],
+                size: 1,
+                mtime: 1234567890,
+                permissions: 0o644,
+                is_executable: false,
+                partial_hash: "deadbeef".to_string(),
+                full_hash: None,
+                kind: crate::files::FileKind::Regular,
+                xattrs: std::collections::BTreeMap::new(),
+            },
+        );
+        db.store_commit(&CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "first".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files,
+        })
+        .unwrap();
+        db.store_ref("HEAD", Some("commit1")).unwrap();
+
+        let report = db.gc(true).unwrap();
+        assert_eq!(report.commits_scanned, 1);
+        assert_eq!(report.chunks_deleted, 0);
+    }
+
+    #[test]
+    fn test_delete_chunks_refuses_to_delete_referenced_chunks() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("referenced", 10).unwrap();
+        db.store_chunk("unreferenced", 20).unwrap();
+
+        db.store_file(&FileRecord {
+            path: "test.txt".to_string(),
+            chunks: vec!["referenced".to_string()],
+            size: 10,
+            mtime: 1234567890,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
+        })
+        .unwrap();
+
+        let deleted = db
+            .delete_chunks(&["referenced".to_string(), "unreferenced".to_string()])
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert!(db.chunk_exists("referenced").unwrap());
+        assert!(!db.chunk_exists("unreferenced").unwrap());
+    }
+
+    #[test]
+    fn test_delete_file_removes_its_file_chunks_rows() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("only_ref", 10).unwrap();
+        db.store_file(&FileRecord {
+            path: "test.txt".to_string(),
+            chunks: vec!["only_ref".to_string()],
+            size: 10,
+            mtime: 1234567890,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
+        })
+        .unwrap();
+
+        db.delete_file("test.txt").unwrap();
+
+        // With the file gone, its chunk is no longer referenced by anything.
+        let deleted = db.delete_chunks(&["only_ref".to_string()]).unwrap();
+        assert_eq!(deleted, 1);
+    }
+
+    #[test]
+    fn test_dump_restore_round_trips_every_table() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("c1", 5).unwrap();
+        let mut files = HashMap::new();
+        files.insert(
+            "a.txt".to_string(),
+            FileRecord {
+                path: "a.txt".to_string(),
+                chunks: vec!["c1".to_string()],
+                size: 5,
+                mtime: 1234567890,
+                permissions: 0o644,
+                is_executable: false,
+                partial_hash: "deadbeef".to_string(),
+                full_hash: None,
+                kind: crate::files::FileKind::Regular,
+                xattrs: std::collections::BTreeMap::new(),
+            },
+        );
+        db.store_file(&files["a.txt"]).unwrap();
+        db.store_commit(&CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "first".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files,
+        })
+        .unwrap();
+        db.store_ref("HEAD", Some("commit1")).unwrap();
+        db.store_change(&ChangeRecord {
+            change_id: "change1".to_string(),
+            commit_hash: "commit1".to_string(),
+            parent_change_id: None,
+            parent_commit_hash: None,
+        })
+        .unwrap();
+
+        let mut archive = Vec::new();
+        db.dump(&mut archive).unwrap();
+
+        let (_restored_dir, restored) = create_test_db();
+        restored.restore(&archive[..]).unwrap();
+
+        assert_eq!(restored.get_stats().unwrap().chunk_count, 1);
+        assert!(restored.chunk_exists("c1").unwrap());
+        assert_eq!(restored.get_file("a.txt").unwrap().unwrap().chunks, vec!["c1"]);
+        assert_eq!(
+            restored.get_commit("commit1").unwrap().unwrap().message,
+            "first"
+        );
+        assert_eq!(
+            restored.get_ref("HEAD").unwrap().unwrap().commit_hash,
+            Some("commit1".to_string())
+        );
+        assert_eq!(
+            restored.get_change("change1").unwrap().unwrap().commit_hash,
+            "commit1"
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_unknown_schema_version() {
+        let (_temp_dir, db) = create_test_db();
+
+        let mut archive = Vec::new();
+        db.dump(&mut archive).unwrap();
+
+        // Corrupt the dump's declared schema version so no loader matches.
+        let decoder = flate2::read::GzDecoder::new(&archive[..]);
+        let mut tar_archive = tar::Archive::new(decoder);
+        let mut entries = HashMap::new();
+        for entry in tar_archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).unwrap();
+            entries.insert(path, bytes);
+        }
+        let mut metadata: DumpMetadata =
+            serde_json::from_slice(&entries["metadata.json"]).unwrap();
+        metadata.db_version = 999;
+
+        let mut rewritten = Vec::new();
+        {
+            let encoder = flate2::write::GzEncoder::new(&mut rewritten, flate2::Compression::default());
+            let mut builder = tar::Builder::new(encoder);
+            append_json_entry(&mut builder, "metadata.json", &metadata).unwrap();
+            for name in ["chunks.json", "files.json", "commits.json", "refs.json", "changes.json"] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(entries[name].len() as u64);
+                header.set_mode(0o644);
+                builder.append_data(&mut header, name, &entries[name][..]).unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let (_restored_dir, restored) = create_test_db();
+        assert!(restored.restore(&rewritten[..]).is_err());
+    }
+
+    #[test]
+    fn test_write_batch_applies_every_op_in_one_transaction() {
+        let (_temp_dir, db) = create_test_db();
+
+        let mut batch = WriteBatch::new();
+        batch.put_chunk("c1", 10);
+        batch.put_file(FileRecord {
+            path: "a.txt".to_string(),
+            chunks: vec!["c1".to_string()],
+            size: 10,
+            mtime: 1234567890,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: crate::files::FileKind::Regular,
+            xattrs: std::collections::BTreeMap::new(),
+        });
+        batch.put_commit(CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "initial".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files: HashMap::new(),
+        });
+        batch.put_ref("main", Some("commit1".to_string()));
+        batch.put_change(ChangeRecord {
+            change_id: "change1".to_string(),
+            commit_hash: "commit1".to_string(),
+            parent_change_id: None,
+            parent_commit_hash: None,
+        });
+
+        db.write(batch).unwrap();
+
+        assert!(db.chunk_exists("c1").unwrap());
+        assert!(db.get_file("a.txt").unwrap().is_some());
+        assert!(db.get_commit("commit1").unwrap().is_some());
+        assert_eq!(
+            db.get_ref("main").unwrap().unwrap().commit_hash,
+            Some("commit1".to_string())
+        );
+        assert!(db.get_change("change1").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_write_batch_deletes_are_applied_too() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("c1", 10).unwrap();
+        db.store_ref("main", Some("commit1")).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.delete_chunk("c1");
+        batch.delete_ref("main");
+        db.write(batch).unwrap();
+
+        assert!(!db.chunk_exists("c1").unwrap());
+        assert!(db.get_ref("main").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_reads_are_unaffected_by_later_writes() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_chunk("before", 10).unwrap();
+        let snapshot = db.snapshot().unwrap();
+
+        db.store_chunk("after", 20).unwrap();
+
+        assert!(snapshot.get_chunk("before").unwrap().is_some());
+        assert!(snapshot.get_chunk("after").unwrap().is_none());
+        assert_eq!(snapshot.get_stats().unwrap().chunk_count, 1);
+
+        // The live database sees the write the snapshot was taken before.
+        assert!(db.chunk_exists("after").unwrap());
+    }
+
+    #[test]
+    fn test_snapshot_get_commits_matches_database_iteration() {
+        let (_temp_dir, db) = create_test_db();
+
+        db.store_commit(&CommitRecord {
+            hash: "commit1".to_string(),
+            parent: None,
+            message: "initial".to_string(),
+            timestamp: current_timestamp(),
+            tree_hash: "tree1".to_string(),
+            files: HashMap::new(),
+        })
+        .unwrap();
+
+        let snapshot = db.snapshot().unwrap();
+        let commits = snapshot.get_commits(None, None).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].hash, "commit1");
+    }
 }
@@ -0,0 +1,203 @@
+//! Persistent dirstate cache recording each tracked path's last-known disk
+//! state, so `status` and `find_modified_files` can trust a `stat()` instead
+//! of re-scanning and re-chunking the whole working tree on every call.
+//!
+//! Follows the dirstate-v2 "racy" design: a file whose mtime falls in the
+//! same second the dirstate itself was last written can't be trusted by
+//! mtime alone (the filesystem clock may not have ticked between the two
+//! writes), so such entries are saved with their mtime cleared and are
+//! always treated as a cache miss. Where the filesystem reports one, the
+//! mtime's sub-second remainder is kept alongside it, so two edits inside
+//! the same second are still told apart without waiting for the next
+//! second to roll over.
+
+use crate::errors::{Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Last-known disk state for one tracked path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct DirstateEntry {
+    size: u64,
+    /// `None` marks an ambiguous mtime recorded too close to the dirstate's
+    /// own write time to trust - always treated as a cache miss
+    mtime: Option<u64>,
+    /// Sub-second remainder of `mtime`, when the filesystem reports one.
+    /// Tightens the match beyond whole-second `mtime` so two edits inside
+    /// the same second are told apart without falling back to the
+    /// ambiguous-mtime cache miss. Always `0` on filesystems that don't
+    /// report sub-second precision, which compares equal to itself just
+    /// like a missing value would.
+    #[serde(default)]
+    mtime_nanos: u32,
+    /// Content identity derived from the tracked chunk list (see
+    /// [`chunk_list_identity`]), kept around for whatever recorded the entry
+    /// to tell a same-content touch apart from a genuine edit
+    hash: String,
+}
+
+/// On-disk cache of `path -> DirstateEntry`, refreshed on `add`/`commit` and
+/// consulted by `status`/`find_modified_files` to skip re-chunking paths
+/// whose metadata hasn't moved
+#[derive(Debug, Default)]
+pub struct Dirstate {
+    entries: HashMap<String, DirstateEntry>,
+    dirty: bool,
+}
+
+impl Dirstate {
+    /// Load a dirstate from disk, starting empty if it doesn't exist yet or
+    /// fails to parse - a corrupt or missing dirstate is never fatal, it
+    /// just means every tracked path gets fully compared once
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        Dirstate {
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// Persist the dirstate, first clearing the mtime of any entry that
+    /// falls in the same second as `written_at` so a same-second edit
+    /// afterward is never silently missed. Skips the write entirely if
+    /// nothing changed since it was loaded.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P, written_at: u64) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        for entry in self.entries.values_mut() {
+            if entry.mtime.is_some_and(|mtime| mtime >= written_at) {
+                entry.mtime = None;
+                entry.mtime_nanos = 0;
+            }
+        }
+
+        let json = serde_json::to_string(&self.entries).context("Failed to serialize dirstate")?;
+        fs::write(path, json).context("Failed to write dirstate")?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Record (or refresh) the cached state of one tracked path
+    pub fn insert(&mut self, path: String, size: u64, mtime: u64, mtime_nanos: u32, hash: String) {
+        self.entries.insert(
+            path,
+            DirstateEntry {
+                size,
+                mtime: Some(mtime),
+                mtime_nanos,
+                hash,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Whether `path`'s on-disk `size`/`mtime` match its cached entry closely
+    /// enough to trust it unchanged without reading its content
+    pub fn is_unchanged(&self, path: &str, size: u64, mtime: u64, mtime_nanos: u32) -> bool {
+        self.entries.get(path).is_some_and(|entry| {
+            entry.size == size && entry.mtime == Some(mtime) && entry.mtime_nanos == mtime_nanos
+        })
+    }
+
+    /// Cached content identity for `path`, if any
+    pub fn cached_hash(&self, path: &str) -> Option<&str> {
+        self.entries.get(path).map(|entry| entry.hash.as_str())
+    }
+}
+
+/// Cheap, order-sensitive identity for a file's chunk list - the same
+/// content always splits into the same chunks in the same order, so hashing
+/// the list stands in for a full content hash without rereading the file
+pub fn chunk_list_identity(chunks: &[String]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for chunk in chunks {
+        hasher.update(chunk.as_bytes());
+        hasher.update(b",");
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unchanged_requires_exact_size_and_mtime_match() {
+        let mut dirstate = Dirstate::default();
+        dirstate.insert("a.txt".to_string(), 10, 100, 0, "h1".to_string());
+
+        assert!(dirstate.is_unchanged("a.txt", 10, 100, 0));
+        assert!(!dirstate.is_unchanged("a.txt", 11, 100, 0));
+        assert!(!dirstate.is_unchanged("a.txt", 10, 101, 0));
+        assert!(!dirstate.is_unchanged("missing.txt", 10, 100, 0));
+    }
+
+    #[test]
+    fn test_unchanged_distinguishes_same_second_edits_by_nanos() {
+        let mut dirstate = Dirstate::default();
+        dirstate.insert("a.txt".to_string(), 10, 100, 500, "h1".to_string());
+
+        assert!(dirstate.is_unchanged("a.txt", 10, 100, 500));
+        assert!(!dirstate.is_unchanged("a.txt", 10, 100, 501));
+    }
+
+    #[test]
+    fn test_ambiguous_mtime_is_cleared_on_save_and_never_trusted() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dirstate.json");
+
+        let mut dirstate = Dirstate::default();
+        // Entry's mtime (100) falls in the same second the dirstate itself
+        // is about to be written (also 100) - ambiguous.
+        dirstate.insert("a.txt".to_string(), 10, 100, 0, "h1".to_string());
+        dirstate.save(&path, 100).unwrap();
+
+        let reloaded = Dirstate::load(&path);
+        assert!(!reloaded.is_unchanged("a.txt", 10, 100, 0));
+        assert_eq!(reloaded.cached_hash("a.txt"), Some("h1"));
+    }
+
+    #[test]
+    fn test_unambiguous_mtime_survives_save_and_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dirstate.json");
+
+        let mut dirstate = Dirstate::default();
+        dirstate.insert("a.txt".to_string(), 10, 100, 0, "h1".to_string());
+        dirstate.save(&path, 105).unwrap();
+
+        let reloaded = Dirstate::load(&path);
+        assert!(reloaded.is_unchanged("a.txt", 10, 100, 0));
+    }
+
+    #[test]
+    fn test_save_is_noop_when_not_dirty() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dirstate.json");
+
+        let mut dirstate = Dirstate::load(&path);
+        dirstate.save(&path, 100).unwrap();
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_chunk_list_identity_is_order_sensitive_and_deterministic() {
+        let a = chunk_list_identity(&["h1".to_string(), "h2".to_string()]);
+        let b = chunk_list_identity(&["h2".to_string(), "h1".to_string()]);
+        let c = chunk_list_identity(&["h1".to_string(), "h2".to_string()]);
+
+        assert_ne!(a, b);
+        assert_eq!(a, c);
+    }
+}
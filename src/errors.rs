@@ -5,57 +5,173 @@ use std::fmt;
 /// Result type alias for Blaze operations
 pub type Result<T> = std::result::Result<T, BlazeError>;
 
-/// Main error type for Blaze VCS operations
-#[derive(Debug)]
-pub enum BlazeError {
+/// Declares `BlazeError`'s plain message-carrying variants in one table,
+/// in the spirit of vaultwarden's `make_error!`: each row gives the
+/// variant's payload type, its `Display` template, and the process exit
+/// code [`BlazeError::exit_code`] maps it to (loosely following
+/// `sysexits.h`, so shell scripts and CI can branch on failure class), with
+/// an optional `from(ExternalType) as |e| ...` to also generate that
+/// conversion's `From` impl. Expands to the enum itself plus its `Display`
+/// and `exit_code` match arms - `Context`/`Traced` carry extra fields the
+/// table can't express and are added by hand alongside the generated
+/// variants instead.
+macro_rules! blaze_error_variants {
+    (
+        $(
+            $(#[$var_meta:meta])*
+            $variant:ident($payload:ty) => $display:literal, exit = $exit:expr
+            $(, from($from_ty:ty) as $conv:expr)?
+        );* $(;)?
+    ) => {
+        /// Main error type for Blaze VCS operations
+        #[derive(Debug)]
+        pub enum BlazeError {
+            $(
+                $(#[$var_meta])*
+                $variant($payload),
+            )*
+            /// A message layered onto an underlying error by `ResultExt::context`,
+            /// keeping the original error inspectable via `source()` instead of
+            /// flattening it into a string
+            Context {
+                context: String,
+                source: Box<BlazeError>,
+            },
+            /// Wraps another `BlazeError` with a backtrace captured at the point an
+            /// error macro or a `From` conversion built it. Transparent to
+            /// `Display` and `source()` - reports exactly as `inner` would, plus
+            /// `BlazeError::backtrace`. Only ever constructed by
+            /// [`BlazeError::with_backtrace`].
+            Traced {
+                inner: Box<BlazeError>,
+                backtrace: CapturedBacktrace,
+            },
+        }
+
+        impl fmt::Display for BlazeError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $( BlazeError::$variant(payload) => write!(f, $display, payload), )*
+                    // The chain is walked separately via `source()`; printing it
+                    // here too would duplicate it for every `Display` call.
+                    BlazeError::Context { context, .. } => write!(f, "{}", context),
+                    BlazeError::Traced { inner, .. } => write!(f, "{}", inner),
+                }
+            }
+        }
+
+        impl BlazeError {
+            /// Process exit code this error should surface as, loosely
+            /// following `sysexits.h` so shell scripts and CI can branch on
+            /// failure class instead of just pass/fail
+            pub fn exit_code(&self) -> i32 {
+                match self {
+                    $( BlazeError::$variant(_) => $exit, )*
+                    BlazeError::Context { source, .. } => source.exit_code(),
+                    BlazeError::Traced { inner, .. } => inner.exit_code(),
+                }
+            }
+
+            /// This error's category, unaffected by however many `Context`
+            /// layers `with_context`/`context` have wrapped around it - so
+            /// callers can `match err.kind()` without caring how the error
+            /// was reported
+            pub fn kind(&self) -> ErrorKind {
+                match self {
+                    $( BlazeError::$variant(_) => ErrorKind::$variant, )*
+                    BlazeError::Context { source, .. } => source.kind(),
+                    BlazeError::Traced { inner, .. } => inner.kind(),
+                }
+            }
+        }
+
+        /// The category a [`BlazeError`] belongs to, stable across however
+        /// many [`BlazeError::Context`] layers wrap it - see
+        /// [`BlazeError::kind`]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrorKind {
+            $( $variant, )*
+        }
+
+        $(
+            $(
+                impl From<$from_ty> for BlazeError {
+                    fn from(err: $from_ty) -> Self {
+                        let convert: fn($from_ty) -> $payload = $conv;
+                        BlazeError::$variant(convert(err)).with_backtrace()
+                    }
+                }
+            )?
+        )*
+    };
+}
+
+blaze_error_variants! {
     /// I/O related errors
-    Io(std::io::Error),
+    Io(std::io::Error) => "I/O error: {}", exit = 74, from(std::io::Error) as |e| e;
     /// Database related errors
-    Database(rusqlite::Error),
+    Database(rusqlite::Error) => "Database error: {}", exit = 74, from(rusqlite::Error) as |e| e;
     /// File system errors
-    FileSystem(String),
+    FileSystem(String) => "File system error: {}", exit = 74, from(walkdir::Error) as |e| e.to_string();
     /// Repository errors
-    Repository(String),
+    Repository(String) => "Repository error: {}", exit = 70;
     /// Configuration errors
-    Config(String),
+    Config(String) => "Configuration error: {}", exit = 78;
     /// Chunk processing errors
-    Chunk(String),
+    Chunk(String) => "Chunk processing error: {}", exit = 65;
     /// Lock file errors
-    Lock(String),
+    Lock(String) => "Lock file error: {}", exit = 75;
     /// Serialization/deserialization errors
-    Serialization(String),
+    Serialization(String) => "Serialization error: {}", exit = 65, from(serde_json::Error) as |e| e.to_string();
     /// Hash computation errors
-    Hash(String),
+    Hash(String) => "Hash computation error: {}", exit = 65;
     /// Path resolution errors
-    Path(String),
+    Path(String) => "Path error: {}", exit = 64;
     /// Permission errors
-    Permission(String),
+    Permission(String) => "Permission error: {}", exit = 77;
     /// Validation errors
-    Validation(String),
+    Validation(String) => "Validation error: {}", exit = 65;
     /// Network errors (for future remote operations)
-    Network(String),
+    Network(String) => "Network error: {}", exit = 69;
     /// Generic error with custom message
-    Generic(String),
+    Generic(String) => "Error: {}", exit = 70, from(anyhow::Error) as |e| e.to_string();
 }
 
-impl fmt::Display for BlazeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            BlazeError::Io(err) => write!(f, "I/O error: {}", err),
-            BlazeError::Database(err) => write!(f, "Database error: {}", err),
-            BlazeError::FileSystem(msg) => write!(f, "File system error: {}", msg),
-            BlazeError::Repository(msg) => write!(f, "Repository error: {}", msg),
-            BlazeError::Config(msg) => write!(f, "Configuration error: {}", msg),
-            BlazeError::Chunk(msg) => write!(f, "Chunk processing error: {}", msg),
-            BlazeError::Lock(msg) => write!(f, "Lock file error: {}", msg),
-            BlazeError::Serialization(msg) => write!(f, "Serialization error: {}", msg),
-            BlazeError::Hash(msg) => write!(f, "Hash computation error: {}", msg),
-            BlazeError::Path(msg) => write!(f, "Path error: {}", msg),
-            BlazeError::Permission(msg) => write!(f, "Permission error: {}", msg),
-            BlazeError::Validation(msg) => write!(f, "Validation error: {}", msg),
-            BlazeError::Network(msg) => write!(f, "Network error: {}", msg),
-            BlazeError::Generic(msg) => write!(f, "Error: {}", msg),
-        }
+/// Backtrace captured alongside a [`BlazeError::Traced`], gated behind the
+/// `backtrace` cargo feature. `std::backtrace::Backtrace::capture()` already
+/// respects `RUST_BACKTRACE`/`RUST_LIB_BACKTRACE` and is itself cheap when
+/// disabled, but this type goes one step further: when the feature is off
+/// it's a zero-size placeholder, so opting out costs nothing beyond an
+/// unreachable match arm.
+#[cfg(feature = "backtrace")]
+#[derive(Debug)]
+pub struct CapturedBacktrace(std::backtrace::Backtrace);
+
+#[cfg(not(feature = "backtrace"))]
+#[derive(Debug, Default)]
+pub struct CapturedBacktrace;
+
+impl CapturedBacktrace {
+    #[cfg(feature = "backtrace")]
+    fn capture() -> Self {
+        CapturedBacktrace(std::backtrace::Backtrace::capture())
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn capture() -> Self {
+        CapturedBacktrace
+    }
+
+    /// The captured backtrace, if the feature is enabled and capture
+    /// actually succeeded (`RUST_BACKTRACE`/`RUST_LIB_BACKTRACE` were set)
+    #[cfg(feature = "backtrace")]
+    fn as_backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        (self.0.status() == std::backtrace::BacktraceStatus::Captured).then_some(&self.0)
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn as_backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        None
     }
 }
 
@@ -64,39 +180,69 @@ impl std::error::Error for BlazeError {
         match self {
             BlazeError::Io(err) => Some(err),
             BlazeError::Database(err) => Some(err),
+            BlazeError::Context { source, .. } => Some(&**source),
+            // Transparent: skips straight to `inner`'s own source rather
+            // than treating `inner` as a distinct chain link, since `inner`
+            // already supplied this node's `Display` message.
+            BlazeError::Traced { inner, .. } => inner.source(),
             _ => None,
         }
     }
 }
 
-// Automatic conversions from common error types
-impl From<std::io::Error> for BlazeError {
-    fn from(err: std::io::Error) -> Self {
-        BlazeError::Io(err)
+impl BlazeError {
+    /// Walk this error's cause chain, starting with itself and then
+    /// following `source()` until it runs out - the same `iter_causes()`
+    /// pattern Cargo uses to render its own multi-line error reports
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn std::error::Error + 'static)> {
+        std::iter::successors(Some(self as &(dyn std::error::Error + 'static)), |err| err.source())
     }
-}
 
-impl From<rusqlite::Error> for BlazeError {
-    fn from(err: rusqlite::Error) -> Self {
-        BlazeError::Database(err)
-    }
-}
+    /// Render the full cause chain as a multi-line diagnostic: the head
+    /// error's `Display` message, followed by an indented `caused by: ...`
+    /// line for each deeper cause, and finally the backtrace captured at
+    /// construction time, if any
+    pub fn report(&self) -> String {
+        let mut chain = self.chain();
+        let mut report = chain.next().map(|err| err.to_string()).unwrap_or_default();
+
+        for cause in chain {
+            report.push_str(&format!("\n  caused by: {}", cause));
+        }
+
+        if let Some(backtrace) = self.backtrace() {
+            report.push_str(&format!("\n\n{}", backtrace));
+        }
 
-impl From<serde_json::Error> for BlazeError {
-    fn from(err: serde_json::Error) -> Self {
-        BlazeError::Serialization(err.to_string())
+        report
     }
-}
 
-impl From<walkdir::Error> for BlazeError {
-    fn from(err: walkdir::Error) -> Self {
-        BlazeError::FileSystem(err.to_string())
+    /// Wrap `self` with a backtrace captured here, when the `backtrace`
+    /// feature is enabled; returns `self` unchanged otherwise, so opting
+    /// out costs neither a capture nor an allocation. Used by the error
+    /// macros and the `From` conversions below, which are where a
+    /// `BlazeError` actually comes into existence.
+    pub fn with_backtrace(self) -> Self {
+        #[cfg(feature = "backtrace")]
+        {
+            BlazeError::Traced {
+                inner: Box::new(self),
+                backtrace: CapturedBacktrace::capture(),
+            }
+        }
+        #[cfg(not(feature = "backtrace"))]
+        {
+            self
+        }
     }
-}
 
-impl From<anyhow::Error> for BlazeError {
-    fn from(err: anyhow::Error) -> Self {
-        BlazeError::Generic(err.to_string())
+    /// The backtrace captured when this error was constructed, if the
+    /// `backtrace` feature is enabled and capture actually succeeded
+    pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        match self {
+            BlazeError::Traced { backtrace, .. } => backtrace.as_backtrace(),
+            _ => None,
+        }
     }
 }
 
@@ -104,40 +250,40 @@ impl From<anyhow::Error> for BlazeError {
 #[macro_export]
 macro_rules! repository_error {
     ($msg:expr) => {
-        $crate::errors::BlazeError::Repository($msg.to_string())
+        $crate::errors::BlazeError::Repository($msg.to_string()).with_backtrace()
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::errors::BlazeError::Repository(format!($fmt, $($arg)*))
+        $crate::errors::BlazeError::Repository(format!($fmt, $($arg)*)).with_backtrace()
     };
 }
 
 #[macro_export]
 macro_rules! config_error {
     ($msg:expr) => {
-        $crate::errors::BlazeError::Config($msg.to_string())
+        $crate::errors::BlazeError::Config($msg.to_string()).with_backtrace()
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::errors::BlazeError::Config(format!($fmt, $($arg)*))
+        $crate::errors::BlazeError::Config(format!($fmt, $($arg)*)).with_backtrace()
     };
 }
 
 #[macro_export]
 macro_rules! chunk_error {
     ($msg:expr) => {
-        $crate::errors::BlazeError::Chunk($msg.to_string())
+        $crate::errors::BlazeError::Chunk($msg.to_string()).with_backtrace()
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::errors::BlazeError::Chunk(format!($fmt, $($arg)*))
+        $crate::errors::BlazeError::Chunk(format!($fmt, $($arg)*)).with_backtrace()
     };
 }
 
 #[macro_export]
 macro_rules! validation_error {
     ($msg:expr) => {
-        $crate::errors::BlazeError::Validation($msg.to_string())
+        $crate::errors::BlazeError::Validation($msg.to_string()).with_backtrace()
     };
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::errors::BlazeError::Validation(format!($fmt, $($arg)*))
+        $crate::errors::BlazeError::Validation(format!($fmt, $($arg)*)).with_backtrace()
     };
 }
 
@@ -160,10 +306,9 @@ where
     where
         F: FnOnce() -> String,
     {
-        self.map_err(|err| {
-            let base_err = err.into();
-            let context = f();
-            BlazeError::Generic(format!("{}: {}", context, base_err))
+        self.map_err(|err| BlazeError::Context {
+            context: f(),
+            source: Box::new(err.into()),
         })
     }
 
@@ -206,6 +351,90 @@ mod tests {
             .contains("additional context"));
     }
 
+    #[test]
+    fn test_context_preserves_source_chain() {
+        use std::error::Error;
+
+        let result: std::result::Result<(), std::io::Error> =
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "file not found"));
+
+        let err = result.context("loading config").unwrap_err();
+        assert_eq!(err.to_string(), "loading config");
+
+        let source = err.source().expect("context should preserve its source");
+        assert!(source.to_string().contains("file not found"));
+    }
+
+    #[test]
+    fn test_chain_and_report_walk_nested_context() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing file");
+        let result: std::result::Result<(), std::io::Error> = Err(io_err);
+
+        let err = result
+            .context("reading config")
+            .with_context(|| "loading repository".to_string())
+            .unwrap_err();
+
+        // Each layer contributes its own node: the two context messages,
+        // then the `Io` variant's own "I/O error: ..." Display, then the
+        // raw `std::io::Error` at the bottom of the chain.
+        let raw_io_message = std::io::Error::new(std::io::ErrorKind::NotFound, "missing file").to_string();
+        let messages: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "loading repository".to_string(),
+                "reading config".to_string(),
+                format!("I/O error: {}", raw_io_message),
+                raw_io_message.clone(),
+            ]
+        );
+
+        assert_eq!(
+            err.report(),
+            format!(
+                "loading repository\n  caused by: reading config\n  caused by: I/O error: {}\n  caused by: {}",
+                raw_io_message, raw_io_message
+            )
+        );
+    }
+
+    #[test]
+    fn test_backtrace_absent_without_feature() {
+        // Without the `backtrace` feature, `with_backtrace` is a no-op and
+        // no error carries a backtrace to report.
+        let err = repository_error!("test message");
+        assert!(err.backtrace().is_none());
+        assert_eq!(err.report(), "Repository error: test message");
+    }
+
+    #[test]
+    fn test_exit_code_by_category_and_through_wrappers() {
+        assert_eq!(BlazeError::Validation("bad".to_string()).exit_code(), 65);
+        assert_eq!(BlazeError::Config("bad".to_string()).exit_code(), 78);
+        assert_eq!(
+            repository_error!("unreachable").exit_code(),
+            BlazeError::Repository(String::new()).exit_code()
+        );
+
+        // `Context` and `Traced` defer to the error they wrap rather than
+        // carrying their own exit code.
+        let wrapped = BlazeError::Context {
+            context: "loading repository".to_string(),
+            source: Box::new(BlazeError::Permission("denied".to_string())),
+        };
+        assert_eq!(wrapped.exit_code(), 77);
+    }
+
+    #[test]
+    fn test_kind_survives_context_wrapping() {
+        let result: std::result::Result<(), BlazeError> = Err(chunk_error!("bad chunk"));
+        let err = result.context("verifying commit").unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Chunk);
+        assert_eq!(err.to_string(), "verifying commit");
+    }
+
     #[test]
     fn test_error_macros() {
         let err = repository_error!("test message");
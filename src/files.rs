@@ -1,17 +1,111 @@
 //! File record management and file-related operations for Blaze VCS
 
-use crate::config::{CHUNK_SIZE, LARGE_FILE_THRESHOLD};
+use crate::config::{ChunkingConfig, ChunkingStrategy, LARGE_FILE_THRESHOLD};
 use crate::errors::{BlazeError, Result, ResultExt};
-use crate::utils::{get_mtime, is_binary_file};
+use crate::utils::{get_mtime, is_binary_file, is_binary_file_with_extensions};
 use blake3::Hasher;
+use digest::Digest;
 use memmap2::MmapOptions;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::Read;
-use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3;
+
+/// Cheap, comparable fingerprint of a file's on-disk metadata, used to decide
+/// whether a previous chunk list can be reused wholesale without touching the
+/// file's contents at all (pxar-style reuse: trust size + mtime, and inode
+/// when the caller has one, instead of re-chunking and re-hashing)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileFingerprint {
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time as Unix timestamp
+    pub mtime: u64,
+    /// Inode number, when available on the current platform/filesystem
+    pub inode: Option<u64>,
+}
+
+/// Kind of filesystem entry a `FileRecord` describes. Following zvault's lead,
+/// Blaze tracks enough about non-regular entries to snapshot and restore them
+/// faithfully instead of silently treating everything as a regular file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FileKind {
+    /// An ordinary file whose content is chunked
+    Regular,
+    /// A symbolic link, storing its target instead of chunked content
+    Symlink {
+        /// The raw target the link points at
+        target: String,
+    },
+    /// A named pipe (FIFO)
+    Fifo,
+    /// A character device node, identified by its device number
+    CharDevice {
+        /// Raw device number, as returned by `stat`
+        rdev: u64,
+    },
+    /// A block device node, identified by its device number
+    BlockDevice {
+        /// Raw device number, as returned by `stat`
+        rdev: u64,
+    },
+}
+
+impl Default for FileKind {
+    fn default() -> Self {
+        FileKind::Regular
+    }
+}
+
+impl FileKind {
+    /// Classify a path from metadata that was stat'ed without following
+    /// symlinks (`symlink_metadata`), reading the link target or device
+    /// number as needed instead of treating every entry as a regular file
+    fn detect(path: &Path, metadata: &std::fs::Metadata) -> Result<Self> {
+        let file_type = metadata.file_type();
+
+        if file_type.is_symlink() {
+            let target = std::fs::read_link(path)
+                .context("Failed to read symlink target")?
+                .to_string_lossy()
+                .to_string();
+            Ok(FileKind::Symlink { target })
+        } else if file_type.is_fifo() {
+            Ok(FileKind::Fifo)
+        } else if file_type.is_char_device() {
+            Ok(FileKind::CharDevice {
+                rdev: metadata.rdev(),
+            })
+        } else if file_type.is_block_device() {
+            Ok(FileKind::BlockDevice {
+                rdev: metadata.rdev(),
+            })
+        } else {
+            Ok(FileKind::Regular)
+        }
+    }
+}
+
+/// Best-effort collection of a path's extended attributes. Not every
+/// filesystem supports xattrs, and reading them can fail for reasons
+/// unrelated to the file's actual content (unsupported fs, permissions), so
+/// any error here just yields an empty map rather than failing the scan.
+fn collect_xattrs(path: &Path) -> BTreeMap<String, Vec<u8>> {
+    let Ok(names) = xattr::list(path) else {
+        return BTreeMap::new();
+    };
+
+    names
+        .filter_map(|name| {
+            let value = xattr::get(path, &name).ok()??;
+            Some((name.to_string_lossy().to_string(), value))
+        })
+        .collect()
+}
 
 /// Represents a file record in the Blaze VCS
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -28,25 +122,130 @@ pub struct FileRecord {
     pub permissions: u32,
     /// Whether the file is executable
     pub is_executable: bool,
+    /// Cheap first-block + length fingerprint used by the two-phase dedup
+    /// pre-filter (see `compute_partial_hash`) to rule out duplicate
+    /// candidates before paying for a full content hash
+    #[serde(default)]
+    pub partial_hash: String,
+    /// Whole-file content hash, only populated once a `partial_hash`
+    /// collision with another file made a full comparison worthwhile
+    #[serde(default)]
+    pub full_hash: Option<String>,
+    /// What kind of filesystem entry this record describes. Defaults to
+    /// `Regular` so existing on-disk records without this field keep working.
+    #[serde(default)]
+    pub kind: FileKind,
+    /// Extended attributes captured from disk, keyed by attribute name
+    #[serde(default)]
+    pub xattrs: BTreeMap<String, Vec<u8>>,
 }
 
 impl FileRecord {
-    /// Create a new FileRecord from a file path
+    /// Create a new FileRecord from a file path, stat'ing the path itself
+    /// (not whatever it points at) so symlinks, FIFOs and device nodes are
+    /// captured faithfully instead of being chunked as if they were regular
+    /// files
     pub fn from_path<P: AsRef<Path>>(
         file_path: P,
         repo_root: P,
         chunks: Vec<String>,
     ) -> Result<Self> {
         let file_path = file_path.as_ref();
-        let repo_root = repo_root.as_ref();
+        let metadata =
+            std::fs::symlink_metadata(file_path).context("Failed to read file metadata")?;
+        let mtime = get_mtime(file_path)?;
+        let kind = FileKind::detect(file_path, &metadata)?;
+        let xattrs = collect_xattrs(file_path);
+
+        let (chunks, partial_hash) = if matches!(kind, FileKind::Regular) {
+            (chunks, compute_partial_hash(&read_leading_block(file_path)?))
+        } else {
+            (Vec::new(), String::new())
+        };
+
+        Self::build(
+            file_path,
+            repo_root.as_ref(),
+            chunks,
+            &metadata,
+            mtime,
+            partial_hash,
+            None,
+            kind,
+            xattrs,
+        )
+    }
+
+    /// Like `from_path`, but reuses metadata and mtime the caller already has
+    /// on hand instead of re-stat'ing the file. Only used for regular files
+    /// the caller has already filtered for, so the kind is always `Regular`.
+    pub fn from_path_with_metadata<P: AsRef<Path>>(
+        file_path: P,
+        repo_root: P,
+        chunks: Vec<String>,
+        metadata: &std::fs::Metadata,
+        mtime: u64,
+    ) -> Result<Self> {
+        let file_path = file_path.as_ref();
+        let partial_hash = compute_partial_hash(&read_leading_block(file_path)?);
+        let xattrs = collect_xattrs(file_path);
+
+        Self::build(
+            file_path,
+            repo_root.as_ref(),
+            chunks,
+            metadata,
+            mtime,
+            partial_hash,
+            None,
+            FileKind::Regular,
+            xattrs,
+        )
+    }
+
+    /// Like `from_path_with_metadata`, but with a precomputed partial/full
+    /// dedup fingerprint, avoiding a redundant leading-block read when the
+    /// caller has already run the two-phase dedup pre-filter
+    pub(crate) fn from_path_with_dedup_hashes<P: AsRef<Path>>(
+        file_path: P,
+        repo_root: P,
+        chunks: Vec<String>,
+        metadata: &std::fs::Metadata,
+        mtime: u64,
+        partial_hash: String,
+        full_hash: Option<String>,
+    ) -> Result<Self> {
+        let xattrs = collect_xattrs(file_path.as_ref());
 
+        Self::build(
+            file_path.as_ref(),
+            repo_root.as_ref(),
+            chunks,
+            metadata,
+            mtime,
+            partial_hash,
+            full_hash,
+            FileKind::Regular,
+            xattrs,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        file_path: &Path,
+        repo_root: &Path,
+        chunks: Vec<String>,
+        metadata: &std::fs::Metadata,
+        mtime: u64,
+        partial_hash: String,
+        full_hash: Option<String>,
+        kind: FileKind,
+        xattrs: BTreeMap<String, Vec<u8>>,
+    ) -> Result<Self> {
         let relative_path = file_path
             .strip_prefix(repo_root)
             .map_err(|e| BlazeError::Path(format!("Invalid file path: {}", e)))?;
 
-        let metadata = std::fs::metadata(file_path).context("Failed to read file metadata")?;
-
-        let mtime = get_mtime(file_path)?;
         let permissions = metadata.permissions().mode();
         let is_executable = permissions & 0o111 != 0;
         let size = metadata.len();
@@ -58,6 +257,10 @@ impl FileRecord {
             mtime,
             permissions,
             is_executable,
+            partial_hash,
+            full_hash,
+            kind,
+            xattrs,
         })
     }
 
@@ -65,7 +268,14 @@ impl FileRecord {
     pub fn is_different_from_disk<P: AsRef<Path>>(&self, repo_root: P) -> Result<bool> {
         let file_path = repo_root.as_ref().join(&self.path);
 
-        if !file_path.exists() {
+        let metadata = match std::fs::symlink_metadata(&file_path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(e) => return Err(e).context("Failed to read current file metadata"),
+        };
+
+        let current_kind = FileKind::detect(&file_path, &metadata)?;
+        if current_kind != self.kind {
             return Ok(true);
         }
 
@@ -74,9 +284,6 @@ impl FileRecord {
             return Ok(true);
         }
 
-        let metadata =
-            std::fs::metadata(&file_path).context("Failed to read current file metadata")?;
-
         if metadata.len() != self.size {
             return Ok(true);
         }
@@ -86,6 +293,10 @@ impl FileRecord {
             return Ok(true);
         }
 
+        if collect_xattrs(&file_path) != self.xattrs {
+            return Ok(true);
+        }
+
         Ok(false)
     }
 
@@ -94,23 +305,36 @@ impl FileRecord {
         self.chunks.len()
     }
 
-    /// Check if this is a binary file based on its extension
+    /// Check if this is a binary file based on its extension, against the
+    /// built-in binary extension list
     pub fn is_binary(&self) -> bool {
         is_binary_file(&self.path)
     }
 
+    /// Check if this is a binary file based on its extension, against a
+    /// configured extension list - for repos that have overridden
+    /// [`crate::config::BINARY_EXTENSIONS`] via `blaze.toml`
+    pub fn is_binary_with_extensions(&self, extensions: &[String]) -> bool {
+        is_binary_file_with_extensions(&self.path, extensions)
+    }
+
     /// Get a human-readable summary of this file record
     pub fn summary(&self) -> String {
+        let kind = match &self.kind {
+            FileKind::Regular if self.is_executable => "executable".to_string(),
+            FileKind::Regular => "regular".to_string(),
+            FileKind::Symlink { target } => format!("symlink -> {}", target),
+            FileKind::Fifo => "fifo".to_string(),
+            FileKind::CharDevice { rdev } => format!("char device {}", rdev),
+            FileKind::BlockDevice { rdev } => format!("block device {}", rdev),
+        };
+
         format!(
             "{} ({} chunks, {} bytes, {})",
             self.path,
             self.chunks.len(),
             crate::utils::format_size(self.size),
-            if self.is_executable {
-                "executable"
-            } else {
-                "regular"
-            }
+            kind
         )
     }
 }
@@ -127,17 +351,30 @@ pub struct FileChunk {
 }
 
 impl FileChunk {
-    /// Create a new chunk from raw data
+    /// Create a new chunk from raw data, hashed with the `Xxh3` default
     pub fn new(data: Vec<u8>) -> Self {
-        let hash = compute_chunk_hash(&data);
+        Self::new_with_algo(data, HashAlgo::default())
+    }
+
+    /// Create a new chunk from raw data, hashed with a specific algorithm -
+    /// for stores that have opted into something other than the `Xxh3`
+    /// default
+    pub fn new_with_algo(data: Vec<u8>, algo: HashAlgo) -> Self {
+        let hash = compute_chunk_hash_with(algo, &data);
         let size = data.len();
 
         FileChunk { hash, size, data }
     }
 
-    /// Verify that the chunk data matches its hash
+    /// Verify that the chunk data matches its hash, assuming it was hashed
+    /// with the `Xxh3` default
     pub fn verify(&self) -> bool {
-        compute_chunk_hash(&self.data) == self.hash
+        compute_chunk_hash_with(HashAlgo::default(), &self.data) == self.hash
+    }
+
+    /// Verify that the chunk data matches its hash under a specific algorithm
+    pub fn verify_with_algo(&self, algo: HashAlgo) -> bool {
+        compute_chunk_hash_with(algo, &self.data) == self.hash
     }
 }
 
@@ -148,8 +385,260 @@ pub fn compute_chunk_hash(data: &[u8]) -> String {
     hasher.finalize().to_hex().to_string()
 }
 
-/// Chunk a file into smaller pieces for storage
+/// Number of leading bytes hashed for the two-phase dedup pre-filter's cheap
+/// fingerprint - small enough that computing it never requires reading a
+/// whole large file
+pub const PARTIAL_HASH_BLOCK_SIZE: usize = 4096;
+
+/// Fast 128-bit fingerprint over a file's first block, paired with its length
+/// by the caller to rule out duplicate candidates before paying for a full
+/// content hash. Not collision-resistant - only used to group candidates for
+/// a later full-hash confirmation, never as a standalone identity check.
+pub fn compute_partial_hash(data: &[u8]) -> String {
+    let block_len = data.len().min(PARTIAL_HASH_BLOCK_SIZE);
+    format!("{:032x}", xxh3::xxh3_128(&data[..block_len]))
+}
+
+/// Read at most `PARTIAL_HASH_BLOCK_SIZE` bytes from the start of a file
+fn read_leading_block(file_path: &Path) -> Result<Vec<u8>> {
+    let file = File::open(file_path).context("Failed to open file for partial hash")?;
+    let mut buf = Vec::new();
+    file.take(PARTIAL_HASH_BLOCK_SIZE as u64)
+        .read_to_end(&mut buf)
+        .context("Failed to read leading block for partial hash")?;
+    Ok(buf)
+}
+
+/// Cheap first-pass fingerprint for the two-phase dedup pre-filter: the
+/// file's size plus a partial hash of only its first block, without reading
+/// (or chunking) the rest of the file
+pub fn partial_fingerprint<P: AsRef<Path>>(file_path: P) -> Result<(u64, String)> {
+    let file_path = file_path.as_ref();
+    let size = std::fs::metadata(file_path)
+        .context("Failed to read file metadata for partial fingerprint")?
+        .len();
+    let partial_hash = compute_partial_hash(&read_leading_block(file_path)?);
+    Ok((size, partial_hash))
+}
+
+/// Content hash algorithm a chunk store addresses its chunks with
+///
+/// `Xxh3` is the default: a non-cryptographic hash that's several times
+/// faster than any of the cryptographic options here, which matters more for
+/// the common case - hashing every chunk of a huge working tree on `add` -
+/// than resisting a motivated collision does. `Blake3` is offered for users
+/// who want a cryptographic, SIMD-accelerated tree hash instead (still
+/// substantially faster than SHA-256 on the crate's 2 MB chunks, since it
+/// parallelizes internally); `Blake2b` and `Sha256` alongside it for
+/// deployments with a specific cryptographic compliance requirement. `Crc32`
+/// trades collision-resistance further still for the cheapest possible check
+/// - enough to catch accidental corruption, not enough to resist a motivated
+/// collision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgo {
+    Blake3,
+    Blake2b,
+    Sha256,
+    Xxh3,
+    Crc32,
+}
+
+impl Default for HashAlgo {
+    /// `Xxh3` (16 hex chars) is safe as the default precisely because the
+    /// chunk index's on-disk record is self-describing about hash length
+    /// rather than assuming Blake3's 64 chars - see `encode_index_record`/
+    /// `decode_index_record` in `chunks.rs`. Don't change this default
+    /// without that guarantee still holding.
+    fn default() -> Self {
+        HashAlgo::Xxh3
+    }
+}
+
+impl HashAlgo {
+    /// Short identifier persisted in a chunk store's metadata file
+    pub fn marker(&self) -> &'static str {
+        match self {
+            HashAlgo::Blake3 => "blake3",
+            HashAlgo::Blake2b => "blake2b",
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Xxh3 => "xxh3",
+            HashAlgo::Crc32 => "crc32",
+        }
+    }
+
+    /// Parse a marker previously written by `marker`
+    pub fn from_marker(marker: &str) -> Result<Self> {
+        match marker {
+            "blake3" => Ok(HashAlgo::Blake3),
+            "blake2b" => Ok(HashAlgo::Blake2b),
+            "sha256" => Ok(HashAlgo::Sha256),
+            "xxh3" => Ok(HashAlgo::Xxh3),
+            "crc32" => Ok(HashAlgo::Crc32),
+            other => Err(BlazeError::Chunk(format!(
+                "Unknown hash algorithm marker: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Build a fresh streaming hasher for this algorithm
+    fn hasher(&self) -> Box<dyn ChunkHasher> {
+        match self {
+            HashAlgo::Blake3 => Box::new(Blake3ChunkHasher(Hasher::new())),
+            HashAlgo::Blake2b => Box::new(Blake2bChunkHasher(blake2::Blake2b512::new())),
+            HashAlgo::Sha256 => Box::new(Sha256ChunkHasher(sha2::Sha256::new())),
+            HashAlgo::Xxh3 => Box::new(Xxh3ChunkHasher(xxh3::Xxh3::new())),
+            HashAlgo::Crc32 => Box::new(Crc32ChunkHasher::new()),
+        }
+    }
+}
+
+/// Compute a chunk's content hash using a specific algorithm, for stores that
+/// have opted into something other than the `Xxh3` default
+pub fn compute_chunk_hash_with(algo: HashAlgo, data: &[u8]) -> String {
+    let mut hasher = algo.hasher();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// A content hasher that can be fed data incrementally instead of requiring
+/// the whole chunk up front, so callers streaming a large chunk don't have to
+/// buffer it twice just to hash it
+pub trait ChunkHasher {
+    /// Feed more data into the running hash
+    fn update(&mut self, data: &[u8]);
+    /// Finish the hash and render it as the hex string stored on a `FileChunk`
+    fn finalize(&self) -> String;
+}
+
+struct Blake3ChunkHasher(Hasher);
+
+impl ChunkHasher for Blake3ChunkHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(&self) -> String {
+        self.0.finalize().to_hex().to_string()
+    }
+}
+
+struct Blake2bChunkHasher(blake2::Blake2b512);
+
+impl ChunkHasher for Blake2bChunkHasher {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(&self) -> String {
+        crate::utils::bytes_to_hex(&Digest::finalize(self.0.clone()))
+    }
+}
+
+struct Sha256ChunkHasher(sha2::Sha256);
+
+impl ChunkHasher for Sha256ChunkHasher {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(&self) -> String {
+        crate::utils::bytes_to_hex(&Digest::finalize(self.0.clone()))
+    }
+}
+
+struct Xxh3ChunkHasher(xxh3::Xxh3);
+
+impl ChunkHasher for Xxh3ChunkHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(&self) -> String {
+        format!("{:016x}", self.0.digest())
+    }
+}
+
+#[derive(Default)]
+struct Crc32ChunkHasher(u32);
+
+impl Crc32ChunkHasher {
+    fn new() -> Self {
+        Crc32ChunkHasher(0xFFFF_FFFF)
+    }
+}
+
+impl ChunkHasher for Crc32ChunkHasher {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            let index = ((self.0 ^ byte as u32) & 0xff) as usize;
+            self.0 = (self.0 >> 8) ^ CRC32_TABLE[index];
+        }
+    }
+
+    fn finalize(&self) -> String {
+        format!("{:08x}", self.0 ^ 0xFFFF_FFFF)
+    }
+}
+
+/// Fixed table of CRC-32 (IEEE 802.3, reflected) remainders for each possible
+/// byte, built at compile time so corruption checks don't need an external
+/// crate dependency.
+static CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut remainder = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            remainder = if remainder & 1 != 0 {
+                (remainder >> 1) ^ 0xEDB8_8320
+            } else {
+                remainder >> 1
+            };
+            bit += 1;
+        }
+        table[i] = remainder;
+        i += 1;
+    }
+    table
+}
+
+/// Compute the standard CRC-32 (IEEE 802.3) checksum of `data`
+fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Crc32ChunkHasher::new();
+    hasher.update(data);
+    hasher.0 ^ 0xFFFF_FFFF
+}
+
+/// Chunk a file into smaller pieces for storage, hashed with the `Xxh3`
+/// default and the default chunking policy
 pub fn chunk_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<FileChunk>> {
+    chunk_file_with_algo(file_path, HashAlgo::default())
+}
+
+/// Chunk a file into smaller pieces for storage, hashed with a specific
+/// algorithm - for repos that have opted into something other than the
+/// `Xxh3` default - using the default chunking policy
+pub fn chunk_file_with_algo<P: AsRef<Path>>(
+    file_path: P,
+    algo: HashAlgo,
+) -> Result<Vec<FileChunk>> {
+    chunk_file_with_config(file_path, algo, &ChunkingConfig::default())
+}
+
+/// Chunk a file into smaller pieces for storage, hashed with `algo` and cut
+/// according to `config` - the entry point that actually honors a store's
+/// configured chunking policy, since [`chunk_file`]/[`chunk_file_with_algo`]
+/// both just forward here with [`ChunkingConfig::default`]
+pub fn chunk_file_with_config<P: AsRef<Path>>(
+    file_path: P,
+    algo: HashAlgo,
+    config: &ChunkingConfig,
+) -> Result<Vec<FileChunk>> {
     let file_path = file_path.as_ref();
     let file = File::open(file_path)
         .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
@@ -157,46 +646,268 @@ pub fn chunk_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<FileChunk>> {
     let file_size = file.metadata()?.len();
 
     if file_size > LARGE_FILE_THRESHOLD {
-        chunk_large_file(file, file_size)
+        chunk_large_file(file, algo, config)
     } else {
-        chunk_regular_file(file)
+        chunk_regular_file(file, algo, config)
     }
 }
 
-/// Chunk a regular-sized file using buffered reading
-fn chunk_regular_file(mut file: File) -> Result<Vec<FileChunk>> {
-    let mut chunks = Vec::new();
-    let mut buffer = vec![0u8; CHUNK_SIZE];
+/// Chunk a regular-sized file by reading it fully and cutting on content
+/// boundaries
+fn chunk_regular_file(mut file: File, algo: HashAlgo, config: &ChunkingConfig) -> Result<Vec<FileChunk>> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
 
-    loop {
-        let bytes_read = file.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
-        }
+    Ok(chunk_boundaries(&data, config)
+        .into_iter()
+        .map(|(start, len)| FileChunk::new_with_algo(data[start..start + len].to_vec(), algo))
+        .collect())
+}
 
-        let chunk_data = buffer[..bytes_read].to_vec();
-        chunks.push(FileChunk::new(chunk_data));
-    }
+/// Chunk a large file using memory mapping, finding content-defined cut
+/// points sequentially and then hashing the resulting chunks in parallel
+fn chunk_large_file(file: File, algo: HashAlgo, config: &ChunkingConfig) -> Result<Vec<FileChunk>> {
+    let mmap = unsafe { MmapOptions::new().map(&file).map_err(BlazeError::Io)? };
 
-    Ok(chunks)
+    let boundaries = chunk_boundaries(&mmap, config);
+    let chunks: Result<Vec<_>> = boundaries
+        .into_par_iter()
+        .map(|(start, len)| Ok(FileChunk::new_with_algo(mmap[start..start + len].to_vec(), algo)))
+        .collect();
+
+    chunks
 }
 
-/// Chunk a large file using memory mapping for better performance
-fn chunk_large_file(file: File, file_size: u64) -> Result<Vec<FileChunk>> {
-    let mmap = unsafe { MmapOptions::new().map(&file).map_err(BlazeError::Io)? };
+/// Chunk many files at once with the `Xxh3` default, balancing work across
+/// the rayon pool by byte size rather than by file count
+pub fn chunk_files(
+    paths: &[PathBuf],
+    max_parallel_chunks: usize,
+    min_span: usize,
+) -> Result<HashMap<String, Vec<FileChunk>>> {
+    chunk_files_with_algo(paths, max_parallel_chunks, min_span, HashAlgo::default())
+}
+
+/// Chunk many files at once, spreading the work evenly across the rayon pool
+/// instead of processing one file at a time. Borrowed from `slb`'s multi-file
+/// block-splitting: every file's size is stat'ed up front, then each file is
+/// cut into byte spans of roughly `total_bytes / max_parallel_chunks` bytes
+/// (never smaller than `min_span`, so a pile of tiny files doesn't balloon
+/// into a pile of tiny spans). A single huge file alongside many small ones
+/// therefore still gets split into several spans that run next to the small
+/// files instead of serializing in front of them.
+///
+/// Each span is content-chunked independently, so a span boundary forces a
+/// chunk cut that a whole-file scan wouldn't have made - this trades a little
+/// dedup fidelity at span edges for even per-worker byte loads. Returns one
+/// chunk list per input path, in on-disk byte order.
+pub fn chunk_files_with_algo(
+    paths: &[PathBuf],
+    max_parallel_chunks: usize,
+    min_span: usize,
+    algo: HashAlgo,
+) -> Result<HashMap<String, Vec<FileChunk>>> {
+    let max_parallel_chunks = max_parallel_chunks.max(1) as u64;
+    let min_span = (min_span.max(1)) as u64;
+
+    let sizes: Vec<u64> = paths
+        .iter()
+        .map(|path| {
+            std::fs::metadata(path)
+                .map(|m| m.len())
+                .with_context(|| format!("Failed to read metadata for {}", path.display()))
+        })
+        .collect::<Result<_>>()?;
+
+    let total_bytes: u64 = sizes.iter().sum();
+    let target_span = (total_bytes / max_parallel_chunks).max(min_span);
+
+    #[derive(Clone, Copy)]
+    struct Span {
+        file_index: usize,
+        start: u64,
+        len: u64,
+    }
+
+    let mut spans = Vec::new();
+    for (file_index, &size) in sizes.iter().enumerate() {
+        if size == 0 {
+            spans.push(Span {
+                file_index,
+                start: 0,
+                len: 0,
+            });
+            continue;
+        }
+
+        let mut pos = 0;
+        while pos < size {
+            let len = target_span.min(size - pos);
+            spans.push(Span {
+                file_index,
+                start: pos,
+                len,
+            });
+            pos += len;
+        }
+    }
+
+    let config = ChunkingConfig::default();
 
-    let chunk_count = (file_size as usize).div_ceil(CHUNK_SIZE);
-    let chunks: Result<Vec<_>> = (0..chunk_count)
+    let results: Result<Vec<(usize, Vec<FileChunk>)>> = spans
         .into_par_iter()
-        .map(|i| {
-            let start = i * CHUNK_SIZE;
-            let end = std::cmp::min(start + CHUNK_SIZE, mmap.len());
-            let chunk_data = mmap[start..end].to_vec();
-            Ok(FileChunk::new(chunk_data))
+        .map(|span| {
+            let path = &paths[span.file_index];
+            let data = read_span(path, span.start, span.len)?;
+            Ok((span.file_index, chunk_bytes_with(&data, &config, algo)))
         })
         .collect();
 
-    chunks
+    let mut by_file: Vec<Vec<FileChunk>> = vec![Vec::new(); paths.len()];
+    for (file_index, chunks) in results? {
+        by_file[file_index].extend(chunks);
+    }
+
+    Ok(paths
+        .iter()
+        .zip(by_file)
+        .map(|(path, chunks)| (path.to_string_lossy().to_string(), chunks))
+        .collect())
+}
+
+/// Read exactly `len` bytes starting at `start` from a file, without reading
+/// the rest of it - the unit of work `chunk_files` hands to each rayon task
+fn read_span(path: &Path, start: u64, len: u64) -> Result<Vec<u8>> {
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file: {}", path.display()))?;
+    file.seek(SeekFrom::Start(start))
+        .with_context(|| format!("Failed to seek in file: {}", path.display()))?;
+
+    let mut buf = vec![0u8; len as usize];
+    file.read_exact(&mut buf)
+        .with_context(|| format!("Failed to read span of file: {}", path.display()))?;
+    Ok(buf)
+}
+
+/// Stricter gear-hash mask used while a candidate chunk is still smaller than
+/// the configured `avg_size` - more set bits means a boundary is less likely to
+/// match, which lets small chunks grow toward the average instead of cutting
+/// early.
+const MASK_SMALL: u64 = 0x0000_d903_0353_0000;
+
+/// Looser gear-hash mask used once a candidate chunk has grown past
+/// the configured `avg_size` - fewer set bits means a boundary is more likely to
+/// match soon, capping how far oversized chunks drift from the average.
+const MASK_LARGE: u64 = 0x0000_4903_0353_0000;
+
+/// Fixed table of 256 pseudorandom 64-bit "gear" values used to roll the
+/// FastCDC fingerprint. Built at compile time from a fixed seed so chunk
+/// boundaries - and therefore dedup - are reproducible across builds and
+/// machines.
+static GEAR: [u64; 256] = build_gear_table();
+
+const fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn build_gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = 0x5EED_CAFE_u64;
+    let mut i = 0;
+    while i < 256 {
+        state = splitmix64(state);
+        table[i] = state;
+        i += 1;
+    }
+    table
+}
+
+/// Find the length of the first content-defined chunk in `data`, using
+/// normalized FastCDC: bytes below `config.min_size` are never tested as a
+/// boundary, a stricter mask applies below the average target and a looser
+/// one past it, and a cut is forced at `config.max_size` if the gear hash
+/// never satisfies either mask.
+fn fastcdc_next_chunk_len(data: &[u8], config: &ChunkingConfig) -> usize {
+    if data.len() <= config.min_size {
+        return data.len();
+    }
+
+    let max_len = data.len().min(config.max_size);
+    let mut fp: u64 = 0;
+
+    for i in config.min_size..max_len {
+        fp = (fp << 1).wrapping_add(GEAR[data[i] as usize]);
+        let mask = if i < config.avg_size {
+            MASK_SMALL
+        } else {
+            MASK_LARGE
+        };
+
+        if fp & mask == 0 {
+            return i + 1;
+        }
+    }
+
+    max_len
+}
+
+/// Split `data` into content-defined chunk boundaries, returned as
+/// `(offset, len)` pairs
+fn fastcdc_boundaries(data: &[u8], config: &ChunkingConfig) -> Vec<(usize, usize)> {
+    let mut boundaries = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let len = fastcdc_next_chunk_len(&data[pos..], config);
+        boundaries.push((pos, len));
+        pos += len;
+    }
+
+    boundaries
+}
+
+/// Split `data` into fixed-size chunks of `config.avg_size` bytes, the way
+/// chunking worked before FastCDC. Doesn't look at content at all, so an
+/// edit near the start of the data shifts every later chunk boundary - kept
+/// only for [`ChunkingStrategy::Fixed`] so repos created before FastCDC can
+/// still be read with their original chunk boundaries.
+fn fixed_size_boundaries(data: &[u8], config: &ChunkingConfig) -> Vec<(usize, usize)> {
+    let mut boundaries = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let len = config.avg_size.min(data.len() - pos);
+        boundaries.push((pos, len));
+        pos += len;
+    }
+
+    boundaries
+}
+
+/// Split `data` into chunk boundaries using whichever strategy `config`
+/// selects
+fn chunk_boundaries(data: &[u8], config: &ChunkingConfig) -> Vec<(usize, usize)> {
+    match config.strategy {
+        ChunkingStrategy::FastCdc => fastcdc_boundaries(data, config),
+        ChunkingStrategy::Fixed => fixed_size_boundaries(data, config),
+    }
+}
+
+/// Chunk a bare byte slice under a specific chunking policy and hash
+/// algorithm, letting a `ChunkStore` tune both instead of being locked to the
+/// crate-wide defaults
+pub(crate) fn chunk_bytes_with(
+    data: &[u8],
+    config: &ChunkingConfig,
+    algo: HashAlgo,
+) -> Vec<FileChunk> {
+    chunk_boundaries(data, config)
+        .into_iter()
+        .map(|(start, len)| FileChunk::new_with_algo(data[start..start + len].to_vec(), algo))
+        .collect()
 }
 
 /// Reconstruct a file from its chunks
@@ -211,6 +922,95 @@ pub fn reconstruct_file_from_chunks(chunks: &[FileChunk]) -> Vec<u8> {
     result
 }
 
+/// Extensions classified as each non-text [`ContentCategory`], checked in
+/// this order so a format that could plausibly fit two buckets (e.g. `.gz`
+/// as both an archive and a compressed document) lands in the more specific
+/// one first
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "bmp", "ico", "webp", "svg", "tiff"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg", "m4a", "aac"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "avi", "mkv", "mov", "wmv", "flv", "webm"];
+const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx"];
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "rar", "7z", "tar", "gz", "bz2", "xz"];
+
+/// Broad content classification used to break `FileStats` down by what's
+/// actually taking up space, beyond a plain binary/text split
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentCategory {
+    Text,
+    Image,
+    Audio,
+    Video,
+    Document,
+    Archive,
+    Binary,
+}
+
+impl ContentCategory {
+    /// Classify a file, checking its extension first and falling back to
+    /// sniffing its first few bytes for a handful of common magic numbers
+    /// when the extension is missing or unrecognized
+    pub fn classify<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            let extension = extension.to_lowercase();
+            if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+                return ContentCategory::Image;
+            }
+            if AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+                return ContentCategory::Audio;
+            }
+            if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+                return ContentCategory::Video;
+            }
+            if DOCUMENT_EXTENSIONS.contains(&extension.as_str()) {
+                return ContentCategory::Document;
+            }
+            if ARCHIVE_EXTENSIONS.contains(&extension.as_str()) {
+                return ContentCategory::Archive;
+            }
+        }
+
+        Self::sniff(path)
+    }
+
+    /// Magic-number fallback for extensionless or unrecognized files
+    fn sniff(path: &Path) -> Self {
+        let mut header = [0u8; 16];
+        let Ok(mut file) = File::open(path) else {
+            return ContentCategory::Binary;
+        };
+        let Ok(read) = file.read(&mut header) else {
+            return ContentCategory::Binary;
+        };
+        let header = &header[..read];
+
+        if header.starts_with(b"\x89PNG") || header.starts_with(b"\xff\xd8\xff") || header.starts_with(b"GIF8")
+        {
+            return ContentCategory::Image;
+        }
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"\x1f\x8b") {
+            return ContentCategory::Archive;
+        }
+        if header.starts_with(b"%PDF") {
+            return ContentCategory::Document;
+        }
+
+        if header.contains(&0u8) {
+            ContentCategory::Binary
+        } else {
+            ContentCategory::Text
+        }
+    }
+}
+
+/// Running count and byte total for one [`ContentCategory`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CategoryStats {
+    pub count: usize,
+    pub bytes: u64,
+}
+
 /// File processing statistics
 #[derive(Debug, Default)]
 pub struct FileStats {
@@ -228,6 +1028,16 @@ pub struct FileStats {
     pub largest_file: u64,
     /// Average file size
     pub average_file_size: f64,
+    /// Files whose chunk list was reused from the persistent chunk cache
+    /// instead of being re-hashed
+    pub cache_hits: usize,
+    /// Files that had to be re-chunked because they were new or their
+    /// (mtime, size, permissions) no longer matched the chunk cache
+    pub cache_misses: usize,
+    /// Count and byte total per [`ContentCategory`], populated by callers
+    /// that classify each file as they scan it (see
+    /// [`FileStats::record_category`]) - left empty otherwise
+    pub by_category: HashMap<ContentCategory, CategoryStats>,
 }
 
 impl FileStats {
@@ -236,13 +1046,25 @@ impl FileStats {
         Self::default()
     }
 
-    /// Add statistics from a file record
+    /// Add statistics from a file record, against the built-in binary
+    /// extension list
     pub fn add_file(&mut self, record: &FileRecord) {
+        self.add_file_inner(record, record.is_binary());
+    }
+
+    /// Add statistics from a file record, against a configured binary
+    /// extension list - for repos that have overridden
+    /// [`crate::config::BINARY_EXTENSIONS`] via `blaze.toml`
+    pub fn add_file_with_extensions(&mut self, record: &FileRecord, binary_extensions: &[String]) {
+        self.add_file_inner(record, record.is_binary_with_extensions(binary_extensions));
+    }
+
+    fn add_file_inner(&mut self, record: &FileRecord, is_binary: bool) {
         self.total_files += 1;
         self.total_chunks += record.chunks.len();
         self.total_bytes += record.size;
 
-        if record.is_binary() {
+        if is_binary {
             self.binary_files += 1;
         }
 
@@ -257,9 +1079,35 @@ impl FileStats {
         self.average_file_size = self.total_bytes as f64 / self.total_files as f64;
     }
 
+    /// Fold one file's [`ContentCategory`] into the running breakdown -
+    /// classification needs the file's full path (for magic-byte sniffing on
+    /// an unrecognized extension), so callers compute it themselves and pass
+    /// the result in rather than this taking a bare `FileRecord`
+    pub fn record_category(&mut self, category: ContentCategory, size: u64) {
+        let entry = self.by_category.entry(category).or_default();
+        entry.count += 1;
+        entry.bytes += size;
+    }
+
+    /// Add statistics for a file chunked through `chunk_files`, which returns
+    /// bare chunk lists rather than full `FileRecord`s (binary/executable
+    /// detection needs a path and permissions the balanced chunker never
+    /// looks at)
+    pub fn add_chunked_file(&mut self, size: u64, chunk_count: usize) {
+        self.total_files += 1;
+        self.total_chunks += chunk_count;
+        self.total_bytes += size;
+
+        if size > self.largest_file {
+            self.largest_file = size;
+        }
+
+        self.average_file_size = self.total_bytes as f64 / self.total_files as f64;
+    }
+
     /// Get a formatted summary of the statistics
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             "Files: {}, Chunks: {}, Total: {}, Binary: {}, Executable: {}, Avg size: {}",
             self.total_files,
             self.total_chunks,
@@ -267,13 +1115,41 @@ impl FileStats {
             self.binary_files,
             self.executable_files,
             crate::utils::format_size(self.average_file_size as u64),
-        )
+        );
+
+        if self.cache_hits + self.cache_misses > 0 {
+            summary.push_str(&format!(
+                ", Cache hits: {}, Cache misses: {}",
+                self.cache_hits, self.cache_misses
+            ));
+        }
+
+        if !self.by_category.is_empty() {
+            let mut categories: Vec<_> = self.by_category.iter().collect();
+            categories.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes));
+
+            let breakdown: Vec<String> = categories
+                .into_iter()
+                .map(|(category, stats)| {
+                    format!(
+                        "{:?}: {} ({})",
+                        category,
+                        stats.count,
+                        crate::utils::format_size(stats.bytes)
+                    )
+                })
+                .collect();
+            summary.push_str(&format!("\nBy content type: {}", breakdown.join(", ")));
+        }
+
+        summary
     }
 }
 
 /// File change detection utilities
 pub mod changes {
     use super::*;
+    use std::collections::HashSet;
 
     /// Types of file changes
     #[derive(Debug, Clone, PartialEq)]
@@ -335,6 +1211,19 @@ pub mod changes {
             }
         }
 
+        /// Create a new file rename change, carrying the old path so a
+        /// rename can still be rendered as "R old -> new"
+        pub fn renamed(old_record: FileRecord, new_record: FileRecord) -> Self {
+            let path = new_record.path.clone();
+            let old_path = old_record.path.clone();
+            FileChange {
+                path,
+                change_type: FileChangeType::Renamed(old_path),
+                new_record: Some(new_record),
+                old_record: Some(old_record),
+            }
+        }
+
         /// Get a human-readable description of this change
         pub fn description(&self) -> String {
             match &self.change_type {
@@ -346,12 +1235,85 @@ pub mod changes {
         }
     }
 
+    /// Minimum Jaccard similarity between two files' chunk-hash sets for a
+    /// delete+add pair to be collapsed into a rename instead of reported as
+    /// two separate changes
+    const RENAME_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+    /// Jaccard similarity between two chunk-hash sets (`|A ∩ B| / |A ∪ B|`) -
+    /// 1.0 for a byte-identical move, 0.0 when nothing is shared
+    fn chunk_similarity(a: &[String], b: &[String]) -> f64 {
+        if a.is_empty() && b.is_empty() {
+            return 0.0;
+        }
+
+        let a_set: HashSet<&String> = a.iter().collect();
+        let b_set: HashSet<&String> = b.iter().collect();
+        let intersection = a_set.intersection(&b_set).count();
+        let union = a_set.union(&b_set).count();
+
+        intersection as f64 / union as f64
+    }
+
+    /// Pair up added and deleted records that are likely the same file moved
+    /// or renamed, by best Jaccard similarity of their chunk-hash sets.
+    /// Candidates are bucketed by size first - only a delete and an add of
+    /// the same size are ever compared, so a move that also changed the
+    /// file's size is reported as a delete plus an add - which keeps this
+    /// close to linear instead of comparing every delete against every add.
+    fn match_renames(
+        added: &[FileRecord],
+        deleted: &[FileRecord],
+    ) -> (HashSet<usize>, HashSet<usize>, Vec<FileChange>) {
+        let mut deleted_by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, record) in deleted.iter().enumerate() {
+            deleted_by_size.entry(record.size).or_default().push(i);
+        }
+
+        let mut matched_added = HashSet::new();
+        let mut matched_deleted = HashSet::new();
+        let mut renames = Vec::new();
+
+        for (added_idx, new_record) in added.iter().enumerate() {
+            let Some(candidates) = deleted_by_size.get(&new_record.size) else {
+                continue;
+            };
+
+            let mut best: Option<(usize, f64)> = None;
+            for &deleted_idx in candidates {
+                if matched_deleted.contains(&deleted_idx) {
+                    continue;
+                }
+
+                let similarity = chunk_similarity(&deleted[deleted_idx].chunks, &new_record.chunks);
+                if similarity >= RENAME_SIMILARITY_THRESHOLD
+                    && best.map_or(true, |(_, best_sim)| similarity > best_sim)
+                {
+                    best = Some((deleted_idx, similarity));
+                }
+            }
+
+            if let Some((deleted_idx, _)) = best {
+                matched_added.insert(added_idx);
+                matched_deleted.insert(deleted_idx);
+                renames.push(FileChange::renamed(
+                    deleted[deleted_idx].clone(),
+                    new_record.clone(),
+                ));
+            }
+        }
+
+        (matched_added, matched_deleted, renames)
+    }
+
     /// Detect changes between two sets of file records
     pub fn detect_changes(
         old_records: &HashMap<String, FileRecord>,
         new_records: &HashMap<String, FileRecord>,
     ) -> Vec<FileChange> {
         let mut changes = Vec::new();
+        let mut added = Vec::new();
+        let mut deleted = Vec::new();
 
         // Find additions and modifications
         for (path, new_record) in new_records {
@@ -360,14 +1322,29 @@ pub mod changes {
                     changes.push(FileChange::modified(old_record.clone(), new_record.clone()));
                 }
             } else {
-                changes.push(FileChange::added(new_record.clone()));
+                added.push(new_record.clone());
             }
         }
 
         // Find deletions
         for (path, old_record) in old_records {
             if !new_records.contains_key(path) {
-                changes.push(FileChange::deleted(old_record.clone()));
+                deleted.push(old_record.clone());
+            }
+        }
+
+        let (matched_added, matched_deleted, renames) = match_renames(&added, &deleted);
+        changes.extend(renames);
+
+        for (i, record) in added.into_iter().enumerate() {
+            if !matched_added.contains(&i) {
+                changes.push(FileChange::added(record));
+            }
+        }
+
+        for (i, record) in deleted.into_iter().enumerate() {
+            if !matched_deleted.contains(&i) {
+                changes.push(FileChange::deleted(record));
             }
         }
 
@@ -380,7 +1357,6 @@ mod tests {
     use super::*;
     use crate::utils::current_timestamp;
     use std::io::Write;
-    use std::path::PathBuf;
     use tempfile::TempDir;
 
     fn create_test_file(dir: &Path, name: &str, content: &[u8]) -> PathBuf {
@@ -405,6 +1381,41 @@ mod tests {
         assert_eq!(record.size, 13);
     }
 
+    #[test]
+    fn test_file_record_from_path_detects_symlink() {
+        let temp_dir = TempDir::new().unwrap();
+        let target_path = create_test_file(temp_dir.path(), "target.txt", b"Hello, world!");
+        let link_path = temp_dir.path().join("link.txt");
+        std::os::unix::fs::symlink(&target_path, &link_path).unwrap();
+
+        let record =
+            FileRecord::from_path(&link_path, &temp_dir.path().to_path_buf(), Vec::new())
+                .unwrap();
+
+        assert_eq!(
+            record.kind,
+            FileKind::Symlink {
+                target: target_path.to_string_lossy().to_string()
+            }
+        );
+        assert!(record.chunks.is_empty());
+    }
+
+    #[test]
+    fn test_is_different_from_disk_detects_kind_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = create_test_file(temp_dir.path(), "test.txt", b"Hello, world!");
+
+        let record =
+            FileRecord::from_path(&file_path, &temp_dir.path().to_path_buf(), Vec::new())
+                .unwrap();
+
+        std::fs::remove_file(&file_path).unwrap();
+        std::os::unix::fs::symlink("/somewhere", &file_path).unwrap();
+
+        assert!(record.is_different_from_disk(temp_dir.path()).unwrap());
+    }
+
     #[test]
     fn test_chunk_computation() {
         let data = b"Hello, world!";
@@ -426,6 +1437,188 @@ mod tests {
         assert_eq!(reconstructed, content);
     }
 
+    #[test]
+    fn test_chunk_files_returns_one_entry_per_path_in_byte_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let small = create_test_file(temp_dir.path(), "small.txt", b"tiny");
+        let big_content: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let big = create_test_file(temp_dir.path(), "big.bin", &big_content);
+
+        let paths = vec![small.clone(), big.clone()];
+        let result = chunk_files(&paths, 4, 1024).unwrap();
+
+        assert_eq!(result.len(), 2);
+
+        let small_chunks = &result[&small.to_string_lossy().to_string()];
+        assert_eq!(reconstruct_file_from_chunks(small_chunks), b"tiny");
+
+        let big_chunks = &result[&big.to_string_lossy().to_string()];
+        assert_eq!(reconstruct_file_from_chunks(big_chunks), big_content);
+        assert!(big_chunks.len() > 1); // split across multiple spans
+    }
+
+    #[test]
+    fn test_chunk_file_with_algo_hashes_with_selected_algorithm() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = b"Hello, world! This is a test file.";
+        let file_path = create_test_file(temp_dir.path(), "test.txt", content);
+
+        let chunks = chunk_file_with_algo(&file_path, HashAlgo::Xxh3).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].hash.len(), 16);
+        assert!(chunks[0].verify_with_algo(HashAlgo::Xxh3));
+    }
+
+    #[test]
+    fn test_chunk_file_with_config_honors_a_non_default_chunking_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        let content: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let file_path = create_test_file(temp_dir.path(), "big.bin", &content);
+
+        let fixed = ChunkingConfig {
+            strategy: ChunkingStrategy::Fixed,
+            avg_size: 4096,
+            min_size: 1,
+            max_size: usize::MAX,
+        };
+
+        let chunks = chunk_file_with_config(&file_path, HashAlgo::default(), &fixed).unwrap();
+        assert_eq!(reconstruct_file_from_chunks(&chunks), content);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(chunk.data.len(), fixed.avg_size);
+        }
+    }
+
+    #[test]
+    fn test_fastcdc_survives_insertion() {
+        // Content-defined chunking should keep most chunk hashes unchanged
+        // after a small insertion, unlike fixed-size offset chunking.
+        let mut rng_state = 0x1234_5678_u64;
+        let mut next_byte = || {
+            rng_state = rng_state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (rng_state >> 56) as u8
+        };
+        let original: Vec<u8> = (0..200_000).map(|_| next_byte()).collect();
+
+        let mut shifted = original[..100_000].to_vec();
+        shifted.extend_from_slice(b"this is an inserted prefix that shifts every later byte");
+        shifted.extend_from_slice(&original[100_000..]);
+
+        let config = ChunkingConfig::default();
+        let original_chunks: std::collections::HashSet<_> = fastcdc_boundaries(&original, &config)
+            .into_iter()
+            .map(|(start, len)| original[start..start + len].to_vec())
+            .collect();
+        let shifted_chunks: std::collections::HashSet<_> = fastcdc_boundaries(&shifted, &config)
+            .into_iter()
+            .map(|(start, len)| shifted[start..start + len].to_vec())
+            .collect();
+
+        let shared = original_chunks.intersection(&shifted_chunks).count();
+        assert!(
+            shared > 0,
+            "expected at least some chunks to survive the insertion unchanged"
+        );
+    }
+
+    #[test]
+    fn test_fastcdc_respects_size_bounds() {
+        let mut rng_state = 0x9999_aaaa_u64;
+        let mut next_byte = || {
+            rng_state = rng_state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (rng_state >> 56) as u8
+        };
+        let data: Vec<u8> = (0..500_000).map(|_| next_byte()).collect();
+
+        let config = ChunkingConfig::default();
+        let boundaries = fastcdc_boundaries(&data, &config);
+        let total: usize = boundaries.iter().map(|(_, len)| *len).sum();
+        assert_eq!(total, data.len());
+
+        for (i, (_, len)) in boundaries.iter().enumerate() {
+            assert!(*len <= config.max_size);
+            if i + 1 < boundaries.len() {
+                // Only the final chunk may be shorter than the minimum.
+                assert!(*len >= config.min_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fastcdc_honors_custom_chunking_config() {
+        let mut rng_state = 0x2024_cafe_u64;
+        let mut next_byte = || {
+            rng_state = rng_state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (rng_state >> 56) as u8
+        };
+        let data: Vec<u8> = (0..200_000).map(|_| next_byte()).collect();
+
+        let config = ChunkingConfig {
+            strategy: ChunkingStrategy::FastCdc,
+            avg_size: 1024,
+            min_size: 256,
+            max_size: 4096,
+        };
+        let boundaries = fastcdc_boundaries(&data, &config);
+
+        assert!(boundaries.len() > 1);
+        for (i, (_, len)) in boundaries.iter().enumerate() {
+            assert!(*len <= config.max_size);
+            if i + 1 < boundaries.len() {
+                assert!(*len >= config.min_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_strategy_cuts_uniform_chunks_regardless_of_content() {
+        let data = vec![0u8; 10_000];
+        let config = ChunkingConfig {
+            strategy: ChunkingStrategy::Fixed,
+            avg_size: 4096,
+            min_size: 1,
+            max_size: usize::MAX,
+        };
+
+        let boundaries = chunk_boundaries(&data, &config);
+
+        assert_eq!(boundaries, vec![(0, 4096), (4096, 4096), (8192, 1808)]);
+    }
+
+    #[test]
+    fn test_fixed_strategy_insertion_shifts_every_later_boundary() {
+        // Unlike FastCDC, fixed-size chunking has no chunk in common once an
+        // insertion shifts the byte offsets.
+        let original: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut shifted = vec![0u8; 7];
+        shifted.extend_from_slice(&original);
+
+        let config = ChunkingConfig {
+            strategy: ChunkingStrategy::Fixed,
+            avg_size: 1024,
+            min_size: 1,
+            max_size: usize::MAX,
+        };
+
+        let original_chunks: std::collections::HashSet<_> =
+            chunk_boundaries(&original, &config)
+                .into_iter()
+                .map(|(start, len)| original[start..start + len].to_vec())
+                .collect();
+        let shifted_chunks: std::collections::HashSet<_> = chunk_boundaries(&shifted, &config)
+            .into_iter()
+            .map(|(start, len)| shifted[start..start + len].to_vec())
+            .collect();
+
+        assert_eq!(original_chunks.intersection(&shifted_chunks).count(), 0);
+    }
+
     #[test]
     fn test_file_chunk_verification() {
         let data = vec![1, 2, 3, 4, 5];
@@ -437,6 +1630,86 @@ mod tests {
         assert!(!invalid_chunk.verify());
     }
 
+    #[test]
+    fn test_hash_algo_marker_roundtrip() {
+        assert_eq!(HashAlgo::Blake3.marker(), "blake3");
+        assert_eq!(HashAlgo::Blake2b.marker(), "blake2b");
+        assert_eq!(HashAlgo::Sha256.marker(), "sha256");
+        assert_eq!(HashAlgo::Xxh3.marker(), "xxh3");
+        assert_eq!(HashAlgo::Crc32.marker(), "crc32");
+        assert!(matches!(
+            HashAlgo::from_marker("blake3").unwrap(),
+            HashAlgo::Blake3
+        ));
+        assert!(matches!(
+            HashAlgo::from_marker("blake2b").unwrap(),
+            HashAlgo::Blake2b
+        ));
+        assert!(matches!(
+            HashAlgo::from_marker("sha256").unwrap(),
+            HashAlgo::Sha256
+        ));
+        assert!(matches!(
+            HashAlgo::from_marker("xxh3").unwrap(),
+            HashAlgo::Xxh3
+        ));
+        assert!(matches!(
+            HashAlgo::from_marker("crc32").unwrap(),
+            HashAlgo::Crc32
+        ));
+        assert!(HashAlgo::from_marker("unknown").is_err());
+    }
+
+    #[test]
+    fn test_compute_chunk_hash_with() {
+        let data = b"Hello, world!";
+        assert_eq!(
+            compute_chunk_hash_with(HashAlgo::Blake3, data),
+            compute_chunk_hash(data)
+        );
+
+        let blake2b_hash = compute_chunk_hash_with(HashAlgo::Blake2b, data);
+        assert_eq!(blake2b_hash.len(), 128); // blake2b-512 produces 512-bit hashes (128 hex chars)
+
+        let sha256_hash = compute_chunk_hash_with(HashAlgo::Sha256, data);
+        assert_eq!(sha256_hash.len(), 64); // sha256 produces 256-bit hashes (64 hex chars)
+
+        let xxh3_hash = compute_chunk_hash_with(HashAlgo::Xxh3, data);
+        assert_eq!(xxh3_hash.len(), 16); // xxh3_64 produces 64-bit hashes (16 hex chars)
+
+        let crc32_hash = compute_chunk_hash_with(HashAlgo::Crc32, data);
+        assert_eq!(crc32_hash.len(), 8); // crc32 produces 32-bit hashes (8 hex chars)
+    }
+
+    #[test]
+    fn test_crc32_matches_known_vector() {
+        // "123456789" is the standard CRC-32/ISO-HDLC test vector (0xCBF43926)
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_chunk_hasher_streaming_matches_one_shot() {
+        let data = b"the quick brown fox jumps over the lazy dog";
+
+        for algo in [HashAlgo::Blake3, HashAlgo::Xxh3, HashAlgo::Crc32] {
+            let mut streamed = algo.hasher();
+            streamed.update(&data[..10]);
+            streamed.update(&data[10..]);
+
+            assert_eq!(streamed.finalize(), compute_chunk_hash_with(algo, data));
+        }
+    }
+
+    #[test]
+    fn test_file_chunk_new_with_algo_threads_through() {
+        let data = b"xxh3 chunk data".to_vec();
+        let chunk = FileChunk::new_with_algo(data.clone(), HashAlgo::Xxh3);
+
+        assert_eq!(chunk.hash, compute_chunk_hash_with(HashAlgo::Xxh3, &data));
+        assert!(chunk.verify_with_algo(HashAlgo::Xxh3));
+        assert!(!chunk.verify_with_algo(HashAlgo::Blake3));
+    }
+
     #[test]
     fn test_file_stats() {
         let mut stats = FileStats::new();
@@ -448,6 +1721,10 @@ mod tests {
             mtime: current_timestamp(),
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
         };
 
         let record2 = FileRecord {
@@ -457,6 +1734,10 @@ mod tests {
             mtime: current_timestamp(),
             permissions: 0o755,
             is_executable: true,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
         };
 
         stats.add_file(&record1);
@@ -481,6 +1762,10 @@ mod tests {
             mtime: 1000,
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
         };
 
         let record1_modified = FileRecord {
@@ -490,6 +1775,10 @@ mod tests {
             mtime: 2000,
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
         };
 
         let record2 = FileRecord {
@@ -499,15 +1788,32 @@ mod tests {
             mtime: 1000,
             permissions: 0o644,
             is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let record3 = FileRecord {
+            path: "file3.txt".to_string(),
+            chunks: vec!["hash3".to_string()],
+            size: 50,
+            mtime: 1000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
         };
 
         // Old state: file1, file2
         old_records.insert("file1.txt".to_string(), record1.clone());
         old_records.insert("file2.txt".to_string(), record2.clone());
 
-        // New state: file1 (modified), file3 (new)
+        // New state: file1 (modified), file3 (new, unrelated content to file2)
         new_records.insert("file1.txt".to_string(), record1_modified.clone());
-        new_records.insert("file3.txt".to_string(), record2.clone());
+        new_records.insert("file3.txt".to_string(), record3.clone());
 
         let changes = changes::detect_changes(&old_records, &new_records);
 
@@ -519,4 +1825,122 @@ mod tests {
         assert!(change_types.contains(&&changes::FileChangeType::Added));
         assert!(change_types.contains(&&changes::FileChangeType::Deleted));
     }
+
+    #[test]
+    fn test_detect_changes_collapses_exact_move_into_rename() {
+        let record = FileRecord {
+            path: "old/path.txt".to_string(),
+            chunks: vec!["hash1".to_string(), "hash2".to_string()],
+            size: 2000,
+            mtime: 1000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let mut moved = record.clone();
+        moved.path = "new/path.txt".to_string();
+
+        let mut old_records = HashMap::new();
+        old_records.insert(record.path.clone(), record);
+        let mut new_records = HashMap::new();
+        new_records.insert(moved.path.clone(), moved);
+
+        let changes = changes::detect_changes(&old_records, &new_records);
+
+        assert_eq!(changes.len(), 1);
+        match &changes[0].change_type {
+            changes::FileChangeType::Renamed(old_path) => assert_eq!(old_path, "old/path.txt"),
+            other => panic!("expected a rename, got {:?}", other),
+        }
+        assert_eq!(changes[0].path, "new/path.txt");
+    }
+
+    #[test]
+    fn test_detect_changes_collapses_partial_match_above_threshold() {
+        // Same size, 2 of 3 chunks shared: Jaccard similarity = 2/4 = 0.5
+        let old_record = FileRecord {
+            path: "old.txt".to_string(),
+            chunks: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            size: 3000,
+            mtime: 1000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let new_record = FileRecord {
+            path: "new.txt".to_string(),
+            chunks: vec!["a".to_string(), "b".to_string(), "d".to_string()],
+            size: 3000,
+            mtime: 2000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let mut old_records = HashMap::new();
+        old_records.insert(old_record.path.clone(), old_record);
+        let mut new_records = HashMap::new();
+        new_records.insert(new_record.path.clone(), new_record);
+
+        let changes = changes::detect_changes(&old_records, &new_records);
+
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(
+            changes[0].change_type,
+            changes::FileChangeType::Renamed(_)
+        ));
+    }
+
+    #[test]
+    fn test_detect_changes_leaves_dissimilar_same_size_files_as_add_and_delete() {
+        // Same size, no shared chunks at all: similarity stays 0.0, below threshold
+        let old_record = FileRecord {
+            path: "old.txt".to_string(),
+            chunks: vec!["a".to_string()],
+            size: 1000,
+            mtime: 1000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let new_record = FileRecord {
+            path: "new.txt".to_string(),
+            chunks: vec!["z".to_string()],
+            size: 1000,
+            mtime: 2000,
+            permissions: 0o644,
+            is_executable: false,
+            partial_hash: "deadbeef".to_string(),
+            full_hash: None,
+            kind: FileKind::Regular,
+            xattrs: BTreeMap::new(),
+        };
+
+        let mut old_records = HashMap::new();
+        old_records.insert(old_record.path.clone(), old_record);
+        let mut new_records = HashMap::new();
+        new_records.insert(new_record.path.clone(), new_record);
+
+        let changes = changes::detect_changes(&old_records, &new_records);
+
+        assert_eq!(changes.len(), 2);
+        let change_types: Vec<_> = changes.iter().map(|c| &c.change_type).collect();
+        assert!(change_types.contains(&&changes::FileChangeType::Added));
+        assert!(change_types.contains(&&changes::FileChangeType::Deleted));
+    }
 }
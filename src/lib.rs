@@ -5,14 +5,21 @@
 //! Blaze uses advanced chunking algorithms and parallel processing to provide lightning-fast
 //! version control operations while maintaining data integrity and ease of use.
 
+pub mod cache;
 pub mod chunks;
 pub mod cli;
 pub mod config;
 pub mod core;
 pub mod database;
+pub mod dirstate;
 pub mod errors;
 pub mod files;
+pub mod messages;
+pub mod rate_limit;
+pub mod settings;
+pub mod storage;
 pub mod utils;
+pub mod watcher;
 
 // Re-export main types for convenience
 pub use crate::cli::{Cli, Commands};
@@ -20,6 +27,7 @@ pub use crate::config::*;
 pub use crate::core::Blaze;
 pub use crate::errors::{BlazeError, Result};
 pub use crate::files::FileRecord;
+pub use crate::settings::Settings;
 
 /// Initialize and run the Blaze CLI
 pub fn run() -> Result<()> {
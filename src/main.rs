@@ -4,7 +4,6 @@
 //! The actual implementation is split across multiple modules for better
 //! organization and maintainability.
 
-use std::error::Error;
 use std::process;
 
 fn main() {
@@ -29,15 +28,7 @@ fn main() {
 
     // Run the CLI application
     if let Err(error) = blaze::run() {
-        eprintln!("💥 Error: {}", error);
-
-        // Show additional context for certain error types
-        let mut source = error.source();
-        while let Some(err) = source {
-            eprintln!("   Caused by: {}", err);
-            source = err.source();
-        }
-
-        process::exit(1);
+        eprintln!("💥 Error: {}", error.report());
+        process::exit(error.exit_code());
     }
 }
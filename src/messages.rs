@@ -0,0 +1,303 @@
+//! Localization layer for Blaze's CLI output.
+//!
+//! User-facing strings are looked up by key through [`msg`] and [`msg_n`]
+//! instead of being written as literals in `run()`, so a translation only
+//! has to be added to the catalogs below rather than hunted down across
+//! every command's match arm. The active language is resolved once from
+//! `BLAZE_LANG` (falling back to `LANG`, then English) by [`Lang::current`].
+//!
+//! Pluralization is per-language rather than the English-only `"" / "s"`
+//! pattern: each catalog entry that varies by count is a list of plural
+//! forms in CLDR order, and [`plural_index`] picks which form applies.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+/// A supported UI language. Add a variant, a `plural_index` arm, and a
+/// catalog to support another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Es,
+    Fr,
+}
+
+impl Lang {
+    /// Resolve the active language from `BLAZE_LANG`, then `LANG`, falling
+    /// back to English. Only the leading subtag is matched, so
+    /// `LANG=es_ES.UTF-8` still selects Spanish.
+    pub fn current() -> Lang {
+        let raw = env::var("BLAZE_LANG")
+            .or_else(|_| env::var("LANG"))
+            .unwrap_or_default();
+        let tag = raw
+            .split(|c| c == '_' || c == '.')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        match tag.as_str() {
+            "es" => Lang::Es,
+            "fr" => Lang::Fr,
+            _ => Lang::En,
+        }
+    }
+}
+
+/// A message's translations: a single string, or a set of plural forms
+/// selected by [`plural_index`]
+enum Entry {
+    One(&'static str),
+    Plural(&'static [&'static str]),
+}
+
+/// Index into a language's `Plural` form list for `count`, in CLDR order.
+/// English, Spanish and French all use a two-form "one / other" split here
+/// (French additionally treats 0 as singular); languages with richer plural
+/// systems (Slavic "few", Arabic "many", ...) just need more forms and a
+/// matching arm.
+fn plural_index(lang: Lang, count: i64) -> usize {
+    match lang {
+        Lang::En | Lang::Es => {
+            if count == 1 {
+                0
+            } else {
+                1
+            }
+        }
+        Lang::Fr => {
+            if count == 0 || count == 1 {
+                0
+            } else {
+                1
+            }
+        }
+    }
+}
+
+fn catalog(lang: Lang) -> &'static HashMap<&'static str, Entry> {
+    static EN: OnceLock<HashMap<&'static str, Entry>> = OnceLock::new();
+    static ES: OnceLock<HashMap<&'static str, Entry>> = OnceLock::new();
+    static FR: OnceLock<HashMap<&'static str, Entry>> = OnceLock::new();
+
+    match lang {
+        Lang::En => EN.get_or_init(|| {
+            HashMap::from([
+                ("init.start", Entry::One("🔥 Initializing Blaze repository in '{}'")),
+                ("init.done", Entry::One("✅ Blaze repository initialized successfully!")),
+                (
+                    "add.result",
+                    Entry::Plural(&["✅ Added {} file", "✅ Added {} files"]),
+                ),
+                ("commit.start", Entry::One("📝 Creating commit...")),
+                ("commit.done", Entry::One("✅ Created commit: {}")),
+                (
+                    "verify.issues",
+                    Entry::Plural(&["⚠️  Found {} issue", "⚠️  Found {} issues"]),
+                ),
+                (
+                    "rebase.result",
+                    Entry::Plural(&["✅ Rebased {} change", "✅ Rebased {} changes"]),
+                ),
+                ("status.head_orphaned", Entry::One("HEAD is orphaned - run 'blaze rebase' to re-parent it")),
+                ("status.changes_to_be_committed", Entry::One("Changes to be committed:")),
+                ("status.changes_not_staged", Entry::One("Changes not staged for commit:")),
+                ("status.untracked_files", Entry::One("Untracked files:")),
+                ("status.ignored_files", Entry::One("Ignored files:")),
+                ("status.nothing_to_commit", Entry::One("nothing to commit, working tree clean")),
+                ("time.just_now", Entry::One("just now")),
+                ("time.in_future", Entry::One("in the future")),
+                ("time.minutes_ago", Entry::Plural(&["{} minute ago", "{} minutes ago"])),
+                ("time.hours_ago", Entry::Plural(&["{} hour ago", "{} hours ago"])),
+                ("time.days_ago", Entry::Plural(&["{} day ago", "{} days ago"])),
+            ])
+        }),
+        Lang::Es => ES.get_or_init(|| {
+            HashMap::from([
+                (
+                    "init.start",
+                    Entry::One("🔥 Inicializando repositorio Blaze en '{}'"),
+                ),
+                (
+                    "init.done",
+                    Entry::One("✅ ¡Repositorio Blaze inicializado correctamente!"),
+                ),
+                (
+                    "add.result",
+                    Entry::Plural(&["✅ Se añadió {} archivo", "✅ Se añadieron {} archivos"]),
+                ),
+                ("commit.start", Entry::One("📝 Creando commit...")),
+                ("commit.done", Entry::One("✅ Commit creado: {}")),
+                (
+                    "verify.issues",
+                    Entry::Plural(&["⚠️  Se encontró {} problema", "⚠️  Se encontraron {} problemas"]),
+                ),
+                (
+                    "rebase.result",
+                    Entry::Plural(&["✅ Se rebasó {} cambio", "✅ Se rebasaron {} cambios"]),
+                ),
+                ("status.head_orphaned", Entry::One("HEAD está huérfano - ejecute 'blaze rebase' para volver a emparentarlo")),
+                ("status.changes_to_be_committed", Entry::One("Cambios a confirmar:")),
+                ("status.changes_not_staged", Entry::One("Cambios sin preparar para confirmar:")),
+                ("status.untracked_files", Entry::One("Archivos sin seguimiento:")),
+                ("status.ignored_files", Entry::One("Archivos ignorados:")),
+                ("status.nothing_to_commit", Entry::One("nada para confirmar, árbol de trabajo limpio")),
+                ("time.just_now", Entry::One("justo ahora")),
+                ("time.in_future", Entry::One("en el futuro")),
+                ("time.minutes_ago", Entry::Plural(&["hace {} minuto", "hace {} minutos"])),
+                ("time.hours_ago", Entry::Plural(&["hace {} hora", "hace {} horas"])),
+                ("time.days_ago", Entry::Plural(&["hace {} día", "hace {} días"])),
+            ])
+        }),
+        Lang::Fr => FR.get_or_init(|| {
+            HashMap::from([
+                (
+                    "init.start",
+                    Entry::One("🔥 Initialisation du dépôt Blaze dans '{}'"),
+                ),
+                (
+                    "init.done",
+                    Entry::One("✅ Dépôt Blaze initialisé avec succès !"),
+                ),
+                (
+                    "add.result",
+                    Entry::Plural(&["✅ {} fichier ajouté", "✅ {} fichiers ajoutés"]),
+                ),
+                ("commit.start", Entry::One("📝 Création du commit...")),
+                ("commit.done", Entry::One("✅ Commit créé : {}")),
+                (
+                    "verify.issues",
+                    Entry::Plural(&["⚠️  {} problème trouvé", "⚠️  {} problèmes trouvés"]),
+                ),
+                (
+                    "rebase.result",
+                    Entry::Plural(&["✅ {} changement rebasé", "✅ {} changements rebasés"]),
+                ),
+                ("status.head_orphaned", Entry::One("HEAD est orpheline - exécutez 'blaze rebase' pour la rattacher")),
+                ("status.changes_to_be_committed", Entry::One("Modifications qui seront validées :")),
+                ("status.changes_not_staged", Entry::One("Modifications non indexées pour le commit :")),
+                ("status.untracked_files", Entry::One("Fichiers non suivis :")),
+                ("status.ignored_files", Entry::One("Fichiers ignorés :")),
+                ("status.nothing_to_commit", Entry::One("rien à valider, l'arbre de travail est propre")),
+                ("time.just_now", Entry::One("à l'instant")),
+                ("time.in_future", Entry::One("dans le futur")),
+                ("time.minutes_ago", Entry::Plural(&["il y a {} minute", "il y a {} minutes"])),
+                ("time.hours_ago", Entry::Plural(&["il y a {} heure", "il y a {} heures"])),
+                ("time.days_ago", Entry::Plural(&["il y a {} jour", "il y a {} jours"])),
+            ])
+        }),
+    }
+}
+
+/// Substitute `{}` placeholders in `template` with `args`, in order
+fn interpolate(template: &str, args: &[&str]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '{' && chars.peek() == Some(&'}') {
+            chars.next();
+            if let Some(arg) = args.next() {
+                result.push_str(arg);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Look up a non-pluralized message by key in the active language, falling
+/// back to English if the key is missing from that catalog, and substitute
+/// `args` for its `{}` placeholders in order
+pub fn msg(key: &str, args: &[&str]) -> String {
+    let lang = Lang::current();
+    let entry = catalog(lang)
+        .get(key)
+        .or_else(|| catalog(Lang::En).get(key));
+
+    match entry {
+        Some(Entry::One(template)) => interpolate(template, args),
+        Some(Entry::Plural(forms)) => interpolate(forms[0], args),
+        None => key.to_string(),
+    }
+}
+
+/// Look up a pluralized message by key, selecting the form appropriate for
+/// `count` in the active language, and substitute `args` (count first, then
+/// any extra args) for its `{}` placeholders
+pub fn msg_n(key: &str, count: i64, args: &[&str]) -> String {
+    let lang = Lang::current();
+    let entry = catalog(lang)
+        .get(key)
+        .or_else(|| catalog(Lang::En).get(key));
+
+    let count_str = count.to_string();
+    let mut all_args = Vec::with_capacity(args.len() + 1);
+    all_args.push(count_str.as_str());
+    all_args.extend_from_slice(args);
+
+    match entry {
+        Some(Entry::Plural(forms)) => {
+            let index = plural_index(lang, count).min(forms.len() - 1);
+            interpolate(forms[index], &all_args)
+        }
+        Some(Entry::One(template)) => interpolate(template, &all_args),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(interpolate("Added {} file{}", &["3", "s"]), "Added 3 files");
+        assert_eq!(interpolate("no placeholders", &[]), "no placeholders");
+    }
+
+    #[test]
+    fn test_plural_index_two_form_languages() {
+        assert_eq!(plural_index(Lang::En, 1), 0);
+        assert_eq!(plural_index(Lang::En, 0), 1);
+        assert_eq!(plural_index(Lang::En, 2), 1);
+        assert_eq!(plural_index(Lang::Es, 1), 0);
+        assert_eq!(plural_index(Lang::Es, 5), 1);
+    }
+
+    #[test]
+    fn test_plural_index_french_treats_zero_as_singular() {
+        assert_eq!(plural_index(Lang::Fr, 0), 0);
+        assert_eq!(plural_index(Lang::Fr, 1), 0);
+        assert_eq!(plural_index(Lang::Fr, 2), 1);
+    }
+
+    #[test]
+    fn test_every_catalog_has_the_same_keys() {
+        let en_keys: std::collections::HashSet<_> = catalog(Lang::En).keys().collect();
+        for lang in [Lang::Es, Lang::Fr] {
+            let keys: std::collections::HashSet<_> = catalog(lang).keys().collect();
+            assert_eq!(keys, en_keys, "{:?} catalog is missing or has extra keys", lang);
+        }
+    }
+
+    #[test]
+    fn test_msg_and_msg_n_format_the_english_catalog() {
+        env::set_var("BLAZE_LANG", "en");
+        assert_eq!(msg("commit.done", &["abc123"]), "✅ Created commit: abc123");
+        assert_eq!(msg_n("add.result", 1, &[]), "✅ Added 1 file");
+        assert_eq!(msg_n("add.result", 3, &[]), "✅ Added 3 files");
+        env::remove_var("BLAZE_LANG");
+    }
+
+    #[test]
+    fn test_unknown_lang_tag_falls_back_to_english() {
+        env::set_var("BLAZE_LANG", "xx_YY");
+        assert_eq!(Lang::current(), Lang::En);
+        env::remove_var("BLAZE_LANG");
+    }
+}
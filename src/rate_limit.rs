@@ -0,0 +1,155 @@
+//! Shared token-bucket throughput limiter for background I/O
+//!
+//! Large commits let chunk writing, compression, and bundle compaction
+//! consume all available disk bandwidth, which makes `blaze` disruptive to
+//! run alongside other work on the same machine. [`RateLimiter`] caps
+//! aggregate throughput to a configured bytes-per-second budget, refilling
+//! its bucket once per [`PROGRESS_REFRESH_RATE`] tick so throttling shows up
+//! as smooth backpressure instead of bursty stalls.
+
+use crate::config::PROGRESS_REFRESH_RATE;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Token-bucket limiter meant to be shared (typically via `Arc`) across every
+/// writer drawing from the same throughput budget.
+pub struct RateLimiter {
+    rate_bytes_per_sec: u64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter capped at `rate_bytes_per_sec` bytes/sec. The bucket
+    /// starts full so the first write after opening a store isn't penalized.
+    pub fn new(rate_bytes_per_sec: u64) -> Self {
+        Self {
+            rate_bytes_per_sec,
+            bucket: Mutex::new(Bucket {
+                tokens: rate_bytes_per_sec as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// The configured throughput cap, in bytes/sec
+    pub fn rate_bytes_per_sec(&self) -> u64 {
+        self.rate_bytes_per_sec
+    }
+
+    /// Block the calling thread until `bytes` worth of budget is available,
+    /// then spend it. Used by foreground flush/write paths, which should
+    /// always make forward progress on a constrained budget.
+    ///
+    /// `bytes` may exceed a single bucket-full (e.g. a chunk or bundle larger
+    /// than `rate_bytes_per_sec`) - tokens are allowed to go into debt rather
+    /// than capping the wait at "one refill's worth", so a call like that
+    /// drains across as many refill cycles as it takes instead of spinning
+    /// forever because the bucket can never hold enough at once.
+    pub fn acquire(&self, bytes: usize) {
+        if self.rate_bytes_per_sec == 0 {
+            return; // 0 means unlimited
+        }
+
+        {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+            bucket.tokens -= bytes as f64;
+        }
+
+        loop {
+            let wait = {
+                let bucket = self.bucket.lock().unwrap();
+                if bucket.tokens >= 0.0 {
+                    None
+                } else {
+                    let shortfall = -bucket.tokens;
+                    Some(Duration::from_secs_f64(shortfall / self.rate_bytes_per_sec as f64))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => {
+                    thread::sleep(duration.min(Duration::from_millis(PROGRESS_REFRESH_RATE)));
+                    let mut bucket = self.bucket.lock().unwrap();
+                    self.refill(&mut bucket);
+                }
+            }
+        }
+    }
+
+    /// Like [`Self::acquire`], but for background maintenance (bundle
+    /// compaction, checkpointing): waits out one extra tick before competing
+    /// for tokens so foreground flush/write traffic gets first claim whenever
+    /// the budget is constrained.
+    pub fn acquire_background(&self, bytes: usize) {
+        if self.rate_bytes_per_sec == 0 {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(PROGRESS_REFRESH_RATE));
+        self.acquire(bytes);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill);
+        let refilled = elapsed.as_secs_f64() * self.rate_bytes_per_sec as f64;
+
+        if refilled > 0.0 {
+            bucket.tokens = (bucket.tokens + refilled).min(self.rate_bytes_per_sec as f64);
+            bucket.last_refill = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_rate_never_blocks() {
+        let limiter = RateLimiter::new(0);
+        // Should return immediately regardless of size.
+        limiter.acquire(usize::MAX);
+    }
+
+    #[test]
+    fn test_acquire_drains_the_bucket_and_then_waits_for_refill() {
+        let limiter = RateLimiter::new(1_000_000);
+        limiter.acquire(1_000_000); // empty the bucket
+
+        // Refilling 1000 bytes at 1,000,000 bytes/sec takes ~1ms, so this
+        // has to block rather than returning instantly.
+        let start = Instant::now();
+        limiter.acquire(1_000);
+        assert!(start.elapsed() >= Duration::from_micros(500));
+    }
+
+    #[test]
+    fn test_acquire_larger_than_rate_completes_instead_of_spinning_forever() {
+        let limiter = RateLimiter::new(1_000_000);
+
+        // A single call for more than the whole per-second rate (e.g. one
+        // oversized chunk/bundle write) used to spin forever, since a capped
+        // `tokens` field could never reach `bytes`. It must now complete,
+        // going into debt and waiting out the remainder across refills.
+        let start = Instant::now();
+        limiter.acquire(1_200_000);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_background_acquire_waits_at_least_one_tick() {
+        let limiter = RateLimiter::new(u64::MAX);
+        let start = Instant::now();
+        limiter.acquire_background(1);
+        assert!(start.elapsed() >= Duration::from_millis(PROGRESS_REFRESH_RATE));
+    }
+}
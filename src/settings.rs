@@ -0,0 +1,857 @@
+//! Layered, file-based tuning configuration for Blaze.
+//!
+//! Every knob in [`crate::config`] used to be a compile-time constant, so
+//! changing chunking, compression, or database behavior meant recompiling.
+//! This module discovers and parses `blaze.toml` files - a repo-local
+//! `.blaze/config.toml` overridden by a user-global one - and merges whatever
+//! they set over the defaults, producing a fully-resolved [`Settings`].
+//!
+//! Only keys actually present in a file are applied; anything omitted falls
+//! through to the layer below it, down to the constants in [`crate::config`].
+
+use crate::cli::UntrackedFiles;
+use crate::config::{
+    ChunkingConfig, ChunkingStrategy, CompressionAlgo, CompressionConfig, CompressionLevel,
+    DatabaseConfig, PerformanceConfig, StorageProfile, BINARY_EXTENSIONS, BLAZE_DIR,
+    DEFAULT_IGNORE_PATTERNS,
+};
+use crate::errors::{BlazeError, Result};
+use crate::files::HashAlgo;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Name of the per-repo and user-global config file, under `.blaze/` in the
+/// repo and under the platform config directory globally
+pub(crate) const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Fully-resolved tuning knobs: defaults from [`crate::config`], layered with
+/// whatever the repo and user-global `blaze.toml` files override
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub performance: PerformanceConfig,
+    pub database: DatabaseConfig,
+    pub ignore_patterns: Vec<String>,
+    pub binary_extensions: Vec<String>,
+    /// How `status` displays untracked files when `--untracked-files` isn't
+    /// passed explicitly on the command line
+    pub default_untracked_files: UntrackedFiles,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            performance: PerformanceConfig::default(),
+            database: DatabaseConfig::default(),
+            ignore_patterns: DEFAULT_IGNORE_PATTERNS.iter().map(|s| s.to_string()).collect(),
+            binary_extensions: BINARY_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+            default_untracked_files: UntrackedFiles::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Resolve settings for a repository at `repo_path`: start from the
+    /// built-in defaults, apply `.blaze/config.toml` if present, then apply
+    /// the user-global config file on top of that, so a machine-wide policy
+    /// can still override a repo's own file.
+    pub fn discover<P: AsRef<Path>>(repo_path: P) -> Result<Self> {
+        let repo_path = repo_path.as_ref();
+        let mut settings = Settings::default();
+
+        let repo_config = load_file(&repo_path.join(BLAZE_DIR).join(CONFIG_FILE_NAME))?;
+        let global_config = match global_config_path() {
+            Some(path) => load_file(&path)?,
+            None => None,
+        };
+
+        // Resolve the storage profile and tune buffer/cache sizes for it
+        // before applying file overrides, so an explicit `cache_size` or
+        // `worker_threads` in a file still wins over the profile's guess.
+        // An explicit `storage_profile` in either file skips probing
+        // entirely; the global file wins over the repo file, same as every
+        // other key.
+        let requested_profile = repo_config
+            .as_ref()
+            .and_then(|c| c.database.storage_profile)
+            .into_iter()
+            .chain(global_config.as_ref().and_then(|c| c.database.storage_profile))
+            .last();
+        let profile = match requested_profile {
+            Some(StorageProfile::Auto) | None => detect_storage_profile(repo_path),
+            Some(profile) => profile,
+        };
+        settings.performance.tune_for_storage(profile);
+        settings.database.tune_for_storage(profile);
+
+        if let Some(repo_config) = repo_config {
+            repo_config.apply(&mut settings);
+        }
+        if let Some(global_config) = global_config {
+            global_config.apply(&mut settings);
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Probe the mount backing `repo_path` and resolve it to [`StorageProfile::Hdd`]
+/// or [`StorageProfile::Ssd`]. Falls back to `Ssd` on any platform other than
+/// Linux, or if anything about the probe fails - that's the right default for
+/// the overwhelming majority of laptops, desktops, and cloud instances today.
+fn detect_storage_profile(repo_path: &Path) -> StorageProfile {
+    #[cfg(target_os = "linux")]
+    {
+        linux::detect_storage_profile(repo_path).unwrap_or(StorageProfile::Ssd)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = repo_path;
+        StorageProfile::Ssd
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::StorageProfile;
+    use std::path::Path;
+
+    /// Probe `/proc/mounts` and `/sys/block` to figure out whether `repo_path`
+    /// sits on spinning or solid-state media. Returns `None` if any step of
+    /// the probe fails, leaving the fallback decision to the caller.
+    pub(super) fn detect_storage_profile(repo_path: &Path) -> Option<StorageProfile> {
+        let device = mount_device(repo_path)?;
+        let rotational = std::fs::read_to_string(format!(
+            "/sys/block/{}/queue/rotational",
+            block_device_name(&device)
+        ))
+        .ok()?;
+
+        match rotational.trim() {
+            "1" => Some(StorageProfile::Hdd),
+            "0" => Some(StorageProfile::Ssd),
+            _ => None,
+        }
+    }
+
+    /// Find the device backing the mount point that `path` lives under, by
+    /// reading `/proc/mounts` and taking the longest matching prefix. `path`
+    /// may not exist yet (e.g. before `.blaze/` is created), so we canonicalize
+    /// the nearest existing ancestor instead.
+    fn mount_device(path: &Path) -> Option<String> {
+        let canonical = existing_ancestor(path)?;
+        let contents = std::fs::read_to_string("/proc/mounts").ok()?;
+
+        let mut best: Option<(usize, String)> = None;
+        for line in contents.lines() {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = fields.next()?;
+
+            if !device.starts_with('/') {
+                continue; // skip pseudo filesystems (proc, tmpfs, cgroup, ...)
+            }
+            if canonical.starts_with(mount_point) {
+                let len = mount_point.len();
+                let better = match &best {
+                    Some((best_len, _)) => len > *best_len,
+                    None => true,
+                };
+                if better {
+                    best = Some((len, device.to_string()));
+                }
+            }
+        }
+
+        best.map(|(_, device)| device)
+    }
+
+    /// Walk up from `path` until we find an ancestor that actually exists, and
+    /// return its canonical form.
+    fn existing_ancestor(path: &Path) -> Option<std::path::PathBuf> {
+        let mut current = path;
+        loop {
+            if let Ok(canonical) = current.canonicalize() {
+                return Some(canonical);
+            }
+            current = current.parent()?;
+        }
+    }
+
+    /// Strip a partition suffix off a device path, since `rotational` is only
+    /// exposed under the whole-disk sysfs entry (`/dev/sda1` -> `sda`,
+    /// `/dev/nvme0n1p1` -> `nvme0n1`).
+    fn block_device_name(device: &str) -> String {
+        let name = device.trim_start_matches("/dev/");
+
+        if name.starts_with("nvme") {
+            if let Some(idx) = name.rfind('p') {
+                let suffix = &name[idx + 1..];
+                if !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit()) {
+                    return name[..idx].to_string();
+                }
+            }
+            // A whole-disk NVMe name's trailing digits are its namespace, not
+            // a partition
+            return name.to_string();
+        }
+
+        name.trim_end_matches(|c: char| c.is_ascii_digit()).to_string()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_block_device_name_strips_partition_suffix() {
+            assert_eq!(block_device_name("/dev/sda1"), "sda");
+            assert_eq!(block_device_name("/dev/sda"), "sda");
+            assert_eq!(block_device_name("/dev/nvme0n1p1"), "nvme0n1");
+        }
+    }
+}
+
+/// Where the user-global config file lives, platform-appropriately (e.g.
+/// `~/.config/blaze/config.toml` on Linux). `None` if the platform has no
+/// notion of a config directory.
+fn global_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(crate::config::app_info::NAME).join(CONFIG_FILE_NAME))
+}
+
+/// Parse `path` into a [`FileConfig`] if it exists, resolving its `include`
+/// directive and applying its `unset` directive along the way; a missing
+/// file is not an error (it just means this layer contributes nothing), but
+/// an existing file that fails to parse, or whose `include`s cycle back on
+/// themselves, is an error, since the user explicitly wrote it.
+fn load_file(path: &Path) -> Result<Option<FileConfig>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    load_file_resolving_includes(path, &mut HashSet::new())
+}
+
+/// Parse one file and recursively fold in whatever it `include`s, so a
+/// repo-local config can layer itself over a shared, team-wide file instead
+/// of duplicating it. Included files are lower priority than the file that
+/// includes them (and earlier includes lower than later ones), matching how
+/// `Settings::discover` already layers the repo file under the global one.
+/// `visited` guards against an `include` cycle.
+fn load_file_resolving_includes(path: &Path, visited: &mut HashSet<PathBuf>) -> Result<Option<FileConfig>> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(canonical.clone()) {
+        return Err(BlazeError::Config(format!(
+            "include cycle detected at {}",
+            path.display()
+        )));
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| BlazeError::Config(format!("failed to read {}: {}", path.display(), e)))?;
+    let config: FileConfig = toml::from_str(&contents)
+        .map_err(|e| BlazeError::Config(format!("failed to parse {}: {}", path.display(), e)))?;
+
+    let include_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut resolved = FileConfig::default();
+    for include in &config.include {
+        let include_path = include_dir.join(include);
+        if let Some(included) = load_file_resolving_includes(&include_path, visited)? {
+            resolved.overlay(&included);
+        }
+    }
+    resolved.overlay(&config);
+
+    for key in &config.unset {
+        resolved.unset(key);
+    }
+
+    // Only files still being resolved up the include chain form a cycle; a
+    // file reached again along a separate branch is not one
+    visited.remove(&canonical);
+    Ok(Some(resolved))
+}
+
+/// `blaze.toml`'s shape: every section and key is optional, so a file only
+/// needs to mention what it wants to change
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct FileConfig {
+    performance: PerformanceOverrides,
+    database: DatabaseOverrides,
+    status: StatusOverrides,
+    ignore_patterns: Option<Vec<String>>,
+    binary_extensions: Option<Vec<String>>,
+    /// Other config files to merge in first, as a lower-priority base,
+    /// resolved relative to this file's own directory - lets a team share one
+    /// `blaze.toml` that repo-local files layer their own overrides on top of
+    #[serde(default)]
+    include: Vec<String>,
+    /// Dotted key paths (e.g. `"performance.rate_limit"`) to reset back to
+    /// the built-in default after merging `include`s and this file's own
+    /// keys, so a repo file can opt back out of a setting a shared include
+    /// turned on
+    #[serde(default)]
+    unset: Vec<String>,
+}
+
+impl FileConfig {
+    fn apply(&self, settings: &mut Settings) {
+        self.performance.apply(&mut settings.performance);
+        self.database.apply(&mut settings.database);
+        self.status.apply(settings);
+
+        if let Some(patterns) = &self.ignore_patterns {
+            settings.ignore_patterns = patterns.clone();
+        }
+        if let Some(extensions) = &self.binary_extensions {
+            settings.binary_extensions = extensions.clone();
+        }
+    }
+
+    /// Overlay `other`'s explicitly-set keys on top of `self`, with `other`
+    /// winning wherever it has a value - used to fold a higher-priority file
+    /// over its lower-priority `include`s.
+    fn overlay(&mut self, other: &FileConfig) {
+        self.performance.overlay(&other.performance);
+        self.database.overlay(&other.database);
+        self.status.overlay(&other.status);
+        if other.ignore_patterns.is_some() {
+            self.ignore_patterns = other.ignore_patterns.clone();
+        }
+        if other.binary_extensions.is_some() {
+            self.binary_extensions = other.binary_extensions.clone();
+        }
+    }
+
+    /// Reset the field named by a dotted key path back to "not set", so it
+    /// falls through to whatever's underneath instead of the value an
+    /// `include` (or this same file) gave it.
+    fn unset(&mut self, key: &str) {
+        match key {
+            "performance.worker_threads" => self.performance.worker_threads = None,
+            "performance.read_buffer_size" => self.performance.read_buffer_size = None,
+            "performance.write_buffer_size" => self.performance.write_buffer_size = None,
+            "performance.use_memory_mapping" => self.performance.use_memory_mapping = None,
+            "performance.enable_compression" => self.performance.enable_compression = None,
+            "performance.hash_algo" => self.performance.hash_algo = None,
+            "performance.read_amplification_batch" => self.performance.read_amplification_batch = None,
+            "performance.rate_limit" => self.performance.rate_limit = None,
+            "performance.chunking.strategy" => self.performance.chunking.strategy = None,
+            "performance.chunking.avg_size" => self.performance.chunking.avg_size = None,
+            "performance.chunking.min_size" => self.performance.chunking.min_size = None,
+            "performance.chunking.max_size" => self.performance.chunking.max_size = None,
+            "performance.compression.algo" => self.performance.compression.algo = None,
+            "performance.compression.level" => self.performance.compression.level = None,
+            "performance.compression.min_size" => self.performance.compression.min_size = None,
+            "performance.compression.min_savings_ratio" => {
+                self.performance.compression.min_savings_ratio = None
+            }
+            "database.timeout" => self.database.timeout = None,
+            "database.enable_wal_mode" => self.database.enable_wal_mode = None,
+            "database.cache_size" => self.database.cache_size = None,
+            "database.enable_foreign_keys" => self.database.enable_foreign_keys = None,
+            "database.storage_profile" => self.database.storage_profile = None,
+            "database.background_threads" => self.database.background_threads = None,
+            "status.default_untracked_files" => self.status.default_untracked_files = None,
+            "ignore_patterns" => self.ignore_patterns = None,
+            "binary_extensions" => self.binary_extensions = None,
+            // An unrecognized key has nothing to unset; left as a no-op
+            // rather than an error so a typo'd `unset` entry doesn't take down an
+            // otherwise-valid config.
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct PerformanceOverrides {
+    worker_threads: Option<usize>,
+    read_buffer_size: Option<usize>,
+    write_buffer_size: Option<usize>,
+    use_memory_mapping: Option<bool>,
+    enable_compression: Option<bool>,
+    chunking: ChunkingOverrides,
+    compression: CompressionOverrides,
+    hash_algo: Option<HashAlgo>,
+    read_amplification_batch: Option<usize>,
+    rate_limit: Option<u64>,
+}
+
+impl PerformanceOverrides {
+    fn apply(&self, config: &mut PerformanceConfig) {
+        if let Some(v) = self.worker_threads {
+            config.worker_threads = v;
+        }
+        if let Some(v) = self.read_buffer_size {
+            config.read_buffer_size = v;
+        }
+        if let Some(v) = self.write_buffer_size {
+            config.write_buffer_size = v;
+        }
+        if let Some(v) = self.use_memory_mapping {
+            config.use_memory_mapping = v;
+        }
+        if let Some(v) = self.enable_compression {
+            config.enable_compression = v;
+        }
+        self.chunking.apply(&mut config.chunking);
+        self.compression.apply(&mut config.compression);
+        if let Some(v) = self.hash_algo {
+            config.hash_algo = v;
+        }
+        if let Some(v) = self.read_amplification_batch {
+            config.read_amplification_batch = v;
+        }
+        if let Some(v) = self.rate_limit {
+            config.rate_limit = Some(v);
+        }
+    }
+
+    fn overlay(&mut self, other: &PerformanceOverrides) {
+        if other.worker_threads.is_some() {
+            self.worker_threads = other.worker_threads;
+        }
+        if other.read_buffer_size.is_some() {
+            self.read_buffer_size = other.read_buffer_size;
+        }
+        if other.write_buffer_size.is_some() {
+            self.write_buffer_size = other.write_buffer_size;
+        }
+        if other.use_memory_mapping.is_some() {
+            self.use_memory_mapping = other.use_memory_mapping;
+        }
+        if other.enable_compression.is_some() {
+            self.enable_compression = other.enable_compression;
+        }
+        self.chunking.overlay(&other.chunking);
+        self.compression.overlay(&other.compression);
+        if other.hash_algo.is_some() {
+            self.hash_algo = other.hash_algo;
+        }
+        if other.read_amplification_batch.is_some() {
+            self.read_amplification_batch = other.read_amplification_batch;
+        }
+        if other.rate_limit.is_some() {
+            self.rate_limit = other.rate_limit;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct ChunkingOverrides {
+    strategy: Option<ChunkingStrategy>,
+    avg_size: Option<usize>,
+    min_size: Option<usize>,
+    max_size: Option<usize>,
+}
+
+impl ChunkingOverrides {
+    fn apply(&self, config: &mut ChunkingConfig) {
+        if let Some(v) = self.strategy {
+            config.strategy = v;
+        }
+        if let Some(v) = self.avg_size {
+            config.avg_size = v;
+        }
+        if let Some(v) = self.min_size {
+            config.min_size = v;
+        }
+        if let Some(v) = self.max_size {
+            config.max_size = v;
+        }
+    }
+
+    fn overlay(&mut self, other: &ChunkingOverrides) {
+        if other.strategy.is_some() {
+            self.strategy = other.strategy;
+        }
+        if other.avg_size.is_some() {
+            self.avg_size = other.avg_size;
+        }
+        if other.min_size.is_some() {
+            self.min_size = other.min_size;
+        }
+        if other.max_size.is_some() {
+            self.max_size = other.max_size;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct CompressionOverrides {
+    algo: Option<CompressionAlgo>,
+    level: Option<CompressionLevel>,
+    min_size: Option<usize>,
+    min_savings_ratio: Option<f64>,
+}
+
+impl CompressionOverrides {
+    fn apply(&self, config: &mut CompressionConfig) {
+        if let Some(v) = self.algo {
+            config.algo = v;
+        }
+        if let Some(v) = self.level {
+            config.level = v;
+        }
+        if let Some(v) = self.min_size {
+            config.min_size = v;
+        }
+        if let Some(v) = self.min_savings_ratio {
+            config.min_savings_ratio = v;
+        }
+    }
+
+    fn overlay(&mut self, other: &CompressionOverrides) {
+        if other.algo.is_some() {
+            self.algo = other.algo;
+        }
+        if other.level.is_some() {
+            self.level = other.level;
+        }
+        if other.min_size.is_some() {
+            self.min_size = other.min_size;
+        }
+        if other.min_savings_ratio.is_some() {
+            self.min_savings_ratio = other.min_savings_ratio;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct DatabaseOverrides {
+    timeout: Option<u32>,
+    enable_wal_mode: Option<bool>,
+    cache_size: Option<i32>,
+    enable_foreign_keys: Option<bool>,
+    storage_profile: Option<StorageProfile>,
+    background_threads: Option<usize>,
+}
+
+impl DatabaseOverrides {
+    fn apply(&self, config: &mut DatabaseConfig) {
+        if let Some(v) = self.timeout {
+            config.timeout = v;
+        }
+        if let Some(v) = self.enable_wal_mode {
+            config.enable_wal_mode = v;
+        }
+        if let Some(v) = self.cache_size {
+            config.cache_size = v;
+        }
+        if let Some(v) = self.enable_foreign_keys {
+            config.enable_foreign_keys = v;
+        }
+        if let Some(v) = self.storage_profile {
+            config.storage_profile = v;
+        }
+        if let Some(v) = self.background_threads {
+            config.background_threads = v;
+        }
+    }
+
+    fn overlay(&mut self, other: &DatabaseOverrides) {
+        if other.timeout.is_some() {
+            self.timeout = other.timeout;
+        }
+        if other.enable_wal_mode.is_some() {
+            self.enable_wal_mode = other.enable_wal_mode;
+        }
+        if other.cache_size.is_some() {
+            self.cache_size = other.cache_size;
+        }
+        if other.enable_foreign_keys.is_some() {
+            self.enable_foreign_keys = other.enable_foreign_keys;
+        }
+        if other.storage_profile.is_some() {
+            self.storage_profile = other.storage_profile;
+        }
+        if other.background_threads.is_some() {
+            self.background_threads = other.background_threads;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct StatusOverrides {
+    default_untracked_files: Option<UntrackedFiles>,
+}
+
+impl StatusOverrides {
+    fn apply(&self, settings: &mut Settings) {
+        if let Some(v) = self.default_untracked_files {
+            settings.default_untracked_files = v;
+        }
+    }
+
+    fn overlay(&mut self, other: &StatusOverrides) {
+        if other.default_untracked_files.is_some() {
+            self.default_untracked_files = other.default_untracked_files;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_settings_match_config_constants() {
+        let settings = Settings::default();
+        assert_eq!(settings.performance.worker_threads, PerformanceConfig::default().worker_threads);
+        assert_eq!(settings.ignore_patterns, DEFAULT_IGNORE_PATTERNS.to_vec());
+        assert_eq!(settings.binary_extensions, BINARY_EXTENSIONS.to_vec());
+    }
+
+    #[test]
+    fn test_file_config_overrides_only_specified_keys() {
+        let toml = r#"
+            [performance]
+            worker_threads = 4
+
+            [performance.compression]
+            algo = "brotli"
+            level = { fixed = 5 }
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        let defaults = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.performance.worker_threads, 4);
+        assert_eq!(settings.performance.compression.algo, CompressionAlgo::Brotli);
+        assert_eq!(settings.performance.compression.level, CompressionLevel::Fixed(5));
+        assert_eq!(settings.performance.hash_algo, defaults.performance.hash_algo);
+        // Everything else stays at the built-in default
+        assert_eq!(settings.performance.read_buffer_size, defaults.performance.read_buffer_size);
+        assert_eq!(settings.database.cache_size, defaults.database.cache_size);
+    }
+
+    #[test]
+    fn test_global_overrides_repo_settings() {
+        let repo_toml = r#"
+            [performance]
+            worker_threads = 4
+        "#;
+        let global_toml = r#"
+            [performance]
+            worker_threads = 16
+        "#;
+
+        let mut settings = Settings::default();
+        let repo_config: FileConfig = toml::from_str(repo_toml).unwrap();
+        repo_config.apply(&mut settings);
+        assert_eq!(settings.performance.worker_threads, 4);
+
+        let global_config: FileConfig = toml::from_str(global_toml).unwrap();
+        global_config.apply(&mut settings);
+        assert_eq!(settings.performance.worker_threads, 16);
+    }
+
+    #[test]
+    fn test_ignore_and_binary_extension_overrides_replace_defaults() {
+        let toml = r#"
+            ignore_patterns = ["*.log"]
+            binary_extensions = ["foo"]
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.ignore_patterns, vec!["*.log".to_string()]);
+        assert_eq!(settings.binary_extensions, vec!["foo".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_file_is_not_an_error() {
+        let result = load_file(Path::new("/nonexistent/blaze-settings-test/config.toml"));
+        assert!(matches!(result, Ok(None)));
+    }
+
+    #[test]
+    fn test_discover_reads_repo_config_toml() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(
+            blaze_dir.join(CONFIG_FILE_NAME),
+            r#"
+                [performance]
+                worker_threads = 2
+
+                [database]
+                cache_size = 4096
+            "#,
+        )
+        .unwrap();
+
+        let settings = Settings::discover(temp_dir.path()).unwrap();
+        assert_eq!(settings.performance.worker_threads, 2);
+        assert_eq!(settings.database.cache_size, 4096);
+    }
+
+    #[test]
+    fn test_file_config_overrides_hash_algo() {
+        let toml = r#"
+            [performance]
+            hash_algo = "sha256"
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.performance.hash_algo, HashAlgo::Sha256);
+    }
+
+    #[test]
+    fn test_file_config_overrides_read_amplification_batch() {
+        let toml = r#"
+            [performance]
+            read_amplification_batch = 32
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.performance.read_amplification_batch, 32);
+    }
+
+    #[test]
+    fn test_file_config_overrides_rate_limit() {
+        let toml = r#"
+            [performance]
+            rate_limit = 10485760
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.performance.rate_limit, Some(10_485_760));
+    }
+
+    #[test]
+    fn test_discover_honors_explicit_storage_profile() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(
+            blaze_dir.join(CONFIG_FILE_NAME),
+            r#"
+                [database]
+                storage_profile = "hdd"
+            "#,
+        )
+        .unwrap();
+
+        let settings = Settings::discover(temp_dir.path()).unwrap();
+        assert_eq!(settings.database.storage_profile, StorageProfile::Hdd);
+        // tuning for Hdd should have run, not just been recorded
+        assert!(settings.performance.read_buffer_size > PerformanceConfig::default().read_buffer_size);
+    }
+
+    #[test]
+    fn test_discover_rejects_malformed_repo_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(blaze_dir.join(CONFIG_FILE_NAME), "not = [valid toml").unwrap();
+
+        assert!(Settings::discover(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_status_default_untracked_files_override() {
+        let toml = r#"
+            [status]
+            default_untracked_files = "all"
+        "#;
+        let file_config: FileConfig = toml::from_str(toml).unwrap();
+        let mut settings = Settings::default();
+        file_config.apply(&mut settings);
+
+        assert_eq!(settings.default_untracked_files, UntrackedFiles::All);
+    }
+
+    #[test]
+    fn test_include_is_overridden_by_the_including_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let shared_path = temp_dir.path().join("shared.toml");
+        std::fs::write(
+            &shared_path,
+            r#"
+                [performance]
+                worker_threads = 2
+
+                [database]
+                cache_size = 4096
+            "#,
+        )
+        .unwrap();
+
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(
+            blaze_dir.join(CONFIG_FILE_NAME),
+            r#"
+                include = ["../shared.toml"]
+
+                [performance]
+                worker_threads = 8
+            "#,
+        )
+        .unwrap();
+
+        let settings = Settings::discover(temp_dir.path()).unwrap();
+        // The including file's own key wins over the included file's...
+        assert_eq!(settings.performance.worker_threads, 8);
+        // ...but a key the including file never mentions still comes from the include.
+        assert_eq!(settings.database.cache_size, 4096);
+    }
+
+    #[test]
+    fn test_unset_clears_a_key_an_include_turned_on() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let shared_path = temp_dir.path().join("shared.toml");
+        std::fs::write(
+            &shared_path,
+            r#"
+                [performance]
+                rate_limit = 10485760
+            "#,
+        )
+        .unwrap();
+
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(
+            blaze_dir.join(CONFIG_FILE_NAME),
+            r#"
+                include = ["../shared.toml"]
+                unset = ["performance.rate_limit"]
+            "#,
+        )
+        .unwrap();
+
+        let settings = Settings::discover(temp_dir.path()).unwrap();
+        assert_eq!(settings.performance.rate_limit, None);
+    }
+
+    #[test]
+    fn test_include_cycle_is_rejected() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let blaze_dir = temp_dir.path().join(crate::config::BLAZE_DIR);
+        std::fs::create_dir_all(&blaze_dir).unwrap();
+        std::fs::write(
+            blaze_dir.join(CONFIG_FILE_NAME),
+            r#"include = ["config.toml"]"#,
+        )
+        .unwrap();
+
+        assert!(Settings::discover(temp_dir.path()).is_err());
+    }
+}
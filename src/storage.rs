@@ -0,0 +1,423 @@
+//! Pluggable storage backend for chunk/object/index I/O
+//!
+//! `ChunkStore` talks to a `dyn StorageBackend` instead of calling
+//! `std::fs` directly, the same way the repository engine walks its source
+//! tree over an abstract path rather than a concrete filesystem API. The
+//! default, [`FsBackend`], is a thin pass-through to `std::fs` and preserves
+//! today's on-disk bundle layout. [`MemBackend`] keeps everything in an
+//! in-memory map instead, so tests can run a full init/add/commit/checkout
+//! workflow against a chunk store without touching disk.
+
+use crate::errors::{BlazeError, Result, ResultExt};
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// An open handle to a stored object, supporting the same random-access
+/// read/write/seek pattern `ChunkStore` needs for its append-only bundles
+pub trait StorageHandle: Read + Write + Seek + Send {
+    /// Flush any buffered writes through to durable storage - a real `fsync`
+    /// for [`FsBackend`], a no-op copy-back into the map for [`MemBackend`]
+    fn sync(&mut self) -> Result<()>;
+}
+
+/// How a path should be opened, mirroring the handful of `std::fs::OpenOptions`
+/// flags the chunk store actually uses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StorageOpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    create: bool,
+    truncate: bool,
+}
+
+impl StorageOpenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(mut self, read: bool) -> Self {
+        self.read = read;
+        self
+    }
+
+    pub fn write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+}
+
+/// Abstraction over the object/chunk/index reads and writes a chunk store
+/// performs, so the same engine code can run against real files or an
+/// in-memory filesystem
+pub trait StorageBackend: Send + Sync {
+    /// Open `path` for random-access reading/writing/appending
+    fn open(&self, path: &Path, opts: &StorageOpenOptions) -> Result<Box<dyn StorageHandle>>;
+    /// Read an entire object into memory
+    fn read(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Write an entire object, replacing any existing content
+    fn write(&self, path: &Path, data: &[u8]) -> Result<()>;
+    /// List the direct children of a directory
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>>;
+    /// Whether an object exists
+    fn exists(&self, path: &Path) -> bool;
+    /// Remove an object
+    fn remove(&self, path: &Path) -> Result<()>;
+    /// Atomically replace `to` with `from`
+    fn rename(&self, from: &Path, to: &Path) -> Result<()>;
+    /// Create a directory and any missing parents
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
+    /// Size in bytes of a stored object
+    fn metadata_len(&self, path: &Path) -> Result<u64>;
+}
+
+/// Default, on-disk storage backend - a thin pass-through to `std::fs`
+/// preserving the existing bundle/index layout
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FsBackend;
+
+impl FsBackend {
+    pub fn new() -> Self {
+        FsBackend
+    }
+}
+
+struct FsHandle(fs::File);
+
+impl Read for FsHandle {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for FsHandle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Seek for FsHandle {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl StorageHandle for FsHandle {
+    fn sync(&mut self) -> Result<()> {
+        self.0.sync_all().map_err(BlazeError::from)
+    }
+}
+
+impl StorageBackend for FsBackend {
+    fn open(&self, path: &Path, opts: &StorageOpenOptions) -> Result<Box<dyn StorageHandle>> {
+        let file = OpenOptions::new()
+            .read(opts.read)
+            .write(opts.write)
+            .append(opts.append)
+            .create(opts.create)
+            .truncate(opts.truncate)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(Box::new(FsHandle(file)))
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<()> {
+        fs::write(path, data).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("Failed to list {}", dir.display()))?
+        {
+            entries.push(entry?.path());
+        }
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        fs::rename(from, to)
+            .with_context(|| format!("Failed to rename {} to {}", from.display(), to.display()))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create directory {}", path.display()))
+    }
+
+    fn metadata_len(&self, path: &Path) -> Result<u64> {
+        Ok(fs::metadata(path)
+            .with_context(|| format!("Failed to stat {}", path.display()))?
+            .len())
+    }
+}
+
+/// In-memory storage backend - every "file" is a `Vec<u8>` in a shared map.
+/// Intended for tests: a whole `init`/`add`/`commit`/`checkout`/`verify`
+/// workflow can run against a chunk store without spawning a process or
+/// touching disk.
+#[derive(Clone, Default)]
+pub struct MemBackend {
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
+}
+
+impl MemBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn not_found(path: &Path) -> BlazeError {
+        BlazeError::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} not found in in-memory backend", path.display()),
+        ))
+    }
+}
+
+struct MemHandle {
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
+    path: PathBuf,
+    append: bool,
+    cursor: Cursor<Vec<u8>>,
+}
+
+impl MemHandle {
+    fn flush_to_store(&self) {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(self.path.clone(), self.cursor.get_ref().clone());
+    }
+}
+
+impl Read for MemHandle {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl Write for MemHandle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.append {
+            let end = self.cursor.get_ref().len() as u64;
+            self.cursor.set_position(end);
+        }
+        let written = self.cursor.write(buf)?;
+        self.flush_to_store();
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_to_store();
+        Ok(())
+    }
+}
+
+impl Seek for MemHandle {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+impl StorageHandle for MemHandle {
+    fn sync(&mut self) -> Result<()> {
+        self.flush_to_store();
+        Ok(())
+    }
+}
+
+impl StorageBackend for MemBackend {
+    fn open(&self, path: &Path, opts: &StorageOpenOptions) -> Result<Box<dyn StorageHandle>> {
+        let mut files = self.files.lock().unwrap();
+        let existing = files.get(path).cloned();
+
+        if existing.is_none() && !opts.create {
+            return Err(Self::not_found(path));
+        }
+
+        let mut data = existing.unwrap_or_default();
+        if opts.truncate {
+            data.clear();
+        }
+        files.entry(path.to_path_buf()).or_insert_with(Vec::new);
+        drop(files);
+
+        let position = if opts.append { data.len() as u64 } else { 0 };
+        let mut cursor = Cursor::new(data);
+        cursor.set_position(position);
+
+        Ok(Box::new(MemHandle {
+            files: Arc::clone(&self.files),
+            path: path.to_path_buf(),
+            append: opts.append,
+            cursor,
+        }))
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), data.to_vec());
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        self.files.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let mut files = self.files.lock().unwrap();
+        if let Some(data) = files.remove(from) {
+            files.insert(to.to_path_buf(), data);
+        }
+        Ok(())
+    }
+
+    fn create_dir_all(&self, _path: &Path) -> Result<()> {
+        // Directories are implicit in a flat path -> bytes map
+        Ok(())
+    }
+
+    fn metadata_len(&self, path: &Path) -> Result<u64> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|data| data.len() as u64)
+            .ok_or_else(|| Self::not_found(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mem_backend_write_then_read_roundtrips() {
+        let backend = MemBackend::new();
+        let path = PathBuf::from("/store/object");
+        backend.write(&path, b"hello").unwrap();
+        assert!(backend.exists(&path));
+        assert_eq!(backend.read(&path).unwrap(), b"hello");
+        assert_eq!(backend.metadata_len(&path).unwrap(), 5);
+    }
+
+    #[test]
+    fn mem_backend_append_handle_appends_regardless_of_seek() {
+        let backend = MemBackend::new();
+        let path = PathBuf::from("/store/log");
+
+        {
+            let mut handle = backend
+                .open(&path, &StorageOpenOptions::new().create(true).append(true))
+                .unwrap();
+            handle.write_all(b"first").unwrap();
+        }
+        {
+            let mut handle = backend
+                .open(&path, &StorageOpenOptions::new().create(true).append(true))
+                .unwrap();
+            handle.seek(SeekFrom::Start(0)).unwrap();
+            handle.write_all(b"second").unwrap();
+        }
+
+        assert_eq!(backend.read(&path).unwrap(), b"firstsecond");
+    }
+
+    #[test]
+    fn mem_backend_open_without_create_fails_on_missing_path() {
+        let backend = MemBackend::new();
+        let path = PathBuf::from("/store/missing");
+        assert!(backend.open(&path, &StorageOpenOptions::new()).is_err());
+    }
+
+    #[test]
+    fn mem_backend_list_returns_direct_children_only() {
+        let backend = MemBackend::new();
+        backend.write(&PathBuf::from("/store/a"), b"1").unwrap();
+        backend.write(&PathBuf::from("/store/b"), b"2").unwrap();
+        backend
+            .write(&PathBuf::from("/store/nested/c"), b"3")
+            .unwrap();
+
+        let mut entries = backend.list(&PathBuf::from("/store")).unwrap();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![PathBuf::from("/store/a"), PathBuf::from("/store/b")]
+        );
+    }
+
+    #[test]
+    fn mem_backend_rename_moves_content() {
+        let backend = MemBackend::new();
+        let from = PathBuf::from("/store/tmp");
+        let to = PathBuf::from("/store/final");
+        backend.write(&from, b"data").unwrap();
+        backend.rename(&from, &to).unwrap();
+
+        assert!(!backend.exists(&from));
+        assert_eq!(backend.read(&to).unwrap(), b"data");
+    }
+}
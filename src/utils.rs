@@ -2,6 +2,7 @@
 
 use crate::config::BINARY_EXTENSIONS;
 use crate::errors::{BlazeError, Result};
+use regex::Regex;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -34,8 +35,10 @@ pub fn format_duration(seconds: u64) -> String {
     }
 }
 
-/// Format elapsed time since a timestamp
+/// Format elapsed time since a timestamp, in the active [`crate::messages::Lang`]
 pub fn format_elapsed_time(timestamp: u64) -> String {
+    use crate::messages::{msg, msg_n};
+
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -44,23 +47,16 @@ pub fn format_elapsed_time(timestamp: u64) -> String {
     if now >= timestamp {
         let elapsed = now - timestamp;
         if elapsed < 60 {
-            "just now".to_string()
+            msg("time.just_now", &[])
         } else if elapsed < 3600 {
-            let minutes = elapsed / 60;
-            format!(
-                "{} minute{} ago",
-                minutes,
-                if minutes == 1 { "" } else { "s" }
-            )
+            msg_n("time.minutes_ago", (elapsed / 60) as i64, &[])
         } else if elapsed < 86400 {
-            let hours = elapsed / 3600;
-            format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
+            msg_n("time.hours_ago", (elapsed / 3600) as i64, &[])
         } else {
-            let days = elapsed / 86400;
-            format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
+            msg_n("time.days_ago", (elapsed / 86400) as i64, &[])
         }
     } else {
-        "in the future".to_string()
+        msg("time.in_future", &[])
     }
 }
 
@@ -72,11 +68,23 @@ pub fn current_timestamp() -> u64 {
         .as_secs()
 }
 
-/// Check if a file should be treated as binary based on its extension
+/// Check if a file should be treated as binary based on its extension,
+/// against the built-in [`BINARY_EXTENSIONS`] list
 pub fn is_binary_file<P: AsRef<Path>>(path: P) -> bool {
+    is_binary_file_with_extensions(path, BINARY_EXTENSIONS)
+}
+
+/// Check if a file should be treated as binary based on its extension,
+/// against a configured extension list - for repos that have overridden
+/// [`BINARY_EXTENSIONS`] via `blaze.toml`
+pub fn is_binary_file_with_extensions<P: AsRef<Path>, S: AsRef<str>>(
+    path: P,
+    extensions: &[S],
+) -> bool {
     if let Some(extension) = path.as_ref().extension() {
         if let Some(ext_str) = extension.to_str() {
-            return BINARY_EXTENSIONS.contains(&ext_str.to_lowercase().as_str());
+            let ext_lower = ext_str.to_lowercase();
+            return extensions.iter().any(|e| e.as_ref() == ext_lower);
         }
     }
     false
@@ -93,76 +101,270 @@ pub fn normalize_path<P: AsRef<Path>>(path: P) -> String {
         .join("/")
 }
 
-/// Check if a path matches any of the ignore patterns
+/// Check if a path matches any of the ignore patterns, gitignore-style (see
+/// [`GlobPattern`]).
 pub fn should_ignore_path<P: AsRef<Path>>(path: P, patterns: &[&str]) -> bool {
     let path_str = normalize_path(path);
+    patterns
+        .iter()
+        .any(|pattern| GlobPattern::compile(pattern).is_match(&path_str))
+}
+
+/// One token of a single path segment's compiled glob: a literal character,
+/// `?` (any single character), `*` (any run of characters), or a `[...]`
+/// character class.
+enum GlobToken {
+    Literal(char),
+    Question,
+    Star,
+    Class { negate: bool, ranges: Vec<(char, char)> },
+}
+
+/// A compiled gitignore-style pattern path segment: either `**` (matches
+/// zero or more whole path segments) or a single segment tokenized for
+/// `*`/`?`/`[...]` matching.
+enum GlobSegment {
+    DoubleStar,
+    Single(Vec<GlobToken>),
+}
+
+/// A compiled `.blazeignore`/gitignore glob pattern.
+///
+/// Patterns are split into segments on `/`. A pattern with no `/` (other
+/// than a trailing one) is unanchored and may match at any depth, exactly
+/// like an implicit `**/` prefix; a pattern containing a `/` elsewhere (or
+/// starting with one) is anchored to the root it was loaded from. A
+/// trailing `/` additionally matches everything below the named directory.
+/// `**` inside the pattern matches across any number of path separators.
+struct GlobPattern {
+    segments: Vec<GlobSegment>,
+}
+
+impl GlobPattern {
+    fn compile(pattern: &str) -> Self {
+        let anchored_leading = pattern.starts_with('/');
+        let trimmed = pattern.strip_prefix('/').unwrap_or(pattern);
+        let dir_only = trimmed.len() > 1 && trimmed.ends_with('/');
+        let body = if dir_only {
+            &trimmed[..trimmed.len() - 1]
+        } else {
+            trimmed
+        };
+
+        let raw_segments: Vec<&str> = body.split('/').collect();
+        let anchored = anchored_leading || raw_segments.len() > 1;
+
+        let mut segments: Vec<GlobSegment> = raw_segments
+            .iter()
+            .map(|seg| {
+                if *seg == "**" {
+                    GlobSegment::DoubleStar
+                } else {
+                    GlobSegment::Single(tokenize_segment(seg))
+                }
+            })
+            .collect();
+
+        if !anchored {
+            segments.insert(0, GlobSegment::DoubleStar);
+        }
+        if dir_only {
+            segments.push(GlobSegment::DoubleStar);
+        }
+
+        GlobPattern { segments }
+    }
 
-    for pattern in patterns {
-        if pattern.ends_with('/') {
-            // Directory pattern
-            let dir_pattern = &pattern[..pattern.len() - 1];
-            if path_str.starts_with(dir_pattern)
-                && (path_str.len() == dir_pattern.len()
-                    || path_str.chars().nth(dir_pattern.len()) == Some('/'))
-            {
-                return true;
+    fn is_match(&self, path: &str) -> bool {
+        let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        Self::match_from(&self.segments, 0, &path_segments, 0)
+    }
+
+    /// Walk pattern segments against path segments, backtracking over every
+    /// possible span a `**` could consume.
+    fn match_from(pattern: &[GlobSegment], pi: usize, path: &[&str], pj: usize) -> bool {
+        if pi == pattern.len() {
+            return pj == path.len();
+        }
+
+        match &pattern[pi] {
+            GlobSegment::DoubleStar => (pj..=path.len())
+                .any(|k| Self::match_from(pattern, pi + 1, path, k)),
+            GlobSegment::Single(tokens) => {
+                pj < path.len()
+                    && match_segment_tokens(tokens, path[pj])
+                    && Self::match_from(pattern, pi + 1, path, pj + 1)
+            }
+        }
+    }
+}
+
+/// Tokenize a single `/`-free path segment into literals, `?`, `*`, and
+/// `[...]` character classes (`[a-z]`, `[abc]`, and negated `[!abc]`).
+fn tokenize_segment(segment: &str) -> Vec<GlobToken> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = segment.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                tokens.push(GlobToken::Star);
+                i += 1;
             }
-        } else if pattern.starts_with("*.") {
-            // Extension pattern
-            let ext = &pattern[2..];
-            if path_str.ends_with(&format!(".{}", ext)) {
-                return true;
+            '?' => {
+                tokens.push(GlobToken::Question);
+                i += 1;
             }
-        } else if pattern.contains('*') {
-            // Simple glob pattern - basic implementation
-            if simple_glob_match(pattern, &path_str) {
-                return true;
+            '[' => {
+                if let Some((token, consumed)) = parse_class(&chars[i..]) {
+                    tokens.push(token);
+                    i += consumed;
+                } else {
+                    tokens.push(GlobToken::Literal('['));
+                    i += 1;
+                }
             }
-        } else {
-            // Exact match
-            if path_str == *pattern {
-                return true;
+            c => {
+                tokens.push(GlobToken::Literal(c));
+                i += 1;
             }
         }
     }
-    false
-}
 
-/// Simple glob pattern matching (supports * wildcard)
-fn simple_glob_match(pattern: &str, text: &str) -> bool {
-    let pattern_parts: Vec<&str> = pattern.split('*').collect();
+    tokens
+}
 
-    if pattern_parts.len() == 1 {
-        return pattern == text;
+/// Parse a `[...]` character class starting at `chars[0] == '['`, returning
+/// the compiled token and how many characters it consumed, or `None` if
+/// there's no closing `]` (in which case the `[` is treated as a literal).
+fn parse_class(chars: &[char]) -> Option<(GlobToken, usize)> {
+    let close = chars.iter().position(|&c| c == ']')?;
+    if close == 0 {
+        return None;
     }
 
-    let mut text_pos = 0;
+    let mut body = &chars[1..close];
+    let negate = matches!(body.first(), Some('!') | Some('^'));
+    if negate {
+        body = &body[1..];
+    }
 
-    for (i, part) in pattern_parts.iter().enumerate() {
-        if part.is_empty() {
-            continue;
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == '-' {
+            ranges.push((body[i], body[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((body[i], body[i]));
+            i += 1;
         }
+    }
 
-        if i == 0 {
-            // First part must match from the beginning
-            if !text[text_pos..].starts_with(part) {
-                return false;
-            }
-            text_pos += part.len();
-        } else if i == pattern_parts.len() - 1 {
-            // Last part must match at the end
-            return text[text_pos..].ends_with(part);
-        } else {
-            // Middle part must be found somewhere
-            if let Some(pos) = text[text_pos..].find(part) {
-                text_pos += pos + part.len();
-            } else {
+    Some((GlobToken::Class { negate, ranges }, close + 1))
+}
+
+/// Match one path segment's text against its tokenized glob, backtracking
+/// over every possible length a `*` could consume.
+fn match_segment_tokens(tokens: &[GlobToken], text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    match_tokens(tokens, 0, &chars, 0)
+}
+
+fn match_tokens(tokens: &[GlobToken], ti: usize, text: &[char], si: usize) -> bool {
+    if ti == tokens.len() {
+        return si == text.len();
+    }
+
+    match &tokens[ti] {
+        GlobToken::Star => (si..=text.len()).any(|k| match_tokens(tokens, ti + 1, text, k)),
+        GlobToken::Question => si < text.len() && match_tokens(tokens, ti + 1, text, si + 1),
+        GlobToken::Literal(c) => {
+            si < text.len() && text[si] == *c && match_tokens(tokens, ti + 1, text, si + 1)
+        }
+        GlobToken::Class { negate, ranges } => {
+            if si >= text.len() {
                 return false;
             }
+            let hit = ranges.iter().any(|(a, b)| text[si] >= *a && text[si] <= *b);
+            (hit != *negate) && match_tokens(tokens, ti + 1, text, si + 1)
         }
     }
+}
+
+/// One compiled rule from a `.blazeignore` file or a config `[ignore]`
+/// section: a glob or anchored regex pattern, optionally negated with a
+/// leading `!`. A `regex:` prefix selects the regex form; everything else
+/// is matched with a [`GlobPattern`].
+enum IgnoreRule {
+    Glob(GlobPattern),
+    Regex(Regex),
+}
+
+struct IgnorePattern {
+    rule: IgnoreRule,
+    negate: bool,
+}
 
-    true
+/// Combined glob/regex ignore matcher, compiled once from a pattern list and
+/// reused across an entire directory walk instead of re-parsing patterns per
+/// file.
+///
+/// Precedence is last-match-wins: patterns are evaluated in the order given
+/// and the verdict of the last matching pattern decides the outcome, so a
+/// later `!pattern` can re-include a path an earlier pattern excluded.
+pub struct IgnoreMatcher {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreMatcher {
+    /// Compile a flat list of pattern lines (as loaded from `.blazeignore`
+    /// and the repo config) into a matcher.
+    pub fn compile(patterns: &[String]) -> Result<Self> {
+        let mut compiled = Vec::with_capacity(patterns.len());
+
+        for raw in patterns {
+            let (negate, raw) = match raw.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, raw.as_str()),
+            };
+
+            let rule = match raw.strip_prefix("regex:") {
+                Some(expr) => {
+                    let regex = Regex::new(expr).map_err(|e| {
+                        BlazeError::Config(format!("Invalid ignore regex '{}': {}", expr, e))
+                    })?;
+                    IgnoreRule::Regex(regex)
+                }
+                None => IgnoreRule::Glob(GlobPattern::compile(raw)),
+            };
+
+            compiled.push(IgnorePattern { rule, negate });
+        }
+
+        Ok(IgnoreMatcher { patterns: compiled })
+    }
+
+    /// Whether `path` is ignored, applying last-match-wins precedence across
+    /// every compiled pattern.
+    pub fn is_ignored<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path_str = normalize_path(path);
+        let mut ignored = false;
+
+        for pattern in &self.patterns {
+            let matched = match &pattern.rule {
+                IgnoreRule::Glob(glob) => glob.is_match(&path_str),
+                IgnoreRule::Regex(regex) => regex.is_match(&path_str),
+            };
+
+            if matched {
+                ignored = !pattern.negate;
+            }
+        }
+
+        ignored
+    }
 }
 
 /// Create a progress bar with consistent styling
@@ -192,21 +394,41 @@ pub fn safe_metadata<P: AsRef<Path>>(path: P) -> Result<std::fs::Metadata> {
     })
 }
 
-/// Get file modification time as Unix timestamp
-pub fn get_mtime<P: AsRef<Path>>(path: P) -> Result<u64> {
-    let metadata = safe_metadata(path)?;
+/// Modification time of already-fetched metadata, split into whole seconds
+/// and the sub-second remainder. The remainder is genuine nanosecond
+/// precision on filesystems that report one (most Linux filesystems do);
+/// on ones that don't, it's always zero, which callers that need a
+/// precision fallback can treat the same as "unavailable".
+pub fn mtime_from_metadata(metadata: &std::fs::Metadata) -> Result<(u64, u32)> {
     let mtime = metadata
         .modified()
         .map_err(|e| BlazeError::FileSystem(format!("Failed to get modification time: {}", e)))?;
 
-    let timestamp = mtime
+    let duration = mtime
         .duration_since(UNIX_EPOCH)
-        .map_err(|e| BlazeError::FileSystem(format!("Invalid modification time: {}", e)))?
-        .as_secs();
+        .map_err(|e| BlazeError::FileSystem(format!("Invalid modification time: {}", e)))?;
+
+    Ok((duration.as_secs(), duration.subsec_nanos()))
+}
+
+/// Get file modification time as Unix timestamp
+pub fn get_mtime<P: AsRef<Path>>(path: P) -> Result<u64> {
+    let metadata = safe_metadata(path)?;
+    let (timestamp, _nanos) = mtime_from_metadata(&metadata)?;
 
     Ok(timestamp)
 }
 
+/// Sub-second remainder of a file's modification time, for callers that
+/// need nanosecond precision but don't already have the file's metadata on
+/// hand (see [`mtime_from_metadata`] for callers that do)
+pub fn get_mtime_nanos<P: AsRef<Path>>(path: P) -> Result<u32> {
+    let metadata = safe_metadata(path)?;
+    let (_secs, nanos) = mtime_from_metadata(&metadata)?;
+
+    Ok(nanos)
+}
+
 /// Convert bytes to a hex string
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
@@ -233,6 +455,72 @@ pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>> {
     Ok(bytes)
 }
 
+/// Resolve an abbreviated hex prefix (e.g. `a1b2c3`) against a list of full
+/// commit/chunk hashes sorted in ascending lexicographic order, via a binary
+/// search for the prefix's lower bound instead of a full linear scan over
+/// every object. Comparison happens on raw bytes (`hex_to_bytes`), so an
+/// odd-length prefix's trailing nibble is masked out rather than treated as a
+/// literal zero.
+pub fn resolve_prefix(sorted_hashes: &[String], prefix: &str) -> Result<String> {
+    if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(BlazeError::Validation(format!(
+            "Invalid revision prefix: {}",
+            prefix
+        )));
+    }
+
+    let odd = prefix.len() % 2 != 0;
+    let padded_prefix = if odd {
+        format!("{}0", prefix)
+    } else {
+        prefix.to_string()
+    };
+    let prefix_bytes = hex_to_bytes(&padded_prefix)?;
+    let whole_bytes = prefix_bytes.len() - if odd { 1 } else { 0 };
+
+    let matches_prefix = |hash: &str| -> Result<bool> {
+        let hash_bytes = hex_to_bytes(hash)?;
+        if hash_bytes.len() < prefix_bytes.len() {
+            return Ok(false);
+        }
+        if hash_bytes[..whole_bytes] != prefix_bytes[..whole_bytes] {
+            return Ok(false);
+        }
+        if odd {
+            Ok(hash_bytes[whole_bytes] & 0xf0 == prefix_bytes[whole_bytes] & 0xf0)
+        } else {
+            Ok(true)
+        }
+    };
+
+    // Binary search only finds where matches *could* start; sorted hashes
+    // sharing the prefix are contiguous from there, so a short forward scan
+    // (not a scan of the whole object list) finds every candidate.
+    let lower = sorted_hashes.partition_point(|h| h.as_str() < prefix);
+
+    let mut matches = Vec::new();
+    for hash in &sorted_hashes[lower..] {
+        if matches_prefix(hash)? {
+            matches.push(hash.clone());
+        } else {
+            break;
+        }
+    }
+
+    match matches.len() {
+        0 => Err(BlazeError::Repository(format!(
+            "unknown revision: {}",
+            prefix
+        ))),
+        1 => Ok(matches.remove(0)),
+        _ => Err(BlazeError::Repository(format!(
+            "ambiguous prefix '{}' matches multiple revisions: {}",
+            prefix,
+            matches.join(", ")
+        ))),
+    }
+}
+
 /// Truncate a string to a maximum length with ellipsis
 pub fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
@@ -290,11 +578,75 @@ mod tests {
     }
 
     #[test]
-    fn test_simple_glob_match() {
-        assert!(simple_glob_match("*.txt", "readme.txt"));
-        assert!(simple_glob_match("test*", "test123"));
-        assert!(simple_glob_match("*test*", "mytest123"));
-        assert!(!simple_glob_match("*.txt", "readme.md"));
+    fn test_ignore_matcher_last_match_wins_with_negation() {
+        let patterns = vec!["target/".to_string(), "!target/keep.txt".to_string()];
+        let matcher = IgnoreMatcher::compile(&patterns).unwrap();
+
+        assert!(matcher.is_ignored("target/debug/app"));
+        assert!(!matcher.is_ignored("target/keep.txt"));
+        assert!(!matcher.is_ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn test_ignore_matcher_regex_pattern() {
+        let patterns = vec![r"regex:^build/.*\.o$".to_string()];
+        let matcher = IgnoreMatcher::compile(&patterns).unwrap();
+
+        assert!(matcher.is_ignored("build/main.o"));
+        assert!(!matcher.is_ignored("build/main.rs"));
+    }
+
+    #[test]
+    fn test_ignore_matcher_rejects_invalid_regex() {
+        let patterns = vec!["regex:(".to_string()];
+        assert!(IgnoreMatcher::compile(&patterns).is_err());
+    }
+
+    #[test]
+    fn test_glob_pattern_basic_star() {
+        assert!(GlobPattern::compile("*.txt").is_match("readme.txt"));
+        assert!(GlobPattern::compile("test*").is_match("test123"));
+        assert!(GlobPattern::compile("*test*").is_match("mytest123"));
+        assert!(!GlobPattern::compile("*.txt").is_match("readme.md"));
+    }
+
+    #[test]
+    fn test_glob_pattern_double_star_crosses_separators() {
+        assert!(GlobPattern::compile("src/**/*.rs").is_match("src/a/b/main.rs"));
+        assert!(GlobPattern::compile("src/**/*.rs").is_match("src/main.rs"));
+        assert!(!GlobPattern::compile("src/**/*.rs").is_match("lib/main.rs"));
+    }
+
+    #[test]
+    fn test_glob_pattern_question_and_class() {
+        assert!(GlobPattern::compile("file?.txt").is_match("file1.txt"));
+        assert!(!GlobPattern::compile("file?.txt").is_match("file12.txt"));
+        assert!(GlobPattern::compile("[a-c].log").is_match("b.log"));
+        assert!(!GlobPattern::compile("[a-c].log").is_match("d.log"));
+        assert!(GlobPattern::compile("[!a-c].log").is_match("d.log"));
+    }
+
+    #[test]
+    fn test_glob_pattern_anchoring() {
+        // Leading slash / embedded slash: anchored to the root.
+        assert!(GlobPattern::compile("/build").is_match("build"));
+        assert!(!GlobPattern::compile("/build").is_match("src/build"));
+        // No slash at all: may match at any depth.
+        assert!(GlobPattern::compile("*.log").is_match("a/b/c.log"));
+    }
+
+    #[test]
+    fn test_glob_pattern_dir_only_matches_contents() {
+        // Unanchored: "target/" matches a directory named target at any depth.
+        let pattern = GlobPattern::compile("target/");
+        assert!(pattern.is_match("target"));
+        assert!(pattern.is_match("target/debug/app"));
+        assert!(pattern.is_match("other/target"));
+
+        // Anchored: "/target/" only matches at the repo root.
+        let anchored = GlobPattern::compile("/target/");
+        assert!(anchored.is_match("target/debug/app"));
+        assert!(!anchored.is_match("other/target"));
     }
 
     #[test]
@@ -318,4 +670,40 @@ mod tests {
         assert_eq!(truncate_string("hi", 2), "hi");
         assert_eq!(truncate_string("test", 3), "...");
     }
+
+    fn sorted_hashes(hashes: &[&str]) -> Vec<String> {
+        let mut hashes: Vec<String> = hashes.iter().map(|h| h.to_string()).collect();
+        hashes.sort();
+        hashes
+    }
+
+    #[test]
+    fn test_resolve_prefix_unique_match() {
+        let hashes = sorted_hashes(&["aabbcc", "112233", "ffeedd"]);
+        assert_eq!(resolve_prefix(&hashes, "aab").unwrap(), "aabbcc");
+    }
+
+    #[test]
+    fn test_resolve_prefix_odd_length_masks_final_nibble() {
+        let hashes = sorted_hashes(&["a1b2c0ff", "a1b2caff", "000000ff"]);
+        // "a1b2c" is 5 nibbles; both a1b2c0ff and a1b2caff share the first
+        // four and a half bytes, so the odd-length prefix must match both
+        let err = resolve_prefix(&hashes, "a1b2c").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("ambiguous"));
+        assert!(message.contains("a1b2c0ff"));
+        assert!(message.contains("a1b2caff"));
+    }
+
+    #[test]
+    fn test_resolve_prefix_unknown_revision() {
+        let hashes = sorted_hashes(&["aabbcc", "112233"]);
+        assert!(resolve_prefix(&hashes, "ffff").is_err());
+    }
+
+    #[test]
+    fn test_resolve_prefix_rejects_non_hex() {
+        let hashes = sorted_hashes(&["aabbcc"]);
+        assert!(resolve_prefix(&hashes, "zz").is_err());
+    }
 }
@@ -0,0 +1,222 @@
+//! Filesystem-watching auto-commit daemon.
+//!
+//! [`Blaze::start_autocommit`] turns a working tree into a continuous-
+//! snapshot store: a background thread watches the repository recursively,
+//! coalesces whatever burst of events a debounce window catches into one
+//! logical change, then re-runs the existing `add`/`commit` pipeline to turn
+//! it into a commit - rather than requiring the user to invoke either
+//! explicitly.
+
+use crate::config::BLAZE_DIR;
+use crate::core::Blaze;
+use crate::errors::{BlazeError, Result, ResultExt};
+use crate::utils::IgnoreMatcher;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher};
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Commit messages list every changed path up to this many before falling
+/// back to a bare count, so a huge burst doesn't produce an unreadable
+/// commit message
+const MAX_LISTED_PATHS: usize = 10;
+
+/// Configuration for [`Blaze::start_autocommit`]
+#[derive(Debug, Clone)]
+pub struct AutoCommitConfig {
+    /// How long the watched tree must stay quiet before a burst of events is
+    /// folded into one commit
+    pub debounce: Duration,
+    /// Extra ignore patterns (same glob/regex syntax as `.blazeignore`)
+    /// applied only to decide whether a raw filesystem event is worth
+    /// waking up for - the repository's own ignore rules still govern what
+    /// `add` actually stages
+    pub ignore_patterns: Vec<String>,
+    /// Prepended to every auto-commit message as `[author] ...`, if set
+    pub author: Option<String>,
+}
+
+impl Default for AutoCommitConfig {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            ignore_patterns: Vec::new(),
+            author: None,
+        }
+    }
+}
+
+/// Handle to a running [`Blaze::start_autocommit`] daemon
+pub struct AutoCommitHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl AutoCommitHandle {
+    /// Ask the watch loop to stop after its current debounce wait - does
+    /// not block; call [`Self::join`] to wait for it to actually exit
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Block until the watch thread exits, returning whatever error (if
+    /// any) ended it
+    pub fn join(mut self) -> Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .map_err(|_| BlazeError::Generic("Auto-commit thread panicked".to_string()))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Blaze {
+    /// Start watching this repository and auto-committing quiescent bursts
+    /// of changes, per `config`. Seeds its initial snapshot with a full
+    /// directory walk (the same one `add --all` does) before entering the
+    /// watch loop, so files already on disk when the daemon starts are
+    /// captured by its first commit rather than only ones changed after.
+    pub fn start_autocommit(&self, config: AutoCommitConfig) -> Result<AutoCommitHandle> {
+        if !self.is_repo() {
+            return Err(BlazeError::Repository(
+                "Not a Blaze repository (or any parent directories)".to_string(),
+            ));
+        }
+
+        let repo_path = self.repo_path.clone();
+        let watch_ignore = IgnoreMatcher::compile(&config.ignore_patterns)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let thread = thread::Builder::new()
+            .name("blaze-autocommit".to_string())
+            .spawn(move || run_autocommit_loop(repo_path, config, watch_ignore, thread_stop))
+            .context("Failed to spawn auto-commit thread")?;
+
+        Ok(AutoCommitHandle {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Body of the background thread started by [`Blaze::start_autocommit`]:
+/// opens its own [`Blaze`] handle (so it never shares `&mut self` with the
+/// caller), seeds an initial snapshot, then watches and debounces until
+/// [`AutoCommitHandle::stop`] is called
+fn run_autocommit_loop(
+    repo_path: PathBuf,
+    config: AutoCommitConfig,
+    watch_ignore: IgnoreMatcher,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let mut blaze = Blaze::new(&repo_path)?;
+
+    seed_and_commit(&mut blaze, &config, BTreeSet::new())?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |event| {
+        // A send error just means the loop below has already exited -
+        // nothing to recover from on this side.
+        let _ = tx.send(event);
+    })
+    .map_err(|e| BlazeError::FileSystem(e.to_string()))?;
+    watcher
+        .watch(&repo_path, RecursiveMode::Recursive)
+        .map_err(|e| BlazeError::FileSystem(e.to_string()))?;
+
+    let mut pending: BTreeSet<String> = BTreeSet::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match rx.recv_timeout(config.debounce) {
+            Ok(Ok(event)) => {
+                for path in event.paths {
+                    if let Some(relative) = relative_watched_path(&repo_path, &path) {
+                        if !watch_ignore.is_ignored(&relative) {
+                            pending.insert(relative);
+                        }
+                    }
+                }
+            }
+            Ok(Err(_)) => {
+                // A transient watch error (e.g. a path vanishing mid-event)
+                // isn't fatal - the next debounce tick still fires on
+                // whatever was already pending.
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if !pending.is_empty() {
+                    let changed = std::mem::take(&mut pending);
+                    seed_and_commit(&mut blaze, &config, changed)?;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Stage everything currently on disk and commit it if anything actually
+/// changed, recording `changed` (the paths the debounce window coalesced,
+/// empty for the initial seed commit) in the message. A `commit` that fails
+/// only because there was nothing to commit is swallowed rather than
+/// propagated, since a watch event can fire without leaving behind a
+/// persistent change (e.g. a file touched back to its original content).
+fn seed_and_commit(blaze: &mut Blaze, config: &AutoCommitConfig, changed: BTreeSet<String>) -> Result<()> {
+    blaze.add(Vec::new(), false, true, false)?;
+
+    let message = autocommit_message(config, &changed);
+    match blaze.commit(message, false, false, false) {
+        Ok(_) => Ok(()),
+        // A debounce window can coalesce down to zero actual changes (e.g. a
+        // save followed by an undo); only that specific, expected condition
+        // is swallowed - anything else (including a `.blaze` directory that
+        // vanished or got corrupted out from under the watcher) must still
+        // surface via `AutoCommitHandle::join`
+        Err(BlazeError::Repository(ref msg)) if msg.starts_with("No changes to commit") => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Build an auto-commit message recording which paths the debounce window
+/// saw change, prefixed with `[author]` if [`AutoCommitConfig::author`] is
+/// set
+fn autocommit_message(config: &AutoCommitConfig, changed: &BTreeSet<String>) -> String {
+    let prefix = config
+        .author
+        .as_ref()
+        .map(|author| format!("[{}] ", author))
+        .unwrap_or_default();
+
+    let body = if changed.is_empty() {
+        "Auto-commit: initial snapshot".to_string()
+    } else if changed.len() <= MAX_LISTED_PATHS {
+        format!(
+            "Auto-commit: {}",
+            changed.iter().cloned().collect::<Vec<_>>().join(", ")
+        )
+    } else {
+        format!("Auto-commit: {} paths changed", changed.len())
+    };
+
+    format!("{}{}", prefix, body)
+}
+
+/// `path`'s repo-relative form, in the same `/`-separated shape
+/// [`IgnoreMatcher`] and the database expect, or `None` for anything inside
+/// `.blaze` itself - the watcher's own writes would otherwise re-trigger it
+fn relative_watched_path(repo_path: &std::path::Path, path: &std::path::Path) -> Option<String> {
+    let relative = path.strip_prefix(repo_path).ok()?;
+    if relative.starts_with(BLAZE_DIR) {
+        return None;
+    }
+
+    Some(crate::utils::normalize_path(relative))
+}